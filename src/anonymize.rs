@@ -0,0 +1,448 @@
+//! Prefix-preserving anonymisation of the addresses of a capture with Crypto-PAn.
+//!
+//! [`CryptoPan`] maps the addresses consistently: two addresses sharing a prefix of n bits are mapped to
+//! two addresses sharing a prefix of n bits, and the same key always gives the same mapping.
+//!
+//! An [`Anonymizer`] rewrites the IPv4 and IPv6 addresses of the packets, fixing the IP, TCP, UDP and ICMPv6
+//! checksums, and the addresses of the interface description and name resolution blocks.
+//! [`PcapAnonymize`] and [`PcapNgAnonymize`] apply it to an [`Iterator`] of packets or blocks.
+//!
+//! This module requires the `anonymize` feature, which is not enabled by default.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::anonymize::{Anonymizer, PcapNgAnonymize};
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, blocks.section().clone()).unwrap();
+//!
+//! let key = [7_u8; 32];
+//! for block in PcapNgAnonymize::new(blocks, Anonymizer::new(&key)) {
+//!     pcapng_writer.write_block(&block.unwrap()).unwrap();
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes128;
+
+use crate::dissect::{Ipv6Header, LinkHeader, NetworkProtocol};
+use crate::errors::PcapError;
+use crate::pcap::PcapPacket;
+use crate::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use crate::pcapng::blocks::name_resolution::{NameResolutionBlock, NameResolutionOption, Record};
+use crate::pcapng::Block;
+use crate::DataLink;
+
+
+/// The Crypto-PAn prefix-preserving anonymisation function.
+///
+/// The first 16 bytes of the key are the AES key, the last 16 bytes are encrypted to give the padding.
+/// The IPv4 mapping is the one of the reference implementation, the IPv6 and MAC mappings extend it
+/// to 128 and 48 bits.
+#[derive(Clone)]
+pub struct CryptoPan {
+    cipher: Aes128,
+    pad: [u8; 16],
+}
+
+impl std::fmt::Debug for CryptoPan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't leak the key
+        f.debug_struct("CryptoPan").finish_non_exhaustive()
+    }
+}
+
+impl CryptoPan {
+    /// Creates a new [`CryptoPan`] from a 32 bytes key.
+    pub fn new(key: &[u8; 32]) -> Self {
+        let cipher = Aes128::new(GenericArray::from_slice(&key[..16]));
+
+        let mut pad = GenericArray::clone_from_slice(&key[16..]);
+        cipher.encrypt_block(&mut pad);
+
+        CryptoPan { cipher, pad: pad.into() }
+    }
+
+    /// Anonymises an IPv4 address.
+    pub fn anonymize_ipv4(&self, addr: Ipv4Addr) -> Ipv4Addr {
+        let mut octets = addr.octets();
+        self.anonymize_bytes(&mut octets);
+        Ipv4Addr::from(octets)
+    }
+
+    /// Anonymises an IPv6 address.
+    pub fn anonymize_ipv6(&self, addr: Ipv6Addr) -> Ipv6Addr {
+        let mut octets = addr.octets();
+        self.anonymize_bytes(&mut octets);
+        Ipv6Addr::from(octets)
+    }
+
+    /// Anonymises a MAC address.
+    pub fn anonymize_mac(&self, mut addr: [u8; 6]) -> [u8; 6] {
+        self.anonymize_bytes(&mut addr);
+        addr
+    }
+
+    /// Anonymises an address of at most 16 bytes in place.
+    ///
+    /// The bit n of the result is the bit n of the address xored with the first bit of the encryption of
+    /// the n first bits of the address followed by the padding.
+    fn anonymize_bytes(&self, addr: &mut [u8]) {
+        debug_assert!(addr.len() <= 16);
+
+        let mut input = GenericArray::from(self.pad);
+        let mut otp = [0_u8; 16];
+
+        for pos in 0..addr.len() * 8 {
+            let (byte, mask) = (pos / 8, 0x80 >> (pos % 8));
+
+            let mut output = input;
+            self.cipher.encrypt_block(&mut output);
+            if output[0] & 0x80 != 0 {
+                otp[byte] |= mask;
+            }
+
+            // The next input has one more bit of the address
+            input[byte] = (input[byte] & !mask) | (addr[byte] & mask);
+        }
+
+        for (a, o) in addr.iter_mut().zip(otp) {
+            *a ^= o;
+        }
+    }
+}
+
+
+/// Rewrites the addresses of the packets and of the metadata of a capture with [`CryptoPan`].
+///
+/// The mappings are cached, so an address is only encrypted once.
+#[derive(Clone, Debug)]
+pub struct Anonymizer {
+    cryptopan: CryptoPan,
+    /// Also anonymise the MAC addresses of the Ethernet headers and of the ARP packets
+    pub anonymize_macs: bool,
+    ipv4_cache: HashMap<[u8; 4], [u8; 4]>,
+    ipv6_cache: HashMap<[u8; 16], [u8; 16]>,
+    mac_cache: HashMap<[u8; 6], [u8; 6]>,
+}
+
+impl Anonymizer {
+    /// Creates a new [`Anonymizer`] anonymising the IP and MAC addresses with the given Crypto-PAn key.
+    pub fn new(key: &[u8; 32]) -> Self {
+        Anonymizer {
+            cryptopan: CryptoPan::new(key),
+            anonymize_macs: true,
+            ipv4_cache: HashMap::new(),
+            ipv6_cache: HashMap::new(),
+            mac_cache: HashMap::new(),
+        }
+    }
+
+    /// Anonymises an IPv4 address in place, ignoring the slices of another length.
+    fn ipv4(&mut self, addr: &mut [u8]) {
+        let Ok(key) = <[u8; 4]>::try_from(&*addr)
+        else {
+            return;
+        };
+        let cryptopan = &self.cryptopan;
+        addr.copy_from_slice(self.ipv4_cache.entry(key).or_insert_with(|| cryptopan.anonymize_ipv4(key.into()).octets()));
+    }
+
+    /// Anonymises an IPv6 address in place, ignoring the slices of another length.
+    fn ipv6(&mut self, addr: &mut [u8]) {
+        let Ok(key) = <[u8; 16]>::try_from(&*addr)
+        else {
+            return;
+        };
+        let cryptopan = &self.cryptopan;
+        addr.copy_from_slice(self.ipv6_cache.entry(key).or_insert_with(|| cryptopan.anonymize_ipv6(key.into()).octets()));
+    }
+
+    /// Anonymises a MAC address in place, ignoring the slices of another length.
+    fn mac(&mut self, addr: &mut [u8]) {
+        let Ok(key) = <[u8; 6]>::try_from(&*addr)
+        else {
+            return;
+        };
+        let cryptopan = &self.cryptopan;
+        addr.copy_from_slice(self.mac_cache.entry(key).or_insert_with(|| cryptopan.anonymize_mac(key)));
+    }
+
+    /// Anonymises the addresses of a packet in place.
+    ///
+    /// The supported link types are the ones of [`LinkHeader::parse`].
+    /// The IPv4 and IPv6 headers, the ARP packets and, if enabled, the Ethernet addresses are rewritten.
+    /// The checksums are updated incrementally, so truncated packets keep valid checksums.
+    pub fn anonymize_packet(&mut self, linktype: DataLink, data: &mut [u8]) {
+        let Some(link) = LinkHeader::parse(linktype, data)
+        else {
+            return;
+        };
+
+        if linktype == DataLink::ETHERNET && self.anonymize_macs && data.len() >= 12 {
+            self.mac(&mut data[0..6]);
+            self.mac(&mut data[6..12]);
+        }
+
+        let network = data.get_mut(link.payload_offset..).unwrap_or_default();
+        match link.protocol {
+            NetworkProtocol::Ipv4 => self.anonymize_ipv4_packet(network),
+            NetworkProtocol::Ipv6 => self.anonymize_ipv6_packet(network),
+            NetworkProtocol::Arp => self.anonymize_arp_packet(network),
+            _ => {},
+        }
+    }
+
+    /// Anonymises an IPv4 packet and fixes its checksums.
+    fn anonymize_ipv4_packet(&mut self, data: &mut [u8]) {
+        if data.len() < 20 {
+            return;
+        }
+
+        let old: [u8; 8] = data[12..20].try_into().unwrap();
+        self.ipv4(&mut data[12..16]);
+        self.ipv4(&mut data[16..20]);
+        let new: [u8; 8] = data[12..20].try_into().unwrap();
+
+        update_checksum(&mut data[10..12], &old, &new);
+
+        // Only the first fragment holds the transport header
+        let header_len = (data[0] & 0x0F) as usize * 4;
+        if u16::from_be_bytes([data[6], data[7]]) & 0x1FFF == 0 {
+            let protocol = data[9];
+            update_transport_checksum(protocol, data.get_mut(header_len..).unwrap_or_default(), &old, &new, true);
+        }
+    }
+
+    /// Anonymises an IPv6 packet and fixes its checksums.
+    fn anonymize_ipv6_packet(&mut self, data: &mut [u8]) {
+        if data.len() < 40 {
+            return;
+        }
+
+        let old: [u8; 32] = data[8..40].try_into().unwrap();
+        self.ipv6(&mut data[8..24]);
+        self.ipv6(&mut data[24..40]);
+        let new: [u8; 32] = data[8..40].try_into().unwrap();
+
+        let Some(ip) = Ipv6Header::parse(data)
+        else {
+            return;
+        };
+
+        // Only the first fragment holds the transport header
+        if ip.fragment_offset() == 0 {
+            let (protocol, header_len) = (ip.protocol(), ip.header_len());
+            update_transport_checksum(protocol, &mut data[header_len..], &old, &new, false);
+        }
+    }
+
+    /// Anonymises the addresses of an Ethernet/IPv4 ARP packet.
+    fn anonymize_arp_packet(&mut self, data: &mut [u8]) {
+        // Hardware type Ethernet, protocol type IPv4, 6 bytes hardware addresses and 4 bytes protocol addresses
+        if data.len() < 28 || data[0..6] != [0, 1, 0x08, 0, 6, 4] {
+            return;
+        }
+
+        if self.anonymize_macs {
+            self.mac(&mut data[8..14]);
+            self.mac(&mut data[18..24]);
+        }
+        self.ipv4(&mut data[14..18]);
+        self.ipv4(&mut data[24..28]);
+    }
+
+    /// Anonymises the `if_IPv4addr`, `if_IPv6addr` and, if enabled, `if_MACaddr` options of an interface.
+    pub fn anonymize_interface(&mut self, interface: &mut InterfaceDescriptionBlock) {
+        for opt in &mut interface.options {
+            match opt {
+                // Address followed by the netmask
+                InterfaceDescriptionOption::IfIpv4Addr(addr) if addr.len() == 8 => self.ipv4(&mut addr.to_mut()[..4]),
+                // Address followed by the prefix length
+                InterfaceDescriptionOption::IfIpv6Addr(addr) if addr.len() == 17 => self.ipv6(&mut addr.to_mut()[..16]),
+                InterfaceDescriptionOption::IfMacAddr(addr) if self.anonymize_macs => self.mac(addr.to_mut()),
+                _ => {},
+            }
+        }
+    }
+
+    /// Anonymises the IPv4 and IPv6 records and the DNS server addresses of a name resolution block.
+    ///
+    /// The names are kept.
+    pub fn anonymize_name_resolution(&mut self, name_resolution: &mut NameResolutionBlock) {
+        for record in &mut name_resolution.records {
+            match record {
+                Record::Ipv4(record) => self.ipv4(record.ip_addr.to_mut()),
+                Record::Ipv6(record) => self.ipv6(record.ip_addr.to_mut()),
+                _ => {},
+            }
+        }
+
+        for opt in &mut name_resolution.options {
+            match opt {
+                NameResolutionOption::NsDnsIpv4Addr(addr) => self.ipv4(addr.to_mut()),
+                NameResolutionOption::NsDnsIpv6Addr(addr) => self.ipv6(addr.to_mut()),
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Updates a checksum after some 16-bit aligned bytes covered by it changed, see RFC 1624.
+fn update_checksum(checksum: &mut [u8], old: &[u8], new: &[u8]) {
+    let mut sum = !u16::from_be_bytes([checksum[0], checksum[1]]) as u32;
+    for (old, new) in old.chunks_exact(2).zip(new.chunks_exact(2)) {
+        sum += !u16::from_be_bytes([old[0], old[1]]) as u32;
+        sum += u16::from_be_bytes([new[0], new[1]]) as u32;
+    }
+
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+
+    checksum.copy_from_slice(&(!(sum as u16)).to_be_bytes());
+}
+
+/// Updates the checksum of a TCP, UDP or ICMPv6 header after the addresses of its pseudo header changed.
+fn update_transport_checksum(protocol: u8, data: &mut [u8], old: &[u8], new: &[u8], ipv4: bool) {
+    let offset = match protocol {
+        6 => 16,
+        17 => 6,
+        58 if !ipv4 => 2,
+        _ => return,
+    };
+
+    let Some(checksum) = data.get_mut(offset..offset + 2)
+    else {
+        return;
+    };
+
+    // A null UDP checksum means no checksum over IPv4
+    if protocol == 17 && checksum == [0, 0] {
+        return;
+    }
+
+    update_checksum(checksum, old, new);
+
+    // A computed UDP checksum of 0 is transmitted as all ones
+    if protocol == 17 && checksum == [0, 0] {
+        checksum.copy_from_slice(&[0xFF, 0xFF]);
+    }
+}
+
+
+/// [`Iterator`] adapter anonymising [`PcapPacket`].
+///
+/// Only the addresses of the outer IP header and of the ARP packets are rewritten. The addresses embedded in the
+/// payloads are left untouched, in particular the IP header quoted by the ICMP and ICMPv6 error messages and the
+/// target address of the NDP messages, so they can reveal real addresses.
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapAnonymize<I> {
+    inner: I,
+    anonymizer: Anonymizer,
+    datalink: DataLink,
+}
+
+impl<I> PcapAnonymize<I> {
+    /// Creates a new [`PcapAnonymize`] over an iterator of packets of the given [`DataLink`].
+    pub fn new(inner: I, datalink: DataLink, anonymizer: Anonymizer) -> Self {
+        PcapAnonymize { inner, anonymizer, datalink }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapAnonymize<I> {
+    type Item = Result<PcapPacket<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = match self.inner.next()? {
+            Ok(packet) => packet,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let (timestamp, orig_len) = (packet.timestamp(), packet.orig_len());
+        let mut data = packet.into_data();
+        self.anonymizer.anonymize_packet(self.datalink, data.to_mut());
+
+        Some(PcapPacket::new(timestamp, orig_len, data))
+    }
+}
+
+
+/// [`Iterator`] adapter anonymising the packet, interface description and name resolution blocks.
+///
+/// The addresses embedded in the payloads of the packets are left untouched, see [`PcapAnonymize`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgAnonymize<I> {
+    inner: I,
+    anonymizer: Anonymizer,
+    /// Link types and snaplens of the interfaces of the current section
+    interfaces: Vec<(DataLink, u32)>,
+}
+
+impl<I> PcapNgAnonymize<I> {
+    /// Creates a new [`PcapNgAnonymize`] over an iterator of blocks.
+    pub fn new(inner: I, anonymizer: Anonymizer) -> Self {
+        PcapNgAnonymize { inner, anonymizer, interfaces: vec![] }
+    }
+
+    /// Anonymises a block.
+    fn anonymize_block(&mut self, block: &mut Block) -> Result<(), PcapError> {
+        match block {
+            Block::SectionHeader(_) => self.interfaces.clear(),
+            Block::InterfaceDescription(blk) => {
+                self.interfaces.push((blk.linktype, blk.snaplen));
+                self.anonymizer.anonymize_interface(blk);
+            },
+            Block::NameResolution(blk) => self.anonymizer.anonymize_name_resolution(blk),
+            Block::EnhancedPacket(blk) => {
+                let linktype = self.linktype(blk.interface_id)?;
+                self.anonymizer.anonymize_packet(linktype, blk.data.to_mut());
+            },
+            Block::Packet(blk) => {
+                let linktype = self.linktype(blk.interface_id as u32)?;
+                self.anonymizer.anonymize_packet(linktype, blk.data.to_mut());
+            },
+            Block::SimplePacket(blk) => {
+                let linktype = self.linktype(0)?;
+                let len = blk.packet_data(self.interfaces[0].1).len();
+                self.anonymizer.anonymize_packet(linktype, &mut blk.data.to_mut()[..len]);
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    /// Returns the link type of an interface.
+    fn linktype(&self, interface_id: u32) -> Result<DataLink, PcapError> {
+        self.interfaces
+            .get(interface_id as usize)
+            .map(|(linktype, _)| *linktype)
+            .ok_or(PcapError::InvalidInterfaceId(interface_id))
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgAnonymize<I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block = match self.inner.next()? {
+            Ok(block) => block,
+            Err(e) => return Some(Err(e)),
+        };
+
+        Some(self.anonymize_block(&mut block).map(|_| block))
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use byteorder_slice::byteorder::WriteBytesExt;
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::BigEndian;
+
+use crate::errors::*;
+
+
+/// Identification pattern at the start of a btsnoop file
+const BTSNOOP_MAGIC: &[u8; 8] = b"btsnoop\0";
+
+/// Btsnoop Global Header
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BtsnoopHeader {
+    /// Version number, should always be 1
+    pub version: u32,
+
+    /// DataLink type (encapsulation of the HCI packets)
+    pub datalink: BtsnoopDataLink,
+}
+
+impl BtsnoopHeader {
+    /// Creates a new [`BtsnoopHeader`] from a slice of bytes.
+    ///
+    /// Returns an error if the slice doesn't start with a valid btsnoop header.
+    ///
+    /// [`PcapError::IncompleteBuffer`] indicates that there is not enough data in the buffer.
+    pub fn from_slice(mut slice: &[u8]) -> PcapResult<(&[u8], BtsnoopHeader)> {
+        if slice.len() < 16 {
+            return Err(PcapError::IncompleteBuffer(16, slice.len()));
+        }
+
+        if &slice[..8] != BTSNOOP_MAGIC {
+            return Err(PcapError::InvalidField("BtsnoopHeader: wrong identification pattern"));
+        }
+        slice = &slice[8..];
+
+        let header = BtsnoopHeader {
+            version: slice.read_u32::<BigEndian>().unwrap(),
+            datalink: BtsnoopDataLink::from(slice.read_u32::<BigEndian>().unwrap()),
+        };
+
+        Ok((slice, header))
+    }
+
+    /// Writes a [`BtsnoopHeader`] to a writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> PcapResult<usize> {
+        writer.write_all(BTSNOOP_MAGIC).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.version).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.datalink.into()).map_err(PcapError::IoError)?;
+
+        Ok(16)
+    }
+}
+
+/// Creates a new [`BtsnoopHeader`] with these parameters:
+///
+/// ```rust,ignore
+/// BtsnoopHeader {
+///     version: 1,
+///     datalink: BtsnoopDataLink::H4,
+/// };
+/// ```
+impl Default for BtsnoopHeader {
+    fn default() -> Self {
+        BtsnoopHeader { version: 1, datalink: BtsnoopDataLink::H4 }
+    }
+}
+
+/// Data link type of a btsnoop file.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BtsnoopDataLink {
+    /// Unencapsulated HCI (H1), the packet type is given by the flags
+    H1,
+    /// HCI UART (H4), the packets start with their packet type
+    H4,
+    /// HCI BCSP
+    BCSP,
+    /// HCI Serial (H5)
+    H5,
+    /// Linux Bluetooth monitor
+    LINUX_MONITOR,
+    /// Simulator
+    SIMULATOR,
+    /// Unknown data link
+    Unknown(u32),
+}
+
+impl From<u32> for BtsnoopDataLink {
+    fn from(n: u32) -> BtsnoopDataLink {
+        match n {
+            1001 => BtsnoopDataLink::H1,
+            1002 => BtsnoopDataLink::H4,
+            1003 => BtsnoopDataLink::BCSP,
+            1004 => BtsnoopDataLink::H5,
+            2001 => BtsnoopDataLink::LINUX_MONITOR,
+            2002 => BtsnoopDataLink::SIMULATOR,
+            _ => BtsnoopDataLink::Unknown(n),
+        }
+    }
+}
+
+impl From<BtsnoopDataLink> for u32 {
+    fn from(link: BtsnoopDataLink) -> u32 {
+        match link {
+            BtsnoopDataLink::H1 => 1001,
+            BtsnoopDataLink::H4 => 1002,
+            BtsnoopDataLink::BCSP => 1003,
+            BtsnoopDataLink::H5 => 1004,
+            BtsnoopDataLink::LINUX_MONITOR => 2001,
+            BtsnoopDataLink::SIMULATOR => 2002,
+            BtsnoopDataLink::Unknown(n) => n,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Contains the btsnoop (Bluetooth HCI) parser, reader and writer
+
+mod header;
+mod packet;
+mod parser;
+mod reader;
+mod writer;
+
+pub use header::*;
+pub use packet::*;
+pub use parser::*;
+pub use reader::*;
+pub use writer::*;
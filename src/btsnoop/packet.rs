@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::io::Write;
+use std::time::Duration;
+
+use byteorder_slice::byteorder::WriteBytesExt;
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::BigEndian;
+
+use crate::errors::*;
+
+
+/// Length of the header of a btsnoop packet record
+const RECORD_HEADER_LEN: usize = 24;
+
+/// Number of microseconds between midnight, January 1st, 0 AD and the EPOCH
+const EPOCH_OFFSET_MICROS: u64 = 0x00DC_DDB3_0F2F_8000;
+
+/// A valid btsnoop packet.
+///
+/// The payload can be owned or borrowed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BtsnoopPacket<'a> {
+    /// Timestamp EPOCH of the packet, with a microsecond resolution
+    timestamp: Duration,
+    /// Original length of the packet
+    orig_len: u32,
+    /// Direction and command/event flags
+    flags: u32,
+    /// Number of packets dropped since the start of the capture
+    cumulative_drops: u32,
+    /// Payload, owned or borrowed, of the packet
+    data: Cow<'a, [u8]>,
+}
+
+impl<'a> BtsnoopPacket<'a> {
+    /// Direction flag, set if the packet was received by the host, cleared if it was sent
+    pub const RECEIVED: u32 = 0x01;
+    /// Command/event flag, set if the packet is a command or an event, cleared if it is data
+    pub const COMMAND_OR_EVENT: u32 = 0x02;
+
+    /// Creates a new [`BtsnoopPacket`] with the given parameters.
+    ///
+    /// The sub-microsecond part of the timestamp is truncated when the packet is written.
+    pub fn new(timestamp: Duration, orig_len: u32, flags: u32, cumulative_drops: u32, data: impl Into<Cow<'a, [u8]>>) -> PcapResult<BtsnoopPacket<'a>> {
+        let data = data.into();
+
+        // Validate inputs //
+        if timestamp.as_micros() > (i64::MAX as u64 - EPOCH_OFFSET_MICROS) as u128 {
+            return Err(PcapError::InvalidField("timestamp too big"));
+        }
+
+        let Ok(incl_len): Result<u32, _> = data.len().try_into() else {
+            return Err(PcapError::InvalidField("data_len > u32::MAX"));
+        };
+
+        if incl_len > orig_len {
+            return Err(PcapError::InvalidField("orig_len < data_len"));
+        }
+
+        Ok(BtsnoopPacket { timestamp, orig_len, flags, cumulative_drops, data })
+    }
+
+    /// Parses a new borrowed [`BtsnoopPacket`] from a slice.
+    pub fn from_slice(mut slice: &'a [u8]) -> PcapResult<(&'a [u8], Self)> {
+        // Check header length
+        if slice.len() < RECORD_HEADER_LEN {
+            return Err(PcapError::IncompleteBuffer(RECORD_HEADER_LEN, slice.len()));
+        }
+
+        // Read record header //
+        // Can unwrap because the length check is done before
+        let orig_len = slice.read_u32::<BigEndian>().unwrap();
+        let incl_len = slice.read_u32::<BigEndian>().unwrap();
+        let flags = slice.read_u32::<BigEndian>().unwrap();
+        let cumulative_drops = slice.read_u32::<BigEndian>().unwrap();
+        let ts_micros = slice.read_i64::<BigEndian>().unwrap();
+
+        // Validate fields //
+        if incl_len > orig_len {
+            return Err(PcapError::InvalidField("BtsnoopPacket: incl_len > orig_len"));
+        }
+
+        let Some(ts_micros) = u64::try_from(ts_micros).ok().and_then(|ts| ts.checked_sub(EPOCH_OFFSET_MICROS))
+        else {
+            return Err(PcapError::InvalidField("BtsnoopPacket: timestamp before the EPOCH"));
+        };
+
+        let pkt_len = incl_len as usize;
+        if slice.len() < pkt_len {
+            return Err(PcapError::IncompleteBuffer(pkt_len, slice.len()));
+        }
+
+        let packet = BtsnoopPacket {
+            timestamp: Duration::from_micros(ts_micros),
+            orig_len,
+            flags,
+            cumulative_drops,
+            data: Cow::Borrowed(&slice[..pkt_len]),
+        };
+
+        Ok((&slice[pkt_len..], packet))
+    }
+
+    /// Writes a [`BtsnoopPacket`] to a writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> PcapResult<usize> {
+        let ts_micros = self.timestamp.as_micros() as u64 + EPOCH_OFFSET_MICROS;
+
+        writer.write_u32::<BigEndian>(self.orig_len).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.len()).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.flags).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.cumulative_drops).map_err(PcapError::IoError)?;
+        writer.write_i64::<BigEndian>(ts_micros as i64).map_err(PcapError::IoError)?;
+        writer.write_all(&self.data).map_err(PcapError::IoError)?;
+
+        Ok(RECORD_HEADER_LEN + self.data.len())
+    }
+
+    /// Returns the packet timestamp.
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Returns the original length of the packet.
+    pub fn orig_len(&self) -> u32 {
+        self.orig_len
+    }
+
+    /// Returns the flags of the packet, see the associated constants.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns true if the packet was received by the host, false if it was sent.
+    pub fn is_received(&self) -> bool {
+        self.flags & Self::RECEIVED != 0
+    }
+
+    /// Returns true if the packet is a command or an event, false if it is data.
+    pub fn is_command_or_event(&self) -> bool {
+        self.flags & Self::COMMAND_OR_EVENT != 0
+    }
+
+    /// Returns the number of packets dropped between the start of the capture and this packet.
+    pub fn cumulative_drops(&self) -> u32 {
+        self.cumulative_drops
+    }
+
+    /// Returns the length of the packet.
+    pub fn len(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    /// Returns true if the packet has no payload.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the packet payload as a slice.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the packet payload.
+    pub fn into_data(self) -> Cow<'a, [u8]> {
+        self.data
+    }
+
+    /// Returns an owned version of the packet.
+    pub fn into_owned(self) -> BtsnoopPacket<'static> {
+        BtsnoopPacket {
+            timestamp: self.timestamp,
+            orig_len: self.orig_len,
+            flags: self.flags,
+            cumulative_drops: self.cumulative_drops,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
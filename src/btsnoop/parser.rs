@@ -0,0 +1,62 @@
+use crate::errors::*;
+use crate::btsnoop::{BtsnoopHeader, BtsnoopPacket};
+
+/// Parses a btsnoop from a slice of bytes.
+///
+/// You can match on [`PcapError::IncompleteBuffer`](crate::errors::PcapError) to known if the parser need more data.
+///
+/// # Example
+/// ```no_run
+/// use pcap_file::btsnoop::BtsnoopParser;
+/// use pcap_file::PcapError;
+///
+/// let btsnoop = vec![0_u8; 0];
+/// let mut src = &btsnoop[..];
+///
+/// // Creates a new parser and parse the btsnoop header
+/// let (rem, btsnoop_parser) = BtsnoopParser::new(&btsnoop[..]).unwrap();
+/// src = rem;
+///
+/// loop {
+///     match btsnoop_parser.next_packet(src) {
+///         Ok((rem, packet)) => {
+///             // Do something
+///
+///             // Don't forget to update src
+///             src = rem;
+///
+///             // No more data, if no more incoming either then this is the end of the file
+///             if rem.is_empty() {
+///                 break;
+///             }
+///         },
+///         Err(PcapError::IncompleteBuffer(_,_)) => {}, // Load more data into src
+///         Err(_) => {},                           // Parsing error
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BtsnoopParser {
+    header: BtsnoopHeader,
+}
+
+impl BtsnoopParser {
+    /// Creates a new [`BtsnoopParser`].
+    ///
+    /// Returns the remainder and the parser.
+    pub fn new(slice: &[u8]) -> PcapResult<(&[u8], BtsnoopParser)> {
+        let (slice, header) = BtsnoopHeader::from_slice(slice)?;
+        let parser = BtsnoopParser { header };
+        Ok((slice, parser))
+    }
+
+    /// Returns the remainder and the next [`BtsnoopPacket`].
+    pub fn next_packet<'a>(&self, slice: &'a [u8]) -> PcapResult<(&'a [u8], BtsnoopPacket<'a>)> {
+        BtsnoopPacket::from_slice(slice)
+    }
+
+    /// Returns the header of the btsnoop file.
+    pub fn header(&self) -> BtsnoopHeader {
+        self.header
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::Read;
+
+use super::BtsnoopParser;
+use crate::errors::*;
+use crate::read_buffer::ReadBuffer;
+use crate::btsnoop::{BtsnoopHeader, BtsnoopPacket};
+
+/// Reads a btsnoop from a reader.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::btsnoop::BtsnoopReader;
+///
+/// let file_in = File::open("test.btsnoop").expect("Error opening file");
+/// let mut btsnoop_reader = BtsnoopReader::new(file_in).unwrap();
+///
+/// // Read test.btsnoop
+/// while let Some(pkt) = btsnoop_reader.next_packet() {
+///     //Check if there is no error
+///     let pkt = pkt.unwrap();
+///
+///     //Do something
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BtsnoopReader<R: Read> {
+    parser: BtsnoopParser,
+    reader: ReadBuffer<R>,
+}
+
+impl<R: Read> BtsnoopReader<R> {
+    /// Creates a new [`BtsnoopReader`] from an existing reader.
+    ///
+    /// This function reads the btsnoop file header to verify its integrity.
+    ///
+    /// The underlying reader must point to a valid btsnoop file/stream.
+    ///
+    /// # Errors
+    /// The data stream is not in a valid btsnoop file format.
+    ///
+    /// The underlying data are not readable.
+    pub fn new(reader: R) -> Result<BtsnoopReader<R>, PcapError> {
+        let mut reader = ReadBuffer::new(reader);
+        let parser = reader.parse_with(BtsnoopParser::new)?;
+
+        Ok(BtsnoopReader { parser, reader })
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Returns the next [`BtsnoopPacket`].
+    pub fn next_packet(&mut self) -> Option<Result<BtsnoopPacket<'_>, PcapError>> {
+        match self.reader.has_data_left() {
+            Ok(has_data) => {
+                if has_data {
+                    Some(self.reader.parse_with(|src| self.parser.next_packet(src)))
+                } else {
+                    None
+                }
+            },
+            Err(e) => Some(Err(PcapError::IoError(e))),
+        }
+    }
+
+    /// Returns the header of the btsnoop.
+    pub fn header(&self) -> BtsnoopHeader {
+        self.parser.header()
+    }
+
+    /// Converts the reader into an [`Iterator`] of owned [`BtsnoopPacket`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_packets(self) -> BtsnoopPackets<R> {
+        BtsnoopPackets { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned packets of a [`BtsnoopReader`], created by [`BtsnoopReader::into_packets`].
+#[derive(Debug)]
+pub struct BtsnoopPackets<R: Read> {
+    reader: BtsnoopReader<R>,
+    done: bool,
+}
+
+impl<R: Read> BtsnoopPackets<R> {
+    /// Returns the header of the btsnoop.
+    pub fn header(&self) -> BtsnoopHeader {
+        self.reader.header()
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> BtsnoopReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for BtsnoopPackets<R> {
+    type Item = Result<BtsnoopPacket<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_packet()?.map(BtsnoopPacket::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
+}
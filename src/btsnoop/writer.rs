@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use crate::errors::*;
+use crate::btsnoop::{BtsnoopDataLink, BtsnoopHeader, BtsnoopPacket};
+
+
+/// Writes a btsnoop to a writer.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::btsnoop::{BtsnoopReader, BtsnoopWriter};
+///
+/// let file_in = File::open("test.btsnoop").expect("Error opening file");
+/// let mut btsnoop_reader = BtsnoopReader::new(file_in).unwrap();
+///
+/// let file_out = File::create("out.btsnoop").expect("Error creating file out");
+/// let mut btsnoop_writer = BtsnoopWriter::with_header(file_out, btsnoop_reader.header()).expect("Error writing file");
+///
+/// // Read test.btsnoop
+/// while let Some(pkt) = btsnoop_reader.next_packet() {
+///     //Check if there is no error
+///     let pkt = pkt.unwrap();
+///
+///     //Write each packet of test.btsnoop in out.btsnoop
+///     btsnoop_writer.write_packet(&pkt).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BtsnoopWriter<W: Write> {
+    datalink: BtsnoopDataLink,
+    writer: W,
+}
+
+impl<W: Write> BtsnoopWriter<W> {
+    /// Creates a new [`BtsnoopWriter`] from an existing writer.
+    ///
+    /// Writes this default btsnoop header to the file:
+    /// ```rust, ignore
+    /// BtsnoopHeader {
+    ///     version: 1,
+    ///     datalink: BtsnoopDataLink::H4,
+    /// };
+    /// ```
+    ///
+    /// # Errors
+    /// The writer can't be written to.
+    pub fn new(writer: W) -> PcapResult<BtsnoopWriter<W>> {
+        BtsnoopWriter::with_header(writer, BtsnoopHeader::default())
+    }
+
+    /// Creates a new [`BtsnoopWriter`] from an existing writer with a user defined [`BtsnoopHeader`].
+    ///
+    /// It also writes the btsnoop header to the file.
+    ///
+    /// # Errors
+    /// The writer can't be written to.
+    pub fn with_header(mut writer: W, header: BtsnoopHeader) -> PcapResult<BtsnoopWriter<W>> {
+        header.write_to(&mut writer)?;
+
+        Ok(BtsnoopWriter { datalink: header.datalink, writer })
+    }
+
+    /// Consumes [`Self`], returning the wrapped writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Writes a [`BtsnoopPacket`].
+    pub fn write_packet(&mut self, packet: &BtsnoopPacket) -> PcapResult<usize> {
+        packet.write_to(&mut self.writer)
+    }
+
+    /// Flush data
+    pub fn flush(&mut self) -> PcapResult<()> {
+        self.writer.flush().map_err(PcapError::IoError)
+    }
+
+    /// Returns the data link type written in the header.
+    pub fn datalink(&self) -> BtsnoopDataLink {
+        self.datalink
+    }
+}
@@ -0,0 +1,405 @@
+//! Reading and writing of the Linux `candump -l` log format.
+//!
+//! Each line of a candump log is a [`CandumpEntry`]: a timestamp, an interface name like `can0` and a [`CanFrame`],
+//! optionally followed by the direction of the frame:
+//! ```text
+//! (1436509052.249713) vcan0 44C#44C9A95B
+//! (1436509052.449713) vcan0 12345678##1DEADBEEF R
+//! ```
+//!
+//! A [`CanFrame`] can be encoded in the [`DataLink::CAN_SOCKETCAN`](crate::DataLink::CAN_SOCKETCAN) format of the
+//! pcap captures. To convert a log into a PcapNg, or the other way around, see
+//! [`candump_to_pcapng`](crate::convert::candump_to_pcapng) and [`pcapng_to_candump`](crate::convert::pcapng_to_candump).
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! use pcap_file::candump::CandumpReader;
+//!
+//! let file_in = File::open("candump.log").expect("Error opening file");
+//! let mut candump_reader = CandumpReader::new(BufReader::new(file_in));
+//!
+//! while let Some(entry) = candump_reader.next_entry() {
+//!     let entry = entry.unwrap();
+//!     println!("{} {:X}: {:?}", entry.interface, entry.frame.id(), entry.frame.data);
+//! }
+//! ```
+
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use crate::errors::{PcapError, PcapResult};
+
+
+/// Valid data lengths of a CAN FD frame
+const CANFD_LENGTHS: [usize; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// A classic CAN or CAN FD frame, with the fields of the Linux SocketCAN structures.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CanFrame {
+    /// Identifier and flags, see the associated constants
+    pub can_id: u32,
+    /// Flags of a CAN FD frame, see the associated constants, [`None`] for a classic CAN frame
+    pub fd_flags: Option<u8>,
+    /// Data length code of a classic CAN frame with 8 bytes of data, if it is between 9 and 15, 0 otherwise
+    pub len8_dlc: u8,
+    /// Data of the frame, its requested length for a remote frame
+    pub data: Vec<u8>,
+}
+
+impl CanFrame {
+    /// Extended frame format flag of the identifier
+    pub const EFF_FLAG: u32 = 0x80000000;
+    /// Remote transmission request flag of the identifier
+    pub const RTR_FLAG: u32 = 0x40000000;
+    /// Error frame flag of the identifier
+    pub const ERR_FLAG: u32 = 0x20000000;
+    /// Bit rate switch flag of a CAN FD frame
+    pub const BRS: u8 = 0x01;
+    /// Error state indicator flag of a CAN FD frame
+    pub const ESI: u8 = 0x02;
+    /// Flag of the SocketCAN header marking a CAN FD frame
+    pub const FDF: u8 = 0x04;
+
+    /// Returns the identifier, without the flags.
+    pub fn id(&self) -> u32 {
+        if self.is_extended() || self.is_error() {
+            self.can_id & 0x1FFFFFFF
+        }
+        else {
+            self.can_id & 0x7FF
+        }
+    }
+
+    /// Returns true if the frame has a 29-bit identifier.
+    pub fn is_extended(&self) -> bool {
+        self.can_id & Self::EFF_FLAG != 0
+    }
+
+    /// Returns true if the frame is a remote transmission request.
+    pub fn is_remote(&self) -> bool {
+        self.can_id & Self::RTR_FLAG != 0
+    }
+
+    /// Returns true if the frame is an error frame.
+    pub fn is_error(&self) -> bool {
+        self.can_id & Self::ERR_FLAG != 0
+    }
+
+    /// Returns true if the frame is a CAN FD frame.
+    pub fn is_fd(&self) -> bool {
+        self.fd_flags.is_some()
+    }
+
+    /// Parses a frame written by `candump`, like `123#DEADBEEF`, `12345678##1DEADBEEF` or `123#R4`.
+    pub fn parse(s: &str) -> PcapResult<Self> {
+        let Some((id, rest)) = s.split_once('#')
+        else {
+            return Err(PcapError::InvalidField("CanFrame: missing '#'"));
+        };
+
+        let Ok(mut can_id) = u32::from_str_radix(id, 16)
+        else {
+            return Err(PcapError::InvalidField("CanFrame: invalid identifier"));
+        };
+
+        match id.len() {
+            3 if can_id <= 0x7FF => {},
+            8 if can_id & Self::ERR_FLAG != 0 => can_id &= Self::ERR_FLAG | 0x1FFFFFFF,
+            8 if can_id <= 0x1FFFFFFF => can_id |= Self::EFF_FLAG,
+            _ => return Err(PcapError::InvalidField("CanFrame: invalid identifier")),
+        }
+
+        let mut frame = CanFrame { can_id, ..Default::default() };
+
+        if let Some(rest) = rest.strip_prefix('#') {
+            // CAN FD frame
+            let mut chars = rest.chars();
+            let Some(flags) = chars.next().and_then(|c| c.to_digit(16))
+            else {
+                return Err(PcapError::InvalidField("CanFrame: missing CAN FD flags"));
+            };
+
+            frame.fd_flags = Some(flags as u8);
+            frame.data = parse_hex(chars.as_str())?;
+            if !CANFD_LENGTHS.contains(&frame.data.len()) {
+                return Err(PcapError::InvalidField("CanFrame: invalid CAN FD data length"));
+            }
+        }
+        else if let Some(rest) = rest.strip_prefix('R') {
+            // Remote frame, with an optional requested length
+            frame.can_id |= Self::RTR_FLAG;
+
+            let (len, len8_dlc) = rest.split_once('_').unwrap_or((rest, ""));
+            let len = match len {
+                "" => 0,
+                len => u8::from_str_radix(len, 16).ok().filter(|len| *len <= 8).ok_or(PcapError::InvalidField("CanFrame: invalid remote frame length"))?,
+            };
+            frame.data = vec![0; len as usize];
+            frame.len8_dlc = parse_len8_dlc(len8_dlc, len as usize)?;
+        }
+        else {
+            let (data, len8_dlc) = rest.split_once('_').unwrap_or((rest, ""));
+            frame.data = parse_hex(data)?;
+            if frame.data.len() > 8 {
+                return Err(PcapError::InvalidField("CanFrame: classic CAN data length > 8"));
+            }
+            frame.len8_dlc = parse_len8_dlc(len8_dlc, frame.data.len())?;
+        }
+
+        Ok(frame)
+    }
+
+    /// Encodes the frame in the [`DataLink::CAN_SOCKETCAN`](crate::DataLink::CAN_SOCKETCAN) format: the identifier
+    /// in big endian, the data length, the CAN FD flags, a reserved byte, the data length code of classic CAN and
+    /// the data.
+    pub fn to_socketcan(&self) -> Vec<u8> {
+        let data_len = if self.is_remote() { 0 } else { self.data.len() };
+
+        let mut buf = Vec::with_capacity(8 + data_len);
+        buf.extend_from_slice(&self.can_id.to_be_bytes());
+        buf.push(self.data.len() as u8);
+        buf.push(self.fd_flags.map_or(0, |flags| flags | Self::FDF));
+        buf.push(0);
+        buf.push(self.len8_dlc);
+        buf.extend_from_slice(&self.data[..data_len]);
+
+        buf
+    }
+
+    /// Decodes a frame in the [`DataLink::CAN_SOCKETCAN`](crate::DataLink::CAN_SOCKETCAN) format.
+    ///
+    /// A frame is CAN FD if its FDF flag is set or if it has more than 8 bytes of data.
+    pub fn from_socketcan(data: &[u8]) -> PcapResult<Self> {
+        if data.len() < 8 {
+            return Err(PcapError::IncompleteBuffer(8, data.len()));
+        }
+
+        let can_id = u32::from_be_bytes(data[..4].try_into().unwrap());
+        let len = data[4] as usize;
+        let is_fd = data[5] & Self::FDF != 0 || len > 8;
+        let fd_flags = is_fd.then_some(data[5] & !Self::FDF);
+
+        let data_len = if can_id & Self::RTR_FLAG != 0 { 0 } else { len };
+        let Some(payload) = data.get(8..8 + data_len)
+        else {
+            return Err(PcapError::IncompleteBuffer(8 + data_len, data.len()));
+        };
+
+        let mut frame = CanFrame { can_id, fd_flags, len8_dlc: if is_fd { 0 } else { data[7] }, data: payload.to_vec() };
+        frame.data.resize(len, 0);
+
+        Ok(frame)
+    }
+}
+
+/// Formats the frame like `candump`.
+impl Display for CanFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_extended() || self.is_error() {
+            write!(f, "{:08X}#", self.can_id & (Self::ERR_FLAG | 0x1FFFFFFF))?;
+        }
+        else {
+            write!(f, "{:03X}#", self.id())?;
+        }
+
+        if let Some(flags) = self.fd_flags {
+            write!(f, "#{:X}", flags & 0xF)?;
+        }
+        else if self.is_remote() {
+            f.write_char('R')?;
+            if !self.data.is_empty() {
+                write!(f, "{:X}", self.data.len())?;
+            }
+        }
+
+        if !self.is_remote() {
+            for byte in &self.data {
+                write!(f, "{byte:02X}")?;
+            }
+        }
+
+        if self.fd_flags.is_none() && self.len8_dlc != 0 {
+            write!(f, "_{:X}", self.len8_dlc)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Direction of a frame, written after the frame by `candump -x`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CanDirection {
+    /// Frame received by the interface
+    Received,
+    /// Frame transmitted by the interface
+    Transmitted,
+}
+
+/// A line of a candump log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CandumpEntry {
+    /// Timestamp EPOCH of the frame, with a microsecond resolution
+    pub timestamp: Duration,
+    /// Name of the interface, like `can0`
+    pub interface: String,
+    /// The frame
+    pub frame: CanFrame,
+    /// Direction of the frame, if it was logged
+    pub direction: Option<CanDirection>,
+}
+
+impl CandumpEntry {
+    /// Parses a line of a candump log, without its line ending.
+    pub fn parse(line: &str) -> PcapResult<Self> {
+        let mut fields = line.split_ascii_whitespace();
+
+        let timestamp = fields
+            .next()
+            .and_then(|ts| ts.strip_prefix('(')?.strip_suffix(')'))
+            .and_then(parse_timestamp)
+            .ok_or(PcapError::InvalidField("CandumpEntry: invalid timestamp"))?;
+
+        let interface = fields.next().ok_or(PcapError::InvalidField("CandumpEntry: missing interface"))?.to_string();
+        let frame = CanFrame::parse(fields.next().ok_or(PcapError::InvalidField("CandumpEntry: missing frame"))?)?;
+
+        let direction = match fields.next() {
+            None => None,
+            Some("R") => Some(CanDirection::Received),
+            Some("T") => Some(CanDirection::Transmitted),
+            Some(_) => return Err(PcapError::InvalidField("CandumpEntry: invalid direction")),
+        };
+
+        if fields.next().is_some() {
+            return Err(PcapError::InvalidField("CandumpEntry: trailing characters"));
+        }
+
+        Ok(CandumpEntry { timestamp, interface, frame, direction })
+    }
+}
+
+/// Formats the entry like `candump -l`, without line ending.
+impl Display for CandumpEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({:010}.{:06}) {} {}", self.timestamp.as_secs(), self.timestamp.subsec_micros(), self.interface, self.frame)?;
+
+        match self.direction {
+            Some(CanDirection::Received) => f.write_str(" R"),
+            Some(CanDirection::Transmitted) => f.write_str(" T"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads a candump log from a reader.
+///
+/// The empty lines are skipped.
+#[derive(Debug)]
+pub struct CandumpReader<R: BufRead> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> CandumpReader<R> {
+    /// Creates a new [`CandumpReader`] from an existing reader.
+    pub fn new(reader: R) -> Self {
+        CandumpReader { reader, line: String::new() }
+    }
+
+    /// Returns the next [`CandumpEntry`].
+    pub fn next_entry(&mut self) -> Option<PcapResult<CandumpEntry>> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) if self.line.trim().is_empty() => continue,
+                Ok(_) => return Some(CandumpEntry::parse(self.line.trim_end())),
+                Err(e) => return Some(Err(PcapError::IoError(e))),
+            }
+        }
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for CandumpReader<R> {
+    type Item = PcapResult<CandumpEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry()
+    }
+}
+
+/// Writes a candump log to a writer.
+#[derive(Debug)]
+pub struct CandumpWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CandumpWriter<W> {
+    /// Creates a new [`CandumpWriter`] from an existing writer.
+    pub fn new(writer: W) -> Self {
+        CandumpWriter { writer }
+    }
+
+    /// Writes a [`CandumpEntry`] followed by a line feed.
+    pub fn write_entry(&mut self, entry: &CandumpEntry) -> PcapResult<()> {
+        writeln!(self.writer, "{entry}").map_err(PcapError::IoError)
+    }
+
+    /// Flush data
+    pub fn flush(&mut self) -> PcapResult<()> {
+        self.writer.flush().map_err(PcapError::IoError)
+    }
+
+    /// Consumes [`Self`], returning the wrapped writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+/// Parses a timestamp like `1436509052.249713`.
+fn parse_timestamp(s: &str) -> Option<Duration> {
+    let (secs, micros) = s.split_once('.')?;
+    if micros.len() != 6 {
+        return None;
+    }
+
+    Some(Duration::new(secs.parse().ok()?, micros.parse::<u32>().ok()? * 1000))
+}
+
+/// Parses hexadecimal bytes, optionally separated by dots.
+fn parse_hex(s: &str) -> PcapResult<Vec<u8>> {
+    let digits: Vec<u8> = s.bytes().filter(|&b| b != b'.').collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(PcapError::InvalidField("CanFrame: odd number of hexadecimal digits"));
+    }
+
+    digits
+        .chunks_exact(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(PcapError::InvalidField("CanFrame: invalid hexadecimal data"))
+        })
+        .collect()
+}
+
+/// Parses the data length code following a `_`, valid only after 8 bytes of data.
+fn parse_len8_dlc(s: &str, data_len: usize) -> PcapResult<u8> {
+    if s.is_empty() {
+        return Ok(0);
+    }
+
+    match u8::from_str_radix(s, 16) {
+        Ok(dlc @ 9..=15) if s.len() == 1 && data_len == 8 => Ok(dlc),
+        _ => Err(PcapError::InvalidField("CanFrame: invalid data length code")),
+    }
+}
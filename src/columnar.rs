@@ -0,0 +1,267 @@
+//! Export of the packet metadata into Arrow record batches and Parquet files, behind the `parquet` feature.
+//!
+//! A [`PacketBatchBuilder`] streams the packets of a capture into Arrow [`RecordBatch`]es with one row per packet
+//! and these columns:
+//!
+//! | Column         | Type                 | Content                                                        |
+//! |----------------|----------------------|----------------------------------------------------------------|
+//! | `timestamp`    | `Timestamp(ns, UTC)` | Timestamp of the packet                                        |
+//! | `interface_id` | `UInt32`             | Index of the interface, across all the sections                |
+//! | `linktype`     | `UInt32`, nullable   | Link type of the interface, null if the interface is unknown   |
+//! | `captured_len` | `UInt32`             | Captured length                                                |
+//! | `original_len` | `UInt32`             | Original length                                                |
+//! | `flags`        | `UInt32`, nullable   | `epb_flags` or `pack_flags` option                             |
+//! | `drop_count`   | `UInt64`, nullable   | `epb_dropcount` option or drop count of a packet block         |
+//! | `payload`      | `Binary`, nullable   | Data of the packet, only if [`ColumnarConfig::payload`] is set |
+//!
+//! [`pcap_to_parquet`] and [`pcapng_to_parquet`] write the batches into a Parquet file, one row group per batch,
+//! so that the memory used doesn't depend on the size of the capture.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::columnar::{pcapng_to_parquet, ColumnarConfig};
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! let file_out = File::create("test.parquet").expect("Error creating file");
+//! pcapng_to_parquet(&mut pcapng_reader, file_out, &ColumnarConfig::default()).unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow_array::builder::{ArrayBuilder, BinaryBuilder, TimestampNanosecondBuilder, UInt32Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+
+use crate::errors::{PcapError, PcapResult};
+use crate::pcap::{PcapHeader, PcapPacket, PcapReader};
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use crate::pcapng::blocks::packet::PacketOption;
+use crate::pcapng::{Block, PcapNgReader};
+use crate::DataLink;
+
+
+/// Configuration of the columnar export.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ColumnarConfig {
+    /// Adds the `payload` column, holding the data of the packets
+    pub payload: bool,
+    /// Maximum number of rows of a [`RecordBatch`], and of a row group of the Parquet files
+    pub batch_size: usize,
+}
+
+/// Creates a new [`ColumnarConfig`] with these parameters:
+///
+/// ```rust,ignore
+/// ColumnarConfig {
+///     payload: false,
+///     batch_size: 65536,
+/// };
+/// ```
+impl Default for ColumnarConfig {
+    fn default() -> Self {
+        ColumnarConfig { payload: false, batch_size: 65536 }
+    }
+}
+
+/// Builds the [`RecordBatch`]es of the metadata of the packets of a capture.
+#[derive(Debug)]
+pub struct PacketBatchBuilder {
+    schema: SchemaRef,
+    timestamp: TimestampNanosecondBuilder,
+    interface_id: UInt32Builder,
+    linktype: UInt32Builder,
+    captured_len: UInt32Builder,
+    original_len: UInt32Builder,
+    flags: UInt32Builder,
+    drop_count: UInt64Builder,
+    payload: Option<BinaryBuilder>,
+    /// Link types and snaplens of the interfaces of all the sections
+    interfaces: Vec<(DataLink, u32)>,
+    /// Index in `interfaces` of the first interface of the current section
+    section_start: usize,
+    /// Timestamp of the previous packet
+    last_timestamp: Duration,
+}
+
+impl PacketBatchBuilder {
+    /// Creates an empty [`PacketBatchBuilder`] for a pcapng.
+    pub fn new(config: &ColumnarConfig) -> Self {
+        let mut fields = vec![
+            Field::new("timestamp", DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())), false),
+            Field::new("interface_id", DataType::UInt32, false),
+            Field::new("linktype", DataType::UInt32, true),
+            Field::new("captured_len", DataType::UInt32, false),
+            Field::new("original_len", DataType::UInt32, false),
+            Field::new("flags", DataType::UInt32, true),
+            Field::new("drop_count", DataType::UInt64, true),
+        ];
+        if config.payload {
+            fields.push(Field::new("payload", DataType::Binary, true));
+        }
+
+        PacketBatchBuilder {
+            schema: Arc::new(Schema::new(fields)),
+            timestamp: TimestampNanosecondBuilder::new().with_timezone("UTC"),
+            interface_id: UInt32Builder::new(),
+            linktype: UInt32Builder::new(),
+            captured_len: UInt32Builder::new(),
+            original_len: UInt32Builder::new(),
+            flags: UInt32Builder::new(),
+            drop_count: UInt64Builder::new(),
+            payload: config.payload.then(BinaryBuilder::new),
+            interfaces: vec![],
+            section_start: 0,
+            last_timestamp: Duration::ZERO,
+        }
+    }
+
+    /// Creates an empty [`PacketBatchBuilder`] for a pcap, with a single interface described by the header.
+    pub fn from_pcap_header(header: &PcapHeader, config: &ColumnarConfig) -> Self {
+        PacketBatchBuilder { interfaces: vec![(header.datalink, header.snaplen)], ..Self::new(config) }
+    }
+
+    /// Returns the schema of the batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Adds the next packet of a pcap.
+    pub fn update_from_pcap_packet(&mut self, packet: &PcapPacket) {
+        self.add_packet(0, packet.timestamp(), packet.data(), packet.orig_len(), None, None);
+    }
+
+    /// Adds the next block of a pcapng, the blocks other than the packets only updating the interfaces.
+    ///
+    /// See [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) for the timestamp and data
+    /// of the simple packets.
+    pub fn update_from_block(&mut self, block: &Block) {
+        match block {
+            Block::SectionHeader(_) => self.section_start = self.interfaces.len(),
+            Block::InterfaceDescription(blk) => self.interfaces.push((blk.linktype, blk.snaplen)),
+            Block::EnhancedPacket(blk) => {
+                let flags = blk.options.iter().find_map(|opt| match opt {
+                    EnhancedPacketOption::Flags(flags) => Some(*flags),
+                    _ => None,
+                });
+                let drop_count = blk.options.iter().find_map(|opt| match opt {
+                    EnhancedPacketOption::DropCount(count) => Some(*count),
+                    _ => None,
+                });
+                self.add_packet(blk.interface_id, blk.timestamp, &blk.data, blk.original_len, flags, drop_count);
+            },
+            Block::Packet(blk) => {
+                let flags = blk.options.iter().find_map(|opt| match opt {
+                    PacketOption::Flags(flags) => Some(*flags),
+                    _ => None,
+                });
+                self.add_packet(blk.interface_id as u32, blk.timestamp, &blk.data, blk.original_len, flags, Some(blk.drop_count as u64));
+            },
+            Block::SimplePacket(blk) => {
+                let snaplen = self.interfaces.get(self.section_start).map_or(0, |&(_, snaplen)| snaplen);
+                self.add_packet(0, self.last_timestamp, blk.packet_data(snaplen), blk.original_len, None, None);
+            },
+            _ => {},
+        }
+    }
+
+    /// Appends a row.
+    fn add_packet(&mut self, interface_id: u32, timestamp: Duration, data: &[u8], original_len: u32, flags: Option<u32>, drop_count: Option<u64>) {
+        self.last_timestamp = timestamp;
+
+        let interface = self.section_start + interface_id as usize;
+        self.timestamp.append_value(timestamp.as_nanos().try_into().unwrap_or(i64::MAX));
+        self.interface_id.append_value(interface as u32);
+        self.linktype.append_option(self.interfaces.get(interface).map(|&(linktype, _)| u32::from(linktype)));
+        self.captured_len.append_value(data.len() as u32);
+        self.original_len.append_value(original_len);
+        self.flags.append_option(flags);
+        self.drop_count.append_option(drop_count);
+        if let Some(payload) = &mut self.payload {
+            payload.append_value(data);
+        }
+    }
+
+    /// Returns the number of rows of the next batch.
+    pub fn len(&self) -> usize {
+        self.timestamp.len()
+    }
+
+    /// Returns true if the next batch has no row.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the [`RecordBatch`] of the rows added since the previous one.
+    pub fn finish(&mut self) -> RecordBatch {
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(self.timestamp.finish()),
+            Arc::new(self.interface_id.finish()),
+            Arc::new(self.linktype.finish()),
+            Arc::new(self.captured_len.finish()),
+            Arc::new(self.original_len.finish()),
+            Arc::new(self.flags.finish()),
+            Arc::new(self.drop_count.finish()),
+        ];
+        if let Some(payload) = &mut self.payload {
+            columns.push(Arc::new(payload.finish()));
+        }
+
+        RecordBatch::try_new(self.schema.clone(), columns).expect("the columns should match the schema")
+    }
+}
+
+/// Writes the metadata of all the remaining packets of a [`PcapReader`] into a Parquet file.
+///
+/// Returns the wrapped writer.
+pub fn pcap_to_parquet<R: Read, W: Write + Send>(reader: &mut PcapReader<R>, writer: W, config: &ColumnarConfig) -> PcapResult<W> {
+    let mut builder = PacketBatchBuilder::from_pcap_header(&reader.header(), config);
+    let mut writer = ArrowWriter::try_new(writer, builder.schema(), None).map_err(io::Error::other)?;
+
+    while let Some(packet) = reader.next_packet() {
+        builder.update_from_pcap_packet(&packet?);
+        if builder.len() >= config.batch_size {
+            write_batch(&mut writer, &mut builder)?;
+        }
+    }
+
+    write_batch(&mut writer, &mut builder)?;
+    Ok(writer.into_inner().map_err(io::Error::other)?)
+}
+
+/// Writes the metadata of all the remaining packets of a [`PcapNgReader`] into a Parquet file.
+///
+/// Returns the wrapped writer.
+pub fn pcapng_to_parquet<R: Read, W: Write + Send>(reader: &mut PcapNgReader<R>, writer: W, config: &ColumnarConfig) -> PcapResult<W> {
+    let mut builder = PacketBatchBuilder::new(config);
+    let mut writer = ArrowWriter::try_new(writer, builder.schema(), None).map_err(io::Error::other)?;
+
+    while let Some(block) = reader.next_block() {
+        builder.update_from_block(&block?);
+        if builder.len() >= config.batch_size {
+            write_batch(&mut writer, &mut builder)?;
+        }
+    }
+
+    write_batch(&mut writer, &mut builder)?;
+    Ok(writer.into_inner().map_err(io::Error::other)?)
+}
+
+/// Writes the next batch as a row group.
+fn write_batch<W: Write + Send>(writer: &mut ArrowWriter<W>, builder: &mut PacketBatchBuilder) -> Result<(), PcapError> {
+    if builder.is_empty() {
+        return Ok(());
+    }
+
+    writer.write(&builder.finish()).map_err(io::Error::other)?;
+    writer.flush().map_err(io::Error::other)?;
+
+    Ok(())
+}
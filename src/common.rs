@@ -2,6 +2,7 @@ use byteorder_slice::ByteOrder;
 
 /// Timestamp resolution of the pcap
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TsResolution {
     /// Microsecond resolution
     MicroSecond,
@@ -11,6 +12,7 @@ pub enum TsResolution {
 
 /// Endianness of the pcap
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Endianness {
     /// Big endian
     Big,
@@ -63,6 +65,7 @@ impl Endianness {
 #[allow(non_camel_case_types)]
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataLink {
     NULL,
     ETHERNET,
@@ -741,3 +744,14 @@ impl From<DataLink> for u32 {
         }
     }
 }
+
+/// Returns the number of days between the EPOCH and a date of the proleptic Gregorian calendar.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
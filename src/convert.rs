@@ -0,0 +1,532 @@
+//! Conversions between Pcap and PcapNg captures, from Snoop, ERF and btsnoop captures to PcapNg,
+//! and between candump logs and PcapNg.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::convert::pcap_to_pcapng;
+//! use pcap_file::pcap::PcapReader;
+//!
+//! let file_in = File::open("test.pcap").expect("Error opening file");
+//! let mut pcap_reader = PcapReader::new(file_in).unwrap();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let pcapng_writer = pcap_to_pcapng(&mut pcap_reader, file_out).unwrap();
+//! ```
+
+use std::borrow::Cow;
+use std::io::{BufRead, Read, Write};
+use std::time::Duration;
+
+use crate::btsnoop::{BtsnoopDataLink, BtsnoopReader};
+use crate::candump::{CanDirection, CanFrame, CandumpEntry, CandumpReader, CandumpWriter};
+use crate::erf::{ErfReader, ErfType};
+use crate::errors::{PcapError, PcapResult};
+use crate::pcap::{PcapHeader, PcapPacket, PcapReader, PcapWriter};
+use crate::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use crate::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption, TsResolution as NgTsResolution};
+use crate::pcapng::blocks::section_header::SectionHeaderBlock;
+use crate::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use crate::snoop::SnoopReader;
+use crate::{DataLink, Endianness, TsResolution};
+
+
+/// Snaplen used when a pcapng interface doesn't limit the captured length, same value as libpcap.
+pub(crate) const MAXIMUM_SNAPLEN: u32 = 262144;
+
+/// Creates the [`InterfaceDescriptionBlock`] equivalent to a [`PcapHeader`].
+///
+/// The `if_tsresol` option matches the [`TsResolution`] of the header.
+pub fn interface_from_pcap_header(header: &PcapHeader) -> InterfaceDescriptionBlock<'static> {
+    let ts_resol = match header.ts_resolution {
+        TsResolution::MicroSecond => NgTsResolution::MICRO,
+        TsResolution::NanoSecond => NgTsResolution::NANO,
+    };
+
+    InterfaceDescriptionBlock {
+        linktype: header.datalink,
+        snaplen: header.snaplen,
+        options: vec![InterfaceDescriptionOption::IfTsResol(ts_resol.to_raw())],
+    }
+}
+
+/// Creates the [`PcapHeader`] equivalent to an [`InterfaceDescriptionBlock`].
+///
+/// The timestamp resolution is nanosecond if the interface one is finer than microsecond.
+/// A snaplen of 0, meaning no limit, is converted to 262144.
+pub fn pcap_header_from_interface(interface: &InterfaceDescriptionBlock, endianness: Endianness) -> PcapResult<PcapHeader> {
+    let ts_resolution = if interface.ts_resolution()?.to_nano_secs() < 1000 { TsResolution::NanoSecond } else { TsResolution::MicroSecond };
+
+    let snaplen = match interface.snaplen {
+        0 => MAXIMUM_SNAPLEN,
+        snaplen => snaplen,
+    };
+
+    Ok(PcapHeader { snaplen, datalink: interface.linktype, ts_resolution, endianness, ..Default::default() })
+}
+
+/// Creates the [`PcapHeader`] holding the packets of all the interfaces with the link type of `interface`.
+///
+/// The headers built by [`pcap_header_from_interface`] are merged: the largest snaplen and the finest timestamp
+/// resolution are kept.
+fn pcap_header_from_interfaces(
+    interfaces: &[InterfaceDescriptionBlock],
+    interface: &InterfaceDescriptionBlock,
+    endianness: Endianness,
+) -> PcapResult<PcapHeader> {
+    let mut header = pcap_header_from_interface(interface, endianness)?;
+
+    for other in interfaces.iter().filter(|other| other.linktype == interface.linktype) {
+        let other = pcap_header_from_interface(other, endianness)?;
+        header.snaplen = header.snaplen.max(other.snaplen);
+        if other.ts_resolution == TsResolution::NanoSecond {
+            header.ts_resolution = TsResolution::NanoSecond;
+        }
+    }
+
+    Ok(header)
+}
+
+/// Converts a Pcap into a PcapNg.
+///
+/// Writes a section header with the endianness of the pcap, an [`InterfaceDescriptionBlock`] built by
+/// [`interface_from_pcap_header`] and an [`EnhancedPacketBlock`] per packet.
+///
+/// Returns the PcapNg writer, so more blocks can be appended.
+pub fn pcap_to_pcapng<R: Read, W: Write>(reader: &mut PcapReader<R>, writer: W) -> PcapResult<PcapNgWriter<W>> {
+    let header = reader.header();
+
+    let section = SectionHeaderBlock { endianness: header.endianness, ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+    writer.write_pcapng_block(interface_from_pcap_header(&header))?;
+
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        let block = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: packet.timestamp(),
+            original_len: packet.orig_len(),
+            data: Cow::Borrowed(packet.data()),
+            options: vec![],
+        };
+
+        writer.write_pcapng_block(block)?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts a Snoop into a PcapNg.
+///
+/// Writes a big endian section header, an [`InterfaceDescriptionBlock`] with the equivalent link type and a
+/// microsecond resolution, and an [`EnhancedPacketBlock`] per packet. The packets dropped since the previous one,
+/// computed from the cumulative drops of the records, are stored in the `epb_dropcount` option.
+///
+/// # Errors
+/// [`PcapError::InvalidField`] if the snoop data link has no pcap equivalent.
+pub fn snoop_to_pcapng<R: Read, W: Write>(reader: &mut SnoopReader<R>, writer: W) -> PcapResult<PcapNgWriter<W>> {
+    let Some(linktype) = reader.header().datalink.to_datalink()
+    else {
+        return Err(PcapError::InvalidField("SnoopHeader: datalink has no pcap equivalent"));
+    };
+
+    let section = SectionHeaderBlock { endianness: Endianness::Big, ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+
+    let interface = InterfaceDescriptionBlock {
+        linktype,
+        snaplen: 0,
+        options: vec![InterfaceDescriptionOption::IfTsResol(NgTsResolution::MICRO.to_raw())],
+    };
+    writer.write_pcapng_block(interface)?;
+
+    let mut cumulative_drops = 0;
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        let mut options = vec![];
+        if packet.cumulative_drops() > cumulative_drops {
+            options.push(EnhancedPacketOption::DropCount((packet.cumulative_drops() - cumulative_drops) as u64));
+        }
+        cumulative_drops = packet.cumulative_drops();
+
+        let block = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: packet.timestamp(),
+            original_len: packet.orig_len(),
+            data: Cow::Borrowed(packet.data()),
+            options,
+        };
+
+        writer.write_pcapng_block(block)?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts an ERF capture into a PcapNg.
+///
+/// Writes a section header with the native endianness, then an [`EnhancedPacketBlock`] per record.
+/// An [`InterfaceDescriptionBlock`] with a nanosecond resolution is written before the first packet of each
+/// host ID, source ID, port and link type, its `if_name` being the port, like `Port A`.
+/// The Ethernet records end with the 4 bytes FCS, given by the `if_fcslen` option of their interfaces.
+///
+/// The records whose [`ErfType::datalink`] is known are stripped from their ERF headers and padding.
+/// The other records are kept whole with [`DataLink::ERF`], the padding records are dropped.
+/// The loss counter of the uncolored records is stored in the `epb_dropcount` option.
+///
+/// The timestamps are truncated to the nanosecond, see [`ErfTimestamp::to_duration`](crate::erf::ErfTimestamp::to_duration).
+pub fn erf_to_pcapng<R: Read, W: Write>(reader: &mut ErfReader<R>, writer: W) -> PcapResult<PcapNgWriter<W>> {
+    let section = SectionHeaderBlock { endianness: Endianness::native(), ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+
+    let mut interfaces = vec![];
+    let mut record_data = vec![];
+    while let Some(record) = reader.next_record() {
+        let record = record?;
+        if record.record_type == ErfType::PAD {
+            continue;
+        }
+
+        let (linktype, data) = match record.record_type.datalink() {
+            Some(linktype) => (linktype, record.packet()),
+            None => {
+                record_data.clear();
+                record.write_to(&mut record_data)?;
+                (DataLink::ERF, &record_data[..])
+            },
+        };
+
+        let key = (record.host_id(), record.interface(), linktype);
+        let interface_id = match interfaces.iter().position(|k| *k == key) {
+            Some(idx) => idx,
+            None => {
+                let mut options = vec![
+                    InterfaceDescriptionOption::IfName(Cow::Owned(format!("Port {}", (b'A' + record.interface()) as char))),
+                    InterfaceDescriptionOption::IfTsResol(NgTsResolution::NANO.to_raw()),
+                ];
+                if linktype == DataLink::ETHERNET {
+                    options.push(InterfaceDescriptionOption::IfFcsLen(4));
+                }
+                if let Some((source_id, host_id)) = record.host_id() {
+                    options.push(InterfaceDescriptionOption::IfDescription(Cow::Owned(format!("Host {host_id:012x}, source {source_id}"))));
+                }
+
+                writer.write_pcapng_block(InterfaceDescriptionBlock { linktype, snaplen: 0, options })?;
+                interfaces.push(key);
+                interfaces.len() - 1
+            },
+        };
+
+        let mut options = vec![];
+        if !record.record_type.has_color() && record.loss_counter != 0 {
+            options.push(EnhancedPacketOption::DropCount(record.loss_counter as u64));
+        }
+
+        let original_len = match linktype {
+            DataLink::ERF => data.len() as u32,
+            _ => record.wire_len as u32,
+        };
+
+        let block = EnhancedPacketBlock {
+            interface_id: interface_id as u32,
+            timestamp: record.timestamp.to_duration(),
+            original_len,
+            data: Cow::Borrowed(data),
+            options,
+        };
+
+        writer.write_pcapng_block(block)?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts a btsnoop into a PcapNg.
+///
+/// Writes a big endian section header, an [`InterfaceDescriptionBlock`] with the
+/// [`DataLink::BLUETOOTH_HCI_H4_WITH_PHDR`] link type and a microsecond resolution, and an [`EnhancedPacketBlock`]
+/// per packet. Each packet is prefixed by the 4 bytes pseudo-header holding its direction, 0 if it was sent and 1 if
+/// it was received. The packets of a [`BtsnoopDataLink::H1`] capture are also prefixed by their H4 packet type,
+/// deduced from their flags: command, event or ACL data.
+///
+/// The packets dropped since the previous one, computed from the cumulative drops of the records, are stored in the
+/// `epb_dropcount` option.
+///
+/// # Errors
+/// [`PcapError::InvalidField`] if the btsnoop data link is neither H1 nor H4.
+pub fn btsnoop_to_pcapng<R: Read, W: Write>(reader: &mut BtsnoopReader<R>, writer: W) -> PcapResult<PcapNgWriter<W>> {
+    let datalink = reader.header().datalink;
+    if !matches!(datalink, BtsnoopDataLink::H1 | BtsnoopDataLink::H4) {
+        return Err(PcapError::InvalidField("BtsnoopHeader: datalink is neither H1 nor H4"));
+    }
+
+    let section = SectionHeaderBlock { endianness: Endianness::Big, ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+
+    let interface = InterfaceDescriptionBlock {
+        linktype: DataLink::BLUETOOTH_HCI_H4_WITH_PHDR,
+        snaplen: 0,
+        options: vec![InterfaceDescriptionOption::IfTsResol(NgTsResolution::MICRO.to_raw())],
+    };
+    writer.write_pcapng_block(interface)?;
+
+    let mut cumulative_drops = 0;
+    let mut data = vec![];
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        data.clear();
+        data.extend_from_slice(&(packet.is_received() as u32).to_be_bytes());
+        if datalink == BtsnoopDataLink::H1 {
+            let packet_type = match (packet.is_command_or_event(), packet.is_received()) {
+                (true, false) => 0x01,
+                (true, true) => 0x04,
+                (false, _) => 0x02,
+            };
+            data.push(packet_type);
+        }
+        let header_len = data.len() as u32;
+        data.extend_from_slice(packet.data());
+
+        let mut options = vec![];
+        if packet.cumulative_drops() > cumulative_drops {
+            options.push(EnhancedPacketOption::DropCount((packet.cumulative_drops() - cumulative_drops) as u64));
+        }
+        cumulative_drops = packet.cumulative_drops();
+
+        let block = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: packet.timestamp(),
+            original_len: packet.orig_len().saturating_add(header_len),
+            data: Cow::Borrowed(&data),
+            options,
+        };
+
+        writer.write_pcapng_block(block)?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts a candump log into a PcapNg.
+///
+/// Writes a section header with the native endianness, then an [`EnhancedPacketBlock`] per frame, encoded in the
+/// [`DataLink::CAN_SOCKETCAN`] format by [`CanFrame::to_socketcan`]. An [`InterfaceDescriptionBlock`] named after
+/// the interface of the log, like `can0`, is written before its first frame. The direction of the frames, if logged,
+/// is stored in the `epb_flags` option.
+pub fn candump_to_pcapng<R: BufRead, W: Write>(reader: R, writer: W) -> PcapResult<PcapNgWriter<W>> {
+    let section = SectionHeaderBlock { endianness: Endianness::native(), ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+
+    let mut interfaces: Vec<String> = vec![];
+    let mut candump_reader = CandumpReader::new(reader);
+    while let Some(entry) = candump_reader.next_entry() {
+        let entry = entry?;
+
+        let interface_id = match interfaces.iter().position(|name| *name == entry.interface) {
+            Some(idx) => idx,
+            None => {
+                let interface = InterfaceDescriptionBlock {
+                    linktype: DataLink::CAN_SOCKETCAN,
+                    snaplen: 0,
+                    options: vec![
+                        InterfaceDescriptionOption::IfName(Cow::Borrowed(&entry.interface)),
+                        InterfaceDescriptionOption::IfTsResol(NgTsResolution::MICRO.to_raw()),
+                    ],
+                };
+                writer.write_pcapng_block(interface)?;
+                interfaces.push(entry.interface.clone());
+                interfaces.len() - 1
+            },
+        };
+
+        let options = match entry.direction {
+            Some(CanDirection::Received) => vec![EnhancedPacketOption::Flags(0x01)],
+            Some(CanDirection::Transmitted) => vec![EnhancedPacketOption::Flags(0x02)],
+            None => vec![],
+        };
+
+        let data = entry.frame.to_socketcan();
+        let block = EnhancedPacketBlock {
+            interface_id: interface_id as u32,
+            timestamp: entry.timestamp,
+            original_len: data.len() as u32,
+            data: Cow::Owned(data),
+            options,
+        };
+
+        writer.write_pcapng_block(block)?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts a PcapNg into a candump log.
+///
+/// The enhanced packet blocks of the [`DataLink::CAN_SOCKETCAN`] interfaces are decoded by
+/// [`CanFrame::from_socketcan`], the other blocks are dropped. The interfaces are named after their `if_name` option,
+/// `can` followed by their index otherwise. The direction of the frames is read from the `epb_flags` option.
+///
+/// Returns the wrapped writer.
+pub fn pcapng_to_candump<R: Read, W: Write>(reader: &mut PcapNgReader<R>, writer: W) -> PcapResult<W> {
+    let mut writer = CandumpWriter::new(writer);
+
+    while let Some(res) = reader.next_block_and_state() {
+        let (block, state) = res?;
+        let Block::EnhancedPacket(packet) = block
+        else {
+            continue;
+        };
+
+        let Some(interface) = state.interfaces().get(packet.interface_id as usize)
+        else {
+            return Err(PcapError::InvalidInterfaceId(packet.interface_id));
+        };
+        if interface.linktype != DataLink::CAN_SOCKETCAN {
+            continue;
+        }
+
+        let name = interface.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfName(name) => Some(name.to_string()),
+            _ => None,
+        });
+
+        let direction = packet.options.iter().find_map(|opt| match opt {
+            EnhancedPacketOption::Flags(flags) if flags & 0x03 == 0x01 => Some(CanDirection::Received),
+            EnhancedPacketOption::Flags(flags) if flags & 0x03 == 0x02 => Some(CanDirection::Transmitted),
+            _ => None,
+        });
+
+        let entry = CandumpEntry {
+            timestamp: packet.timestamp,
+            interface: name.unwrap_or_else(|| format!("can{}", packet.interface_id)),
+            frame: CanFrame::from_socketcan(&packet.data)?,
+            direction,
+        };
+
+        writer.write_entry(&entry)?;
+    }
+
+    Ok(writer.into_writer())
+}
+
+/// Converts a PcapNg into a Pcap.
+///
+/// The header of the pcap is built by [`pcap_header_from_interface`] from the interfaces described before the first
+/// packet with its link type, keeping the largest snaplen and the finest timestamp resolution.
+/// Enhanced, simple and obsolete packet blocks are converted, the other blocks are dropped.
+///
+/// # Errors
+/// [`PcapError::DataLinkMismatch`] if the packets come from interfaces with different link types,
+/// use [`pcapng_to_pcap_split`] to handle those captures.
+pub fn pcapng_to_pcap<R: Read, W: Write>(reader: &mut PcapNgReader<R>, writer: W) -> PcapResult<PcapWriter<W>> {
+    let mut writer = Some(writer);
+    let mut pcap_writer: Option<PcapWriter<W>> = None;
+
+    convert_pcapng_packets(reader, |interfaces, interface, endianness, packet| {
+        let pcap_writer = match &mut pcap_writer {
+            Some(pcap_writer) => pcap_writer,
+            None => {
+                let header = match interface {
+                    Some(interface) => pcap_header_from_interfaces(interfaces, interface, endianness)?,
+                    None => PcapHeader { endianness, ..Default::default() },
+                };
+                pcap_writer.insert(PcapWriter::with_header(writer.take().unwrap(), header)?)
+            },
+        };
+
+        if let Some((linktype, packet)) = packet {
+            if linktype != pcap_writer.datalink() {
+                return Err(PcapError::DataLinkMismatch(pcap_writer.datalink(), linktype));
+            }
+            pcap_writer.write_packet(&packet)?;
+        }
+
+        Ok(())
+    })?;
+
+    // The callback is always called at least once, at the end of the capture
+    Ok(pcap_writer.unwrap())
+}
+
+/// Converts a PcapNg into one Pcap per data link type.
+///
+/// `new_writer` is called with the header of the pcap the first time a packet with a new link type is found.
+/// The header is built by [`pcap_header_from_interface`] from the interfaces with this link type described before
+/// the packet, keeping the largest snaplen and the finest timestamp resolution.
+///
+/// Returns the writers in the order of their creation.
+pub fn pcapng_to_pcap_split<R, W, F>(reader: &mut PcapNgReader<R>, mut new_writer: F) -> PcapResult<Vec<PcapWriter<W>>>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&PcapHeader) -> PcapResult<W>,
+{
+    let mut writers: Vec<PcapWriter<W>> = vec![];
+
+    convert_pcapng_packets(reader, |interfaces, interface, endianness, packet| {
+        let (Some(interface), Some((linktype, packet))) = (interface, packet)
+        else {
+            return Ok(());
+        };
+
+        let idx = match writers.iter().position(|w| w.datalink() == linktype) {
+            Some(idx) => idx,
+            None => {
+                let header = pcap_header_from_interfaces(interfaces, interface, endianness)?;
+                writers.push(PcapWriter::with_header(new_writer(&header)?, header)?);
+                writers.len() - 1
+            },
+        };
+
+        writers[idx].write_packet(&packet)?;
+
+        Ok(())
+    })?;
+
+    Ok(writers)
+}
+
+/// Iterates over the packets of a PcapNg, calling `f` with the interfaces of the section, the interface of the packet,
+/// the section endianness and the converted packet.
+///
+/// `f` is called once with no packet at the end of the capture.
+fn convert_pcapng_packets<R, F>(reader: &mut PcapNgReader<R>, mut f: F) -> PcapResult<()>
+where
+    R: Read,
+    F: FnMut(&[InterfaceDescriptionBlock], Option<&InterfaceDescriptionBlock>, Endianness, Option<(DataLink, PcapPacket)>) -> PcapResult<()>,
+{
+    // Simple packet blocks have no timestamp, they inherit the one of the previous packet
+    let mut last_timestamp = Duration::ZERO;
+
+    while let Some(res) = reader.next_block_and_state() {
+        let (block, state) = res?;
+        let endianness = state.section().endianness;
+
+        let (interface_id, timestamp, original_len, data) = match &block {
+            Block::EnhancedPacket(blk) => (blk.interface_id, blk.timestamp, blk.original_len, &blk.data[..]),
+            Block::Packet(blk) => (blk.interface_id as u32, blk.timestamp, blk.original_len, &blk.data[..]),
+            Block::SimplePacket(blk) => {
+                let snaplen = state.interfaces().first().map_or(0, |interface| interface.snaplen);
+                (0, last_timestamp, blk.original_len, blk.packet_data(snaplen))
+            },
+            _ => continue,
+        };
+
+        last_timestamp = timestamp;
+
+        let interface = state
+            .interfaces()
+            .get(interface_id as usize)
+            .ok_or(PcapError::InvalidInterfaceId(interface_id))?;
+
+        let packet = PcapPacket::new(timestamp, original_len, data)?;
+        f(state.interfaces(), Some(interface), endianness, Some((interface.linktype, packet)))?;
+    }
+
+    let endianness = reader.section().endianness;
+    f(reader.interfaces(), reader.interfaces().first(), endianness, None)
+}
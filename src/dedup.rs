@@ -0,0 +1,337 @@
+//! Removal of duplicate packets, like `editcap -d`.
+//!
+//! A [`Deduplicator`] remembers the hash of the data of the recent packets, within a [`DedupWindow`], and reports
+//! the packets whose hash was already seen. [`PcapDedup`] and [`PcapNgDedup`] apply it to an [`Iterator`] of
+//! packets or blocks.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! use pcap_file::dedup::{DedupConfig, DedupWindow, PcapNgDedup};
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, blocks.section().clone()).unwrap();
+//!
+//! // Ignore the IPv4 TTL and header checksum of Ethernet frames
+//! let config = DedupConfig { window: DedupWindow::Time(Duration::from_millis(1)), ignored_ranges: vec![22..23, 24..26], annotation: None };
+//! let mut dedup = PcapNgDedup::new(blocks, config);
+//!
+//! for block in dedup.by_ref() {
+//!     pcapng_writer.write_block(&block.unwrap()).unwrap();
+//! }
+//!
+//! println!("{} duplicates removed", dedup.stats().duplicates);
+//! ```
+
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::Hasher;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::errors::PcapError;
+use crate::pcap::PcapPacket;
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use crate::pcapng::blocks::opt_common::CommonOption;
+use crate::pcapng::blocks::packet::PacketOption;
+use crate::pcapng::Block;
+
+
+/// Packets a new packet is compared to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DedupWindow {
+    /// The given number of previous packets, like `editcap -D`
+    Count(usize),
+    /// The previous packets whose timestamp is at most the given duration before the new one, like `editcap -w`
+    Time(Duration),
+}
+
+impl Default for DedupWindow {
+    /// The 5 previous packets, the default of `editcap -d`.
+    fn default() -> Self {
+        DedupWindow::Count(5)
+    }
+}
+
+/// How [`PcapNgDedup`] marks the duplicate packets it keeps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DuplicateAnnotation {
+    /// Adds a comment `Duplicate of packet <index>`, the index being the 0-based position of the first occurrence
+    Comment,
+    /// Sets these bits of the `epb_flags` option, adding the option if needed
+    Flags(u32),
+}
+
+/// Configuration of a [`Deduplicator`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DedupConfig {
+    /// Previous packets a packet is compared to
+    pub window: DedupWindow,
+    /// Byte ranges of the packet data excluded from the comparison, like the IP TTL or checksum.
+    ///
+    /// The ranges beyond the end of a packet are ignored.
+    pub ignored_ranges: Vec<Range<usize>>,
+    /// If set, [`PcapNgDedup`] keeps the duplicates and annotates them instead of dropping them.
+    ///
+    /// The simple packet blocks have no options, their duplicates are kept without annotation.
+    /// [`PcapDedup`] always drops the duplicates.
+    pub annotation: Option<DuplicateAnnotation>,
+}
+
+/// Statistics of a [`Deduplicator`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DedupStats {
+    /// Number of packets checked
+    pub packets: u64,
+    /// Number of duplicates found
+    pub duplicates: u64,
+    /// Sum of the captured lengths of the duplicates
+    pub duplicate_bytes: u64,
+}
+
+/// Finds the duplicate packets of a sequence of packets.
+///
+/// Two packets are duplicates if they have the same captured length and the same hash of their data
+/// outside of the ignored ranges.
+#[derive(Clone, Debug)]
+pub struct Deduplicator {
+    config: DedupConfig,
+    /// Hash, timestamp and index of the packets of the window, oldest first
+    recent: VecDeque<(u64, Duration, u64)>,
+    stats: DedupStats,
+}
+
+impl Deduplicator {
+    /// Creates a new [`Deduplicator`].
+    pub fn new(config: DedupConfig) -> Self {
+        Deduplicator { config, recent: VecDeque::new(), stats: DedupStats::default() }
+    }
+
+    /// Checks the next packet, returning the index of its first occurrence if it is a duplicate.
+    ///
+    /// The index is the 0-based position of the packet among the checked packets.
+    pub fn check(&mut self, data: &[u8], timestamp: Duration) -> Option<u64> {
+        let index = self.stats.packets;
+        self.stats.packets += 1;
+
+        let hash = self.hash(data);
+
+        if let DedupWindow::Time(duration) = self.config.window {
+            while self.recent.front().is_some_and(|(_, ts, _)| timestamp.saturating_sub(*ts) > duration) {
+                self.recent.pop_front();
+            }
+        }
+
+        let original = self.recent.iter().rev().find(|(h, _, _)| *h == hash).map(|(_, _, idx)| *idx);
+
+        match original {
+            Some(_) => {
+                self.stats.duplicates += 1;
+                self.stats.duplicate_bytes += data.len() as u64;
+            },
+            None => {
+                self.recent.push_back((hash, timestamp, index));
+                if let DedupWindow::Count(count) = self.config.window {
+                    if self.recent.len() > count {
+                        self.recent.pop_front();
+                    }
+                }
+            },
+        }
+
+        original
+    }
+
+    /// Returns the statistics of the packets checked so far.
+    pub fn stats(&self) -> DedupStats {
+        self.stats
+    }
+
+    /// Returns the [`DedupConfig`].
+    pub fn config(&self) -> &DedupConfig {
+        &self.config
+    }
+
+    /// Hashes the length of the data and its bytes outside of the ignored ranges.
+    fn hash(&self, data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_usize(data.len());
+
+        let mut start = 0;
+        while start < data.len() {
+            match self.config.ignored_ranges.iter().find(|r| r.contains(&start)) {
+                Some(range) => start = range.end,
+                None => {
+                    // Hash up to the next ignored range
+                    let end = self
+                        .config
+                        .ignored_ranges
+                        .iter()
+                        .filter(|r| r.start > start)
+                        .map(|r| r.start)
+                        .min()
+                        .unwrap_or(data.len())
+                        .min(data.len());
+
+                    hasher.write(&data[start..end]);
+                    start = end;
+                },
+            }
+        }
+
+        hasher.finish()
+    }
+}
+
+
+/// [`Iterator`] adapter dropping the duplicate [`PcapPacket`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapDedup<I> {
+    inner: I,
+    dedup: Deduplicator,
+}
+
+impl<I> PcapDedup<I> {
+    /// Creates a new [`PcapDedup`] over an iterator of packets.
+    pub fn new(inner: I, config: DedupConfig) -> Self {
+        PcapDedup { inner, dedup: Deduplicator::new(config) }
+    }
+
+    /// Returns the statistics of the packets read so far.
+    pub fn stats(&self) -> DedupStats {
+        self.dedup.stats()
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapDedup<I> {
+    type Item = Result<PcapPacket<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.inner.next()? {
+                Ok(packet) => packet,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if self.dedup.check(packet.data(), packet.timestamp()).is_none() {
+                return Some(Ok(packet));
+            }
+        }
+    }
+}
+
+
+/// [`Iterator`] adapter dropping, or annotating, the duplicate packet blocks.
+///
+/// The non-packet blocks are kept.
+/// See [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) for the timestamp and data
+/// of the simple packets.
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgDedup<I> {
+    inner: I,
+    dedup: Deduplicator,
+    last_timestamp: Duration,
+    /// Snaplen of the first interface of the current section
+    first_snaplen: Option<u32>,
+}
+
+impl<I> PcapNgDedup<I> {
+    /// Creates a new [`PcapNgDedup`] over an iterator of blocks.
+    pub fn new(inner: I, config: DedupConfig) -> Self {
+        PcapNgDedup { inner, dedup: Deduplicator::new(config), last_timestamp: Duration::ZERO, first_snaplen: None }
+    }
+
+    /// Returns the statistics of the packets read so far.
+    pub fn stats(&self) -> DedupStats {
+        self.dedup.stats()
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgDedup<I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut block = match self.inner.next()? {
+                Ok(block) => block,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let (data, timestamp) = match &block {
+                Block::SectionHeader(_) => {
+                    self.first_snaplen = None;
+                    return Some(Ok(block));
+                },
+                Block::InterfaceDescription(blk) => {
+                    self.first_snaplen.get_or_insert(blk.snaplen);
+                    return Some(Ok(block));
+                },
+                Block::EnhancedPacket(blk) => (&blk.data[..], blk.timestamp),
+                Block::Packet(blk) => (&blk.data[..], blk.timestamp),
+                Block::SimplePacket(blk) => (blk.packet_data(self.first_snaplen.unwrap_or(0)), self.last_timestamp),
+                _ => return Some(Ok(block)),
+            };
+
+            self.last_timestamp = timestamp;
+
+            let Some(original) = self.dedup.check(data, timestamp)
+            else {
+                return Some(Ok(block));
+            };
+
+            match (&self.dedup.config().annotation, &mut block) {
+                (None, _) => continue,
+                (Some(annotation), Block::EnhancedPacket(blk)) => annotate_epb(&mut blk.options, annotation, original),
+                (Some(annotation), Block::Packet(blk)) => annotate_pb(&mut blk.options, annotation, original),
+                (Some(_), _) => {},
+            }
+
+            return Some(Ok(block));
+        }
+    }
+}
+
+/// Adds a duplicate annotation to the options of an enhanced packet block.
+fn annotate_epb(options: &mut Vec<EnhancedPacketOption>, annotation: &DuplicateAnnotation, original: u64) {
+    match annotation {
+        DuplicateAnnotation::Comment => {
+            options.push(EnhancedPacketOption::Common(CommonOption::Comment(Cow::Owned(format!("Duplicate of packet {original}")))));
+        },
+        DuplicateAnnotation::Flags(bits) => {
+            match options.iter_mut().find_map(|opt| match opt {
+                EnhancedPacketOption::Flags(flags) => Some(flags),
+                _ => None,
+            }) {
+                Some(flags) => *flags |= bits,
+                None => options.push(EnhancedPacketOption::Flags(*bits)),
+            }
+        },
+    }
+}
+
+/// Adds a duplicate annotation to the options of an obsolete packet block.
+fn annotate_pb(options: &mut Vec<PacketOption>, annotation: &DuplicateAnnotation, original: u64) {
+    match annotation {
+        DuplicateAnnotation::Comment => {
+            options.push(PacketOption::Common(CommonOption::Comment(Cow::Owned(format!("Duplicate of packet {original}")))));
+        },
+        DuplicateAnnotation::Flags(bits) => {
+            match options.iter_mut().find_map(|opt| match opt {
+                PacketOption::Flags(flags) => Some(flags),
+                _ => None,
+            }) {
+                Some(flags) => *flags |= bits,
+                None => options.push(PacketOption::Flags(*bits)),
+            }
+        },
+    }
+}
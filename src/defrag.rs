@@ -0,0 +1,486 @@
+//! Reassembly of fragmented IPv4 and IPv6 datagrams.
+//!
+//! A [`Defragmenter`] buffers the fragments of the IP datagrams until they are complete, within a timeout
+//! and a memory limit set by a [`DefragConfig`]. [`PcapDefrag`] and [`PcapNgDefrag`] apply it to an [`Iterator`]
+//! of packets or blocks and yield the IP [`Datagram`]s, along with the packets they were built from.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::defrag::{DefragConfig, PcapNgDefrag};
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! for datagram in PcapNgDefrag::new(blocks, DefragConfig::default()) {
+//!     let datagram = datagram.unwrap();
+//!     if datagram.is_reassembled() {
+//!         println!("{} bytes from {} fragments", datagram.data.len(), datagram.packets.len());
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::dissect::{fold, sum, IpHeader, LinkHeader};
+use crate::errors::PcapError;
+use crate::pcap::PcapPacket;
+use crate::pcapng::Block;
+use crate::DataLink;
+
+
+/// Configuration of a [`Defragmenter`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DefragConfig {
+    /// Maximum time between the first fragment of a datagram and the fragment completing it.
+    ///
+    /// The timestamps of the packets are used as clock, the incomplete datagrams are dropped when it expires.
+    pub timeout: Duration,
+    /// Maximum number of bytes buffered for the incomplete datagrams.
+    ///
+    /// The oldest incomplete datagrams are dropped when it is exceeded.
+    pub max_memory: usize,
+}
+
+impl Default for DefragConfig {
+    fn default() -> Self {
+        DefragConfig { timeout: Duration::from_secs(30), max_memory: 16 * 1024 * 1024 }
+    }
+}
+
+/// Counters of a [`Defragmenter`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DefragStats {
+    /// Number of IP packets processed
+    pub packets: u64,
+    /// Number of IP fragments processed
+    pub fragments: u64,
+    /// Number of datagrams reassembled from fragments
+    pub reassembled: u64,
+    /// Number of incomplete datagrams dropped after the timeout
+    pub expired: u64,
+    /// Number of incomplete datagrams dropped to respect the memory limit
+    pub evicted: u64,
+    /// Number of fragments dropped because they are truncated or inconsistent.
+    ///
+    /// A fragment inconsistent with the end of the datagram, given by its last fragment, also drops the datagram.
+    pub invalid: u64,
+}
+
+/// Packet contributing to a [`Datagram`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PacketRef {
+    /// Index of the packet in the capture, starting at 0
+    pub index: u64,
+    /// Timestamp of the packet
+    pub timestamp: Duration,
+    /// Index of the interface of the packet.
+    ///
+    /// The interfaces of all the sections of a pcapng are numbered one after the other, a pcap has a single interface.
+    pub interface: usize,
+}
+
+/// IP datagram, reassembled or not.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Datagram {
+    /// The IP datagram, from the start of its IP header.
+    ///
+    /// A reassembled datagram has the header of its first fragment, without fragmentation fields and with
+    /// its lengths and checksum updated. A datagram which was not fragmented is copied as captured.
+    pub data: Vec<u8>,
+    /// Packets the datagram was built from, in order of arrival
+    pub packets: Vec<PacketRef>,
+}
+
+impl Datagram {
+    /// Returns true if the datagram was reassembled from fragments.
+    pub fn is_reassembled(&self) -> bool {
+        self.packets.len() > 1
+    }
+
+    /// Returns the timestamp of the packet which completed the datagram.
+    pub fn timestamp(&self) -> Duration {
+        self.packets.last().expect("A datagram is built from at least one packet").timestamp
+    }
+}
+
+/// Identifies the fragments of a datagram.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+struct FragmentKey {
+    src: IpAddr,
+    dst: IpAddr,
+    /// Protocol of an IPv4 datagram, 0 for IPv6
+    protocol: u8,
+    identification: u32,
+    interface: usize,
+}
+
+/// Datagram whose fragments are being collected.
+struct PendingDatagram {
+    /// Unfragmentable part of the first fragment, with the offset of the field to update with `next_header`
+    header: Option<(Vec<u8>, usize)>,
+    /// Protocol of the fragmentable part of an IPv6 datagram
+    next_header: u8,
+    /// Offsets and data of the fragments
+    fragments: Vec<(usize, Vec<u8>)>,
+    /// Length of the fragmentable part, known with the last fragment
+    total_len: Option<usize>,
+    packets: Vec<PacketRef>,
+    first_timestamp: Duration,
+    memory: usize,
+}
+
+/// Reassembles the fragmented IP datagrams.
+pub struct Defragmenter {
+    config: DefragConfig,
+    pending: HashMap<FragmentKey, PendingDatagram>,
+    memory: usize,
+    stats: DefragStats,
+}
+
+impl Defragmenter {
+    /// Creates a new [`Defragmenter`].
+    pub fn new(config: DefragConfig) -> Self {
+        Defragmenter { config, pending: HashMap::new(), memory: 0, stats: DefragStats::default() }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &DefragConfig {
+        &self.config
+    }
+
+    /// Returns the counters.
+    pub fn stats(&self) -> &DefragStats {
+        &self.stats
+    }
+
+    /// Returns the number of incomplete datagrams.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the number of bytes buffered for the incomplete datagrams.
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Processes a packet, returns the datagram it carries or completes.
+    ///
+    /// Returns [`None`] if the packet is not an IP packet, if its link type is not supported, or if it is a fragment
+    /// of a datagram which is still incomplete.
+    pub fn add_packet(&mut self, linktype: DataLink, data: &[u8], packet: PacketRef) -> Option<Datagram> {
+        self.expire(packet.timestamp);
+
+        let link = LinkHeader::parse(linktype, data)?;
+        let ip = IpHeader::parse(data.get(link.payload_offset..)?)?;
+        self.stats.packets += 1;
+
+        if !ip.is_fragment() {
+            let len = ip.header_len() + ip.payload().len();
+            let data = data[link.payload_offset..link.payload_offset + len].to_vec();
+            return Some(Datagram { data, packets: vec![packet] });
+        }
+
+        self.stats.fragments += 1;
+        if ip.is_truncated() {
+            self.stats.invalid += 1;
+            return None;
+        }
+
+        let (key, more_fragments, unfragmentable, fragment) = match &ip {
+            IpHeader::V4(ipv4) => {
+                let key = FragmentKey {
+                    src: ip.src(),
+                    dst: ip.dst(),
+                    protocol: ipv4.protocol(),
+                    identification: ipv4.identification() as u32,
+                    interface: packet.interface,
+                };
+                (key, ipv4.more_fragments(), (ipv4.header(), None), ipv4.payload())
+            },
+            IpHeader::V6(ipv6) => {
+                let key = FragmentKey { src: ip.src(), dst: ip.dst(), protocol: 0, identification: ipv6.identification(), interface: packet.interface };
+                let unfragmentable_len = ipv6.unfragmentable_len().expect("A fragment has a fragment header");
+                let network = &data[link.payload_offset..];
+                let end = ipv6.header_len() + ipv6.payload().len();
+                let next_header = (network[unfragmentable_len], ipv6.fragment_next_header());
+                (key, ipv6.more_fragments(), (&network[..unfragmentable_len], Some(next_header)), &network[unfragmentable_len + 8..end])
+            },
+        };
+
+        let offset = ip.fragment_offset() as usize;
+        if (more_fragments && fragment.len() % 8 != 0) || offset + fragment.len() > u16::MAX as usize {
+            self.stats.invalid += 1;
+            return None;
+        }
+
+        let pending = self.pending.entry(key).or_insert_with(|| PendingDatagram {
+            header: None,
+            next_header: 0,
+            fragments: vec![],
+            total_len: None,
+            packets: vec![],
+            first_timestamp: packet.timestamp,
+            memory: 0,
+        });
+
+        // As in the hole algorithm of RFC 815, the last fragment must end after all the others
+        let end = offset + fragment.len();
+        let inconsistent = match pending.total_len {
+            Some(total_len) => end > total_len || (!more_fragments && end != total_len),
+            None => !more_fragments && pending.fragments.iter().any(|(offset, fragment)| offset + fragment.len() > end),
+        };
+        if inconsistent {
+            self.remove(&key);
+            self.stats.invalid += 1;
+            return None;
+        }
+
+        if offset == 0 {
+            let (header, next_header) = unfragmentable;
+            let next_header_pos = match next_header {
+                Some((value, pos)) => {
+                    pending.next_header = value;
+                    pos
+                },
+                None => 0,
+            };
+            pending.memory += header.len();
+            self.memory += header.len();
+            pending.header = Some((header.to_vec(), next_header_pos));
+        }
+        if !more_fragments {
+            pending.total_len = Some(end);
+        }
+        pending.memory += fragment.len();
+        self.memory += fragment.len();
+        pending.fragments.push((offset, fragment.to_vec()));
+        pending.packets.push(packet);
+
+        let datagram = match pending.assemble(matches!(ip, IpHeader::V6(_))) {
+            Some(datagram) => {
+                self.remove(&key);
+                self.stats.reassembled += 1;
+                Some(datagram)
+            },
+            None => None,
+        };
+
+        self.enforce_memory_limit();
+
+        datagram
+    }
+
+    /// Drops the incomplete datagrams whose timeout expired.
+    fn expire(&mut self, now: Duration) {
+        let timeout = self.config.timeout;
+        let expired: Vec<FragmentKey> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.saturating_sub(pending.first_timestamp) > timeout)
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in expired {
+            self.remove(&key);
+            self.stats.expired += 1;
+        }
+    }
+
+    /// Drops the oldest incomplete datagrams until the memory limit is respected.
+    fn enforce_memory_limit(&mut self) {
+        while self.memory > self.config.max_memory {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, pending)| pending.first_timestamp)
+                .map(|(key, _)| *key)
+                .expect("Memory is used by pending datagrams");
+
+            self.remove(&oldest);
+            self.stats.evicted += 1;
+        }
+    }
+
+    /// Removes an incomplete datagram.
+    fn remove(&mut self, key: &FragmentKey) {
+        if let Some(pending) = self.pending.remove(key) {
+            self.memory -= pending.memory;
+        }
+    }
+}
+
+impl PendingDatagram {
+    /// Builds the datagram if all its fragments were received.
+    fn assemble(&mut self, ipv6: bool) -> Option<Datagram> {
+        let total_len = self.total_len?;
+        let (header, next_header_pos) = self.header.as_ref()?;
+
+        // Check that the fragments cover the whole payload
+        self.fragments.sort_by_key(|(offset, _)| *offset);
+        let mut covered = 0;
+        for (offset, fragment) in &self.fragments {
+            if *offset > covered {
+                return None;
+            }
+            covered = covered.max(offset + fragment.len());
+        }
+        if covered < total_len {
+            return None;
+        }
+
+        let mut data = header.clone();
+        let header_len = data.len();
+        data.resize(header_len + total_len, 0);
+        for (offset, fragment) in self.fragments.iter().filter(|(offset, _)| *offset < total_len) {
+            let end = (offset + fragment.len()).min(total_len);
+            data[header_len + offset..header_len + end].copy_from_slice(&fragment[..end - offset]);
+        }
+
+        if ipv6 {
+            let payload_len = u16::try_from(data.len() - 40).ok()?;
+            data[4..6].copy_from_slice(&payload_len.to_be_bytes());
+            data[*next_header_pos] = self.next_header;
+        }
+        else {
+            let total_len = u16::try_from(data.len()).ok()?;
+            data[2..4].copy_from_slice(&total_len.to_be_bytes());
+            // Keep only the Don't Fragment flag
+            data[6] &= 0x40;
+            data[7] = 0;
+            data[10..12].copy_from_slice(&[0, 0]);
+            let checksum = !fold(sum(0, &data[..header_len]));
+            data[10..12].copy_from_slice(&checksum.to_be_bytes());
+        }
+
+        Some(Datagram { data, packets: std::mem::take(&mut self.packets) })
+    }
+}
+
+
+/// Iterator adapter yielding the IP datagrams of the packets of a Pcap, see [`Defragmenter`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapDefrag<I> {
+    inner: I,
+    datalink: DataLink,
+    defrag: Defragmenter,
+    index: u64,
+}
+
+impl<I> PcapDefrag<I> {
+    /// Creates a new [`PcapDefrag`] over an iterator of packets whose link type is `datalink`.
+    pub fn new(inner: I, datalink: DataLink, config: DefragConfig) -> Self {
+        PcapDefrag { inner, datalink, defrag: Defragmenter::new(config), index: 0 }
+    }
+
+    /// Returns the underlying [`Defragmenter`].
+    pub fn defragmenter(&self) -> &Defragmenter {
+        &self.defrag
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapDefrag<I> {
+    type Item = Result<Datagram, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.inner.next()? {
+                Ok(packet) => packet,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let packet_ref = PacketRef { index: self.index, timestamp: packet.timestamp(), interface: 0 };
+            self.index += 1;
+
+            if let Some(datagram) = self.defrag.add_packet(self.datalink, packet.data(), packet_ref) {
+                return Some(Ok(datagram));
+            }
+        }
+    }
+}
+
+/// Iterator adapter yielding the IP datagrams of the packets of a PcapNg, see [`Defragmenter`].
+///
+/// See [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) for the timestamp and data
+/// of the simple packets.
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgDefrag<I> {
+    inner: I,
+    defrag: Defragmenter,
+    /// Link types and snaplens of the interfaces of all the sections
+    interfaces: Vec<(DataLink, u32)>,
+    /// Index in `interfaces` of the first interface of the current section
+    section_start: usize,
+    last_timestamp: Duration,
+    index: u64,
+}
+
+impl<I> PcapNgDefrag<I> {
+    /// Creates a new [`PcapNgDefrag`] over an iterator of blocks.
+    pub fn new(inner: I, config: DefragConfig) -> Self {
+        PcapNgDefrag {
+            inner,
+            defrag: Defragmenter::new(config),
+            interfaces: vec![],
+            section_start: 0,
+            last_timestamp: Duration::ZERO,
+            index: 0,
+        }
+    }
+
+    /// Returns the underlying [`Defragmenter`].
+    pub fn defragmenter(&self) -> &Defragmenter {
+        &self.defrag
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgDefrag<I> {
+    type Item = Result<Datagram, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let block = match self.inner.next()? {
+                Ok(block) => block,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let (interface_id, timestamp, data) = match &block {
+                Block::SectionHeader(_) => {
+                    self.section_start = self.interfaces.len();
+                    continue;
+                },
+                Block::InterfaceDescription(blk) => {
+                    self.interfaces.push((blk.linktype, blk.snaplen));
+                    continue;
+                },
+                Block::EnhancedPacket(blk) => (blk.interface_id as usize, blk.timestamp, &blk.data[..]),
+                Block::Packet(blk) => (blk.interface_id as usize, blk.timestamp, &blk.data[..]),
+                Block::SimplePacket(blk) => {
+                    let snaplen = self.interfaces.get(self.section_start).map_or(0, |&(_, snaplen)| snaplen);
+                    (0, self.last_timestamp, blk.packet_data(snaplen))
+                },
+                _ => continue,
+            };
+
+            self.last_timestamp = timestamp;
+            let interface = self.section_start + interface_id;
+            let packet_ref = PacketRef { index: self.index, timestamp, interface };
+            self.index += 1;
+
+            let Some(&(linktype, _)) = self.interfaces.get(interface)
+            else {
+                return Some(Err(PcapError::InvalidInterfaceId(interface_id as u32)));
+            };
+
+            if let Some(datagram) = self.defrag.add_packet(linktype, data, packet_ref) {
+                return Some(Ok(datagram));
+            }
+        }
+    }
+}
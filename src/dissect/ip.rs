@@ -0,0 +1,403 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+
+/// IP protocol number of the IPv6 hop-by-hop options
+const HOP_BY_HOP: u8 = 0;
+/// IP protocol number of the IPv6 routing header
+const ROUTING: u8 = 43;
+/// IP protocol number of the IPv6 fragment header
+const FRAGMENT: u8 = 44;
+/// IP protocol number of the IPv6 authentication header
+const AUTHENTICATION: u8 = 51;
+/// IP protocol number of the IPv6 destination options
+const DESTINATION_OPTIONS: u8 = 60;
+
+/// Zero-copy view of an IPv4 header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Ipv4Header<'a> {
+    /// Bytes from the start of the header to the end of the packet data
+    data: &'a [u8],
+}
+
+impl<'a> Ipv4Header<'a> {
+    /// Parses an IPv4 header.
+    ///
+    /// Returns [`None`] if the version is not 4 or if the data is too short to contain the header and its options.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let header_len = (*data.first()? & 0x0F) as usize * 4;
+        if data[0] >> 4 != 4 || header_len < 20 || data.len() < header_len {
+            return None;
+        }
+
+        Some(Ipv4Header { data })
+    }
+
+    /// Returns the length of the header, options included.
+    pub fn header_len(&self) -> usize {
+        (self.data[0] & 0x0F) as usize * 4
+    }
+
+    /// Returns the bytes of the header, options included.
+    pub fn header(&self) -> &'a [u8] {
+        &self.data[..self.header_len()]
+    }
+
+    /// Returns the DSCP and ECN field.
+    pub fn tos(&self) -> u8 {
+        self.data[1]
+    }
+
+    /// Returns the total length field.
+    pub fn total_len(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Returns the identification field.
+    pub fn identification(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    /// Returns true if the Don't Fragment flag is set.
+    pub fn dont_fragment(&self) -> bool {
+        self.data[6] & 0x40 != 0
+    }
+
+    /// Returns true if the More Fragments flag is set.
+    pub fn more_fragments(&self) -> bool {
+        self.data[6] & 0x20 != 0
+    }
+
+    /// Returns the offset, in bytes, of the fragment in the original payload.
+    pub fn fragment_offset(&self) -> u16 {
+        (read_u16(self.data, 6) & 0x1FFF) << 3
+    }
+
+    /// Returns true if the packet is a fragment of a bigger one.
+    pub fn is_fragment(&self) -> bool {
+        self.more_fragments() || self.fragment_offset() != 0
+    }
+
+    /// Returns the time to live.
+    pub fn ttl(&self) -> u8 {
+        self.data[8]
+    }
+
+    /// Returns the protocol of the payload.
+    pub fn protocol(&self) -> u8 {
+        self.data[9]
+    }
+
+    /// Returns the header checksum.
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 10)
+    }
+
+    /// Returns true if the header checksum is valid.
+    pub fn checksum_valid(&self) -> bool {
+        fold(sum(0, self.header())) == 0xFFFF
+    }
+
+    /// Returns the source address.
+    pub fn src(&self) -> Ipv4Addr {
+        Ipv4Addr::new(self.data[12], self.data[13], self.data[14], self.data[15])
+    }
+
+    /// Returns the destination address.
+    pub fn dst(&self) -> Ipv4Addr {
+        Ipv4Addr::new(self.data[16], self.data[17], self.data[18], self.data[19])
+    }
+
+    /// Returns the captured part of the payload.
+    ///
+    /// The payload ends at the total length of the packet, or at the end of the data if the packet is truncated.
+    /// A total length of 0, as written by TCP segmentation offload, is treated as the length of the data.
+    pub fn payload(&self) -> &'a [u8] {
+        let end = match self.total_len() as usize {
+            0 => self.data.len(),
+            len => len.clamp(self.header_len(), self.data.len()),
+        };
+        &self.data[self.header_len()..end]
+    }
+
+    /// Returns true if the payload is shorter than announced by the total length.
+    pub fn is_truncated(&self) -> bool {
+        self.data.len() < self.total_len() as usize
+    }
+}
+
+/// Zero-copy view of an IPv6 header and of its extension headers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Ipv6Header<'a> {
+    /// Bytes from the start of the header to the end of the packet data
+    data: &'a [u8],
+    /// Length of the fixed header and of the extension headers
+    header_len: usize,
+    /// Protocol following the extension headers
+    protocol: u8,
+    /// Offset of the fragment header, if any
+    fragment_header: Option<usize>,
+    /// Offset of the next header field announcing the fragment header
+    fragment_next_header: usize,
+}
+
+impl<'a> Ipv6Header<'a> {
+    /// Parses an IPv6 header and its extension headers.
+    ///
+    /// The hop-by-hop options, routing, fragment, authentication and destination options headers are skipped.
+    /// If an extension header is truncated, the parsing stops before it and it becomes the [`protocol`](Self::protocol).
+    /// Returns [`None`] if the version is not 6 or if the data is too short to contain the fixed header.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 40 || data[0] >> 4 != 6 {
+            return None;
+        }
+
+        let mut header = Ipv6Header { data, header_len: 40, protocol: data[6], fragment_header: None, fragment_next_header: 0 };
+        let mut next_header = 6;
+        loop {
+            let offset = header.header_len;
+            let len = match header.protocol {
+                HOP_BY_HOP | ROUTING | DESTINATION_OPTIONS => data.get(offset + 1).map(|&len| (len as usize + 1) * 8),
+                FRAGMENT => Some(8),
+                AUTHENTICATION => data.get(offset + 1).map(|&len| (len as usize + 2) * 4),
+                _ => break,
+            };
+
+            match len {
+                Some(len) if offset + len <= data.len() => {
+                    if header.protocol == FRAGMENT {
+                        header.fragment_header = Some(offset);
+                        header.fragment_next_header = next_header;
+                    }
+                    header.protocol = data[offset];
+                    next_header = offset;
+                    header.header_len += len;
+
+                    // The payload of the next fragments doesn't start with the next header
+                    if header.fragment_offset() != 0 {
+                        break;
+                    }
+                },
+                _ => break,
+            }
+        }
+
+        Some(header)
+    }
+
+    /// Returns the length of the fixed header and of the extension headers.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// Returns the bytes of the fixed header and of the extension headers.
+    pub fn header(&self) -> &'a [u8] {
+        &self.data[..self.header_len]
+    }
+
+    /// Returns the traffic class.
+    pub fn traffic_class(&self) -> u8 {
+        (read_u16(self.data, 0) >> 4) as u8
+    }
+
+    /// Returns the flow label.
+    pub fn flow_label(&self) -> u32 {
+        u32::from_be_bytes(self.data[0..4].try_into().unwrap()) & 0x000F_FFFF
+    }
+
+    /// Returns the payload length field, extension headers included.
+    pub fn payload_len(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    /// Returns the protocol following the extension headers.
+    pub fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    /// Returns the hop limit.
+    pub fn hop_limit(&self) -> u8 {
+        self.data[7]
+    }
+
+    /// Returns the source address.
+    pub fn src(&self) -> Ipv6Addr {
+        Ipv6Addr::from(<[u8; 16]>::try_from(&self.data[8..24]).unwrap())
+    }
+
+    /// Returns the destination address.
+    pub fn dst(&self) -> Ipv6Addr {
+        Ipv6Addr::from(<[u8; 16]>::try_from(&self.data[24..40]).unwrap())
+    }
+
+    /// Returns the identification of the fragment header, 0 if there is none.
+    pub fn identification(&self) -> u32 {
+        self.fragment_header
+            .map(|offset| u32::from_be_bytes(self.data[offset + 4..offset + 8].try_into().unwrap()))
+            .unwrap_or(0)
+    }
+
+    /// Returns true if the More Fragments flag of the fragment header is set.
+    pub fn more_fragments(&self) -> bool {
+        self.fragment_header.is_some_and(|offset| self.data[offset + 3] & 0x01 != 0)
+    }
+
+    /// Returns the offset, in bytes, of the fragment in the original payload.
+    pub fn fragment_offset(&self) -> u16 {
+        self.fragment_header
+            .map(|offset| read_u16(self.data, offset + 2) & 0xFFF8)
+            .unwrap_or(0)
+    }
+
+    /// Returns true if the packet is a fragment of a bigger one.
+    pub fn is_fragment(&self) -> bool {
+        self.more_fragments() || self.fragment_offset() != 0
+    }
+
+    /// Returns the length of the headers preceding the fragment header, which are repeated in each fragment.
+    pub fn unfragmentable_len(&self) -> Option<usize> {
+        self.fragment_header
+    }
+
+    /// Returns the offset of the next header field announcing the fragment header, 0 if there is none.
+    pub(crate) fn fragment_next_header(&self) -> usize {
+        self.fragment_next_header
+    }
+
+    /// Returns the captured part of the payload, following the extension headers.
+    ///
+    /// A payload length of 0, as used by jumbograms, is treated as the length of the data.
+    pub fn payload(&self) -> &'a [u8] {
+        let end = match self.payload_len() as usize {
+            0 => self.data.len(),
+            len => (40 + len).clamp(self.header_len, self.data.len()),
+        };
+        &self.data[self.header_len..end]
+    }
+
+    /// Returns true if the payload is shorter than announced by the payload length.
+    pub fn is_truncated(&self) -> bool {
+        self.data.len() < 40 + self.payload_len() as usize
+    }
+}
+
+/// Zero-copy view of an IPv4 or IPv6 header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IpHeader<'a> {
+    /// IPv4 header
+    V4(Ipv4Header<'a>),
+    /// IPv6 header
+    V6(Ipv6Header<'a>),
+}
+
+impl<'a> IpHeader<'a> {
+    /// Parses an IPv4 or IPv6 header according to its version.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        match data.first()? >> 4 {
+            4 => Ipv4Header::parse(data).map(IpHeader::V4),
+            6 => Ipv6Header::parse(data).map(IpHeader::V6),
+            _ => None,
+        }
+    }
+
+    /// Returns the source address.
+    pub fn src(&self) -> IpAddr {
+        match self {
+            IpHeader::V4(ip) => ip.src().into(),
+            IpHeader::V6(ip) => ip.src().into(),
+        }
+    }
+
+    /// Returns the destination address.
+    pub fn dst(&self) -> IpAddr {
+        match self {
+            IpHeader::V4(ip) => ip.dst().into(),
+            IpHeader::V6(ip) => ip.dst().into(),
+        }
+    }
+
+    /// Returns the protocol of the payload, following the extension headers for IPv6.
+    pub fn protocol(&self) -> u8 {
+        match self {
+            IpHeader::V4(ip) => ip.protocol(),
+            IpHeader::V6(ip) => ip.protocol(),
+        }
+    }
+
+    /// Returns the length of the header.
+    pub fn header_len(&self) -> usize {
+        match self {
+            IpHeader::V4(ip) => ip.header_len(),
+            IpHeader::V6(ip) => ip.header_len(),
+        }
+    }
+
+    /// Returns the captured part of the payload.
+    pub fn payload(&self) -> &'a [u8] {
+        match self {
+            IpHeader::V4(ip) => ip.payload(),
+            IpHeader::V6(ip) => ip.payload(),
+        }
+    }
+
+    /// Returns true if the packet is truncated.
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            IpHeader::V4(ip) => ip.is_truncated(),
+            IpHeader::V6(ip) => ip.is_truncated(),
+        }
+    }
+
+    /// Returns true if the packet is a fragment of a bigger one.
+    pub fn is_fragment(&self) -> bool {
+        match self {
+            IpHeader::V4(ip) => ip.is_fragment(),
+            IpHeader::V6(ip) => ip.is_fragment(),
+        }
+    }
+
+    /// Returns the fragment offset, in bytes.
+    pub fn fragment_offset(&self) -> u16 {
+        match self {
+            IpHeader::V4(ip) => ip.fragment_offset(),
+            IpHeader::V6(ip) => ip.fragment_offset(),
+        }
+    }
+
+    /// Returns the one's complement sum of the pseudo-header of a transport protocol.
+    pub(crate) fn pseudo_header_sum(&self, protocol: u8, len: usize) -> u32 {
+        let (src, dst) = match self {
+            IpHeader::V4(ip) => (&ip.data[12..16], &ip.data[16..20]),
+            IpHeader::V6(ip) => (&ip.data[8..24], &ip.data[24..40]),
+        };
+
+        let sum = sum(sum(0, src), dst);
+        sum + protocol as u32 + (len as u32 >> 16) + (len as u32 & 0xFFFF)
+    }
+}
+
+
+/// Reads a big endian u16, the offset must be in bounds.
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+/// Adds bytes to a one's complement sum, as 16-bit big endian words.
+pub(crate) fn sum(mut sum: u32, data: &[u8]) -> u32 {
+    let mut words = data.chunks_exact(2);
+    for word in &mut words {
+        sum += u16::from_be_bytes([word[0], word[1]]) as u32;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    if let [last] = words.remainder() {
+        sum += (*last as u32) << 8;
+    }
+    sum
+}
+
+/// Folds a one's complement sum into 16 bits.
+pub(crate) fn fold(mut sum: u32) -> u16 {
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum as u16
+}
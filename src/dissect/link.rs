@@ -0,0 +1,130 @@
+use crate::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use crate::pcapng::Block;
+use crate::DataLink;
+
+
+/// EtherType of IPv4
+const ETHERTYPE_IPV4: u16 = 0x0800;
+/// EtherType of ARP
+const ETHERTYPE_ARP: u16 = 0x0806;
+/// EtherType of IPv6
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+
+/// Network-layer protocol of a packet.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NetworkProtocol {
+    /// IPv4
+    Ipv4,
+    /// IPv6
+    Ipv6,
+    /// ARP
+    Arp,
+    /// Other protocol, identified by its EtherType.
+    ///
+    /// The EtherType is 0 when the link header doesn't carry one, like a `NULL` header with an unknown address family.
+    Other(u16),
+}
+
+impl NetworkProtocol {
+    /// Returns the [`NetworkProtocol`] corresponding to an EtherType.
+    pub fn from_ethertype(ethertype: u16) -> Self {
+        match ethertype {
+            ETHERTYPE_IPV4 => NetworkProtocol::Ipv4,
+            ETHERTYPE_IPV6 => NetworkProtocol::Ipv6,
+            ETHERTYPE_ARP => NetworkProtocol::Arp,
+            _ => NetworkProtocol::Other(ethertype),
+        }
+    }
+
+    /// Returns the EtherType of the protocol.
+    pub fn ethertype(&self) -> u16 {
+        match self {
+            NetworkProtocol::Ipv4 => ETHERTYPE_IPV4,
+            NetworkProtocol::Ipv6 => ETHERTYPE_IPV6,
+            NetworkProtocol::Arp => ETHERTYPE_ARP,
+            NetworkProtocol::Other(ethertype) => *ethertype,
+        }
+    }
+}
+
+/// Decoded link-layer header of a packet.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LinkHeader {
+    /// Network-layer protocol of the payload
+    pub protocol: NetworkProtocol,
+    /// Offset of the network-layer payload in the packet data, which is also the length of the link header
+    pub payload_offset: usize,
+}
+
+impl LinkHeader {
+    /// Decodes the link header of a packet.
+    ///
+    /// Returns [`None`] if the link type is not supported or if the packet is too short to contain its link header.
+    pub fn parse(linktype: DataLink, data: &[u8]) -> Option<LinkHeader> {
+        let read_u16 = |offset: usize| Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().unwrap()));
+
+        let (protocol, payload_offset) = match linktype {
+            DataLink::ETHERNET => {
+                let mut len = 14;
+                let mut ethertype = read_u16(12)?;
+                // 802.1Q, 802.1ad and legacy QinQ tags
+                while matches!(ethertype, 0x8100 | 0x88A8 | 0x9100) {
+                    ethertype = read_u16(len + 2)?;
+                    len += 4;
+                }
+                (NetworkProtocol::from_ethertype(ethertype), len)
+            },
+            DataLink::LINUX_SLL => (NetworkProtocol::from_ethertype(read_u16(14)?), 16),
+            DataLink::LINUX_SLL2 => {
+                // The header is 20 bytes long, even if only its protocol type is read
+                if data.len() < 20 {
+                    return None;
+                }
+                (NetworkProtocol::from_ethertype(read_u16(0)?), 20)
+            },
+            DataLink::NULL | DataLink::LOOP => {
+                let family: [u8; 4] = data.get(..4)?.try_into().unwrap();
+                // NULL is in the byte order of the capturing host, LOOP in network byte order
+                let family = match linktype {
+                    DataLink::LOOP => u32::from_be_bytes(family),
+                    _ => u32::from_le_bytes(family).min(u32::from_be_bytes(family)),
+                };
+                let protocol = match family {
+                    2 => NetworkProtocol::Ipv4,
+                    // BSD AF_INET6 values
+                    24 | 28 | 30 => NetworkProtocol::Ipv6,
+                    _ => NetworkProtocol::Other(0),
+                };
+                (protocol, 4)
+            },
+            DataLink::RAW => {
+                let protocol = match data.first()? >> 4 {
+                    4 => NetworkProtocol::Ipv4,
+                    6 => NetworkProtocol::Ipv6,
+                    _ => NetworkProtocol::Other(0),
+                };
+                (protocol, 0)
+            },
+            DataLink::IPV4 => (NetworkProtocol::Ipv4, 0),
+            DataLink::IPV6 => (NetworkProtocol::Ipv6, 0),
+            _ => return None,
+        };
+
+        Some(LinkHeader { protocol, payload_offset })
+    }
+
+    /// Decodes the link header of a PcapNg packet block, using the link type of its interface.
+    ///
+    /// Returns [`None`] if the block is not a packet block, if its interface is unknown or if [`LinkHeader::parse`] fails.
+    /// A [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) belongs to the first interface.
+    pub fn from_block(block: &Block, interfaces: &[InterfaceDescriptionBlock]) -> Option<LinkHeader> {
+        let (interface_id, data) = match block {
+            Block::EnhancedPacket(blk) => (blk.interface_id as usize, &blk.data[..]),
+            Block::Packet(blk) => (blk.interface_id as usize, &blk.data[..]),
+            Block::SimplePacket(blk) => (0, blk.packet_data(interfaces.first()?.snaplen)),
+            _ => return None,
+        };
+
+        LinkHeader::parse(interfaces.get(interface_id)?.linktype, data)
+    }
+}
@@ -0,0 +1,98 @@
+//! Decoding of the link, network and transport headers of the packets.
+//!
+//! [`LinkHeader::parse`] decodes the link header of a packet according to its [`DataLink`](crate::DataLink), taken
+//! from the [`PcapHeader`](crate::pcap::PcapHeader) of a Pcap or from the interface of a PcapNg packet, and returns
+//! its network-layer protocol and the offset of its payload.
+//!
+//! The supported link types are `ETHERNET` (with 802.1Q, 802.1ad and QinQ tags), `LINUX_SLL`, `LINUX_SLL2`,
+//! `NULL`, `LOOP`, `RAW`, `IPV4` and `IPV6`.
+//!
+//! [`PacketHeaders::parse`] goes further and gives zero-copy views of the IPv4/IPv6 header and of the TCP, UDP,
+//! ICMP or ICMPv6 header of the packet. It is also available as [`PcapPacket::headers`](crate::pcap::PcapPacket::headers)
+//! and [`EnhancedPacketBlock::headers`](crate::pcapng::blocks::enhanced_packet::EnhancedPacketBlock::headers).
+//!
+//! # Examples
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::dissect::{LinkHeader, NetworkProtocol};
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! let mut ipv6_packets = 0;
+//! while let Some(block) = blocks.next() {
+//!     let block = block.unwrap();
+//!     if let Some(header) = LinkHeader::from_block(&block, blocks.interfaces()) {
+//!         if header.protocol == NetworkProtocol::Ipv6 {
+//!             ipv6_packets += 1;
+//!         }
+//!     }
+//! }
+//! ```
+
+//!
+//! ```rust,no_run
+//! use std::collections::HashMap;
+//! use std::fs::File;
+//!
+//! use pcap_file::pcap::PcapReader;
+//!
+//! let file_in = File::open("test.pcap").expect("Error opening file");
+//! let mut pcap_reader = PcapReader::new(file_in).unwrap();
+//! let datalink = pcap_reader.header().datalink;
+//!
+//! // Count the packets per 5-tuple
+//! let mut counts = HashMap::new();
+//! while let Some(packet) = pcap_reader.next_packet() {
+//!     let packet = packet.unwrap();
+//!     let Some(headers) = packet.headers(datalink) else { continue };
+//!     let (Some(ip), Some(transport)) = (headers.ip, headers.transport) else { continue };
+//!
+//!     let key = (ip.src(), transport.src_port(), ip.dst(), transport.dst_port(), ip.protocol());
+//!     *counts.entry(key).or_insert(0) += 1;
+//! }
+//! ```
+
+mod ip;
+mod link;
+mod transport;
+
+pub use ip::*;
+pub use link::*;
+pub use transport::*;
+
+use crate::DataLink;
+
+
+/// Zero-copy views of the headers of a packet.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PacketHeaders<'a> {
+    /// Link-layer header
+    pub link: LinkHeader,
+    /// IPv4 or IPv6 header, [`None`] if the network protocol is not IP or if the header is truncated
+    pub ip: Option<IpHeader<'a>>,
+    /// Transport header, [`None`] if the protocol is not supported, if the header is truncated or if the
+    /// packet is an IP fragment other than the first one
+    pub transport: Option<TransportHeader<'a>>,
+}
+
+impl<'a> PacketHeaders<'a> {
+    /// Parses the headers of a packet.
+    ///
+    /// Returns [`None`] if the link header can't be decoded, see [`LinkHeader::parse`].
+    pub fn parse(linktype: DataLink, data: &'a [u8]) -> Option<Self> {
+        let link = LinkHeader::parse(linktype, data)?;
+        let network = data.get(link.payload_offset..).unwrap_or_default();
+
+        let ip = match link.protocol {
+            NetworkProtocol::Ipv4 => Ipv4Header::parse(network).map(IpHeader::V4),
+            NetworkProtocol::Ipv6 => Ipv6Header::parse(network).map(IpHeader::V6),
+            _ => None,
+        };
+        let transport = ip.as_ref().and_then(TransportHeader::parse);
+
+        Some(PacketHeaders { link, ip, transport })
+    }
+}
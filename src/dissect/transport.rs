@@ -0,0 +1,315 @@
+use super::ip::{fold, read_u16, sum, IpHeader};
+
+
+/// IP protocol number of ICMP
+pub(crate) const PROTO_ICMP: u8 = 1;
+/// IP protocol number of TCP
+pub(crate) const PROTO_TCP: u8 = 6;
+/// IP protocol number of UDP
+pub(crate) const PROTO_UDP: u8 = 17;
+/// IP protocol number of ICMPv6
+pub(crate) const PROTO_ICMPV6: u8 = 58;
+
+/// Zero-copy view of a TCP header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TcpHeader<'a> {
+    /// Bytes from the start of the header to the end of the IP payload
+    data: &'a [u8],
+}
+
+impl<'a> TcpHeader<'a> {
+    /// FIN flag
+    pub const FIN: u8 = 0x01;
+    /// SYN flag
+    pub const SYN: u8 = 0x02;
+    /// RST flag
+    pub const RST: u8 = 0x04;
+    /// PSH flag
+    pub const PSH: u8 = 0x08;
+    /// ACK flag
+    pub const ACK: u8 = 0x10;
+    /// URG flag
+    pub const URG: u8 = 0x20;
+    /// ECE flag
+    pub const ECE: u8 = 0x40;
+    /// CWR flag
+    pub const CWR: u8 = 0x80;
+
+    /// Parses a TCP header.
+    ///
+    /// Returns [`None`] if the data is too short to contain the header and its options.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let header_len = (*data.get(12)? >> 4) as usize * 4;
+        if header_len < 20 || data.len() < header_len {
+            return None;
+        }
+
+        Some(TcpHeader { data })
+    }
+
+    /// Returns the source port.
+    pub fn src_port(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    /// Returns the destination port.
+    pub fn dst_port(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Returns the sequence number.
+    pub fn seq(&self) -> u32 {
+        u32::from_be_bytes(self.data[4..8].try_into().unwrap())
+    }
+
+    /// Returns the acknowledgment number.
+    pub fn ack(&self) -> u32 {
+        u32::from_be_bytes(self.data[8..12].try_into().unwrap())
+    }
+
+    /// Returns the length of the header, options included.
+    pub fn header_len(&self) -> usize {
+        (self.data[12] >> 4) as usize * 4
+    }
+
+    /// Returns the bytes of the header, options included.
+    pub fn header(&self) -> &'a [u8] {
+        &self.data[..self.header_len()]
+    }
+
+    /// Returns the flags, see the associated constants.
+    pub fn flags(&self) -> u8 {
+        self.data[13]
+    }
+
+    /// Returns true if all the given flags are set.
+    pub fn has_flags(&self, flags: u8) -> bool {
+        self.flags() & flags == flags
+    }
+
+    /// Returns the window size.
+    pub fn window(&self) -> u16 {
+        read_u16(self.data, 14)
+    }
+
+    /// Returns the checksum.
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 16)
+    }
+
+    /// Returns the urgent pointer.
+    pub fn urgent_pointer(&self) -> u16 {
+        read_u16(self.data, 18)
+    }
+
+    /// Returns the captured part of the payload.
+    pub fn payload(&self) -> &'a [u8] {
+        &self.data[self.header_len()..]
+    }
+
+    /// Returns true if the checksum is valid, [`None`] if it can't be computed because the packet is truncated.
+    ///
+    /// `ip` must be the header this segment was parsed from.
+    pub fn checksum_valid(&self, ip: &IpHeader) -> Option<bool> {
+        transport_checksum_valid(ip, PROTO_TCP, self.data)
+    }
+}
+
+/// Zero-copy view of a UDP header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UdpHeader<'a> {
+    /// Bytes from the start of the header to the end of the IP payload
+    data: &'a [u8],
+}
+
+impl<'a> UdpHeader<'a> {
+    /// Parses a UDP header.
+    ///
+    /// Returns [`None`] if the data is too short to contain the header.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+
+        Some(UdpHeader { data })
+    }
+
+    /// Returns the source port.
+    pub fn src_port(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    /// Returns the destination port.
+    pub fn dst_port(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Returns the length field, header included.
+    pub fn len(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    /// Returns true if the length field is 0.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the checksum.
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 6)
+    }
+
+    /// Returns the captured part of the payload.
+    pub fn payload(&self) -> &'a [u8] {
+        let end = (self.len() as usize).clamp(8, self.data.len());
+        &self.data[8..end]
+    }
+
+    /// Returns true if the checksum is valid, [`None`] if it can't be computed because the packet is truncated.
+    ///
+    /// A checksum of 0 over IPv4 means that no checksum was computed, it is considered valid.
+    /// `ip` must be the header this datagram was parsed from.
+    pub fn checksum_valid(&self, ip: &IpHeader) -> Option<bool> {
+        if self.checksum() == 0 && matches!(ip, IpHeader::V4(_)) {
+            return Some(true);
+        }
+
+        transport_checksum_valid(ip, PROTO_UDP, self.data)
+    }
+}
+
+/// Zero-copy view of an ICMP or ICMPv6 header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IcmpHeader<'a> {
+    /// Bytes from the start of the header to the end of the IP payload
+    data: &'a [u8],
+}
+
+impl<'a> IcmpHeader<'a> {
+    /// Parses an ICMP or ICMPv6 header.
+    ///
+    /// Returns [`None`] if the data is too short to contain the type, code, checksum and the 4 following bytes.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+
+        Some(IcmpHeader { data })
+    }
+
+    /// Returns the type of the message.
+    pub fn icmp_type(&self) -> u8 {
+        self.data[0]
+    }
+
+    /// Returns the code of the message.
+    pub fn code(&self) -> u8 {
+        self.data[1]
+    }
+
+    /// Returns the checksum.
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Returns the 4 bytes following the checksum, whose meaning depends on the type of the message.
+    pub fn rest_of_header(&self) -> [u8; 4] {
+        self.data[4..8].try_into().unwrap()
+    }
+
+    /// Returns the captured part of the payload.
+    pub fn payload(&self) -> &'a [u8] {
+        &self.data[8..]
+    }
+
+    /// Returns true if the checksum is valid, [`None`] if it can't be computed because the packet is truncated.
+    ///
+    /// The checksum of ICMPv6 covers the IPv6 pseudo-header, the one of ICMP doesn't.
+    /// `ip` must be the header this message was parsed from.
+    pub fn checksum_valid(&self, ip: &IpHeader) -> Option<bool> {
+        match ip {
+            IpHeader::V4(_) if ip.is_truncated() => None,
+            IpHeader::V4(_) => Some(fold(sum(0, self.data)) == 0xFFFF),
+            IpHeader::V6(_) => transport_checksum_valid(ip, PROTO_ICMPV6, self.data),
+        }
+    }
+}
+
+/// Zero-copy view of a transport header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TransportHeader<'a> {
+    /// TCP header
+    Tcp(TcpHeader<'a>),
+    /// UDP header
+    Udp(UdpHeader<'a>),
+    /// ICMP header
+    Icmp(IcmpHeader<'a>),
+    /// ICMPv6 header
+    Icmpv6(IcmpHeader<'a>),
+}
+
+impl<'a> TransportHeader<'a> {
+    /// Parses the transport header of an IP packet.
+    ///
+    /// Returns [`None`] if the protocol is not supported, if the header is truncated or if the packet is
+    /// a fragment other than the first one.
+    pub fn parse(ip: &IpHeader<'a>) -> Option<Self> {
+        if ip.fragment_offset() != 0 {
+            return None;
+        }
+
+        let data = ip.payload();
+        match ip.protocol() {
+            PROTO_TCP => TcpHeader::parse(data).map(TransportHeader::Tcp),
+            PROTO_UDP => UdpHeader::parse(data).map(TransportHeader::Udp),
+            PROTO_ICMP => IcmpHeader::parse(data).map(TransportHeader::Icmp),
+            PROTO_ICMPV6 => IcmpHeader::parse(data).map(TransportHeader::Icmpv6),
+            _ => None,
+        }
+    }
+
+    /// Returns the source port, [`None`] for ICMP.
+    pub fn src_port(&self) -> Option<u16> {
+        match self {
+            TransportHeader::Tcp(tcp) => Some(tcp.src_port()),
+            TransportHeader::Udp(udp) => Some(udp.src_port()),
+            TransportHeader::Icmp(_) | TransportHeader::Icmpv6(_) => None,
+        }
+    }
+
+    /// Returns the destination port, [`None`] for ICMP.
+    pub fn dst_port(&self) -> Option<u16> {
+        match self {
+            TransportHeader::Tcp(tcp) => Some(tcp.dst_port()),
+            TransportHeader::Udp(udp) => Some(udp.dst_port()),
+            TransportHeader::Icmp(_) | TransportHeader::Icmpv6(_) => None,
+        }
+    }
+
+    /// Returns the captured part of the payload.
+    pub fn payload(&self) -> &'a [u8] {
+        match self {
+            TransportHeader::Tcp(tcp) => tcp.payload(),
+            TransportHeader::Udp(udp) => udp.payload(),
+            TransportHeader::Icmp(icmp) | TransportHeader::Icmpv6(icmp) => icmp.payload(),
+        }
+    }
+
+    /// Returns true if the checksum is valid, [`None`] if it can't be computed because the packet is truncated.
+    pub fn checksum_valid(&self, ip: &IpHeader) -> Option<bool> {
+        match self {
+            TransportHeader::Tcp(tcp) => tcp.checksum_valid(ip),
+            TransportHeader::Udp(udp) => udp.checksum_valid(ip),
+            TransportHeader::Icmp(icmp) | TransportHeader::Icmpv6(icmp) => icmp.checksum_valid(ip),
+        }
+    }
+}
+
+/// Checks the checksum of a transport protocol covering the IP pseudo-header.
+fn transport_checksum_valid(ip: &IpHeader, protocol: u8, data: &[u8]) -> Option<bool> {
+    if ip.is_truncated() || ip.is_fragment() {
+        return None;
+    }
+
+    Some(fold(sum(ip.pseudo_header_sum(protocol, data.len()), data)) == 0xFFFF)
+}
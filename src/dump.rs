@@ -0,0 +1,364 @@
+//! Hex dumps and human-readable summaries of blocks and packets, like `tshark -x`.
+//!
+//! [`HexDump`] renders bytes as lines of 16 bytes, each with its offset and an ASCII dump:
+//! ```text
+//! 0000  00 11 22 33 44 55 66 77 88 99 aa bb cc dd ee ff   .."3DUfw........
+//! 0010  01 02                                             ..
+//! ```
+//!
+//! [`BlockDump`] and [`PcapPacketDump`] render a [`Block`] or a [`PcapPacket`] as a summary line,
+//! followed by one line per option and by the hex dump of its data. Their output can be read back by
+//! the [`HexDumpReader`](crate::text2pcap::HexDumpReader), which skips the summary and option lines.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::dump::BlockDump;
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! while let Some(block) = pcapng_reader.next_block() {
+//!     println!("{}\n", BlockDump::new(&block.unwrap()));
+//! }
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use crate::pcap::PcapPacket;
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use crate::pcapng::blocks::interface_description::InterfaceDescriptionOption;
+use crate::pcapng::blocks::interface_statistics::InterfaceStatisticsOption;
+use crate::pcapng::blocks::name_resolution::{NameResolutionOption, Record};
+use crate::pcapng::blocks::opt_common::CommonOption;
+use crate::pcapng::blocks::packet::PacketOption;
+use crate::pcapng::blocks::section_header::SectionHeaderOption;
+use crate::pcapng::Block;
+
+
+/// Hex and ASCII dump of bytes.
+///
+/// Each line holds 16 bytes, preceded by their offset and followed by their ASCII dump, in which the non-printable
+/// characters are replaced by dots. The lines are separated by a newline, without a trailing one.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct HexDump<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> HexDump<'a> {
+    /// Creates a new [`HexDump`] of the bytes.
+    pub fn new(data: &'a [u8]) -> Self {
+        HexDump { data }
+    }
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.data.chunks(16).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{:04x} ", i * 16)?;
+            for byte in line {
+                write!(f, " {byte:02x}")?;
+            }
+            write!(f, "{:width$}   ", "", width = (16 - line.len()) * 3)?;
+
+            for &byte in line {
+                let c = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                write!(f, "{c}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Summary and hex dump of a [`Block`].
+///
+/// The first line describes the block, the next ones its records and options, indented by 2 spaces.
+/// The data of the packets, custom, systemd journal and unknown blocks follows as a [`HexDump`].
+/// The snaplen of the interface being unknown, the data of a simple packet block is only cut at its original length.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockDump<'a, 'b> {
+    block: &'a Block<'b>,
+    with_data: bool,
+}
+
+impl<'a, 'b> BlockDump<'a, 'b> {
+    /// Creates a new [`BlockDump`] of the block, with the hex dump of its data.
+    pub fn new(block: &'a Block<'b>) -> Self {
+        BlockDump { block, with_data: true }
+    }
+
+    /// Creates a new [`BlockDump`] of the block, without the hex dump of its data.
+    pub fn summary(block: &'a Block<'b>) -> Self {
+        BlockDump { block, with_data: false }
+    }
+}
+
+impl Display for BlockDump<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let data: &[u8] = match self.block {
+            Block::SectionHeader(blk) => {
+                write!(
+                    f,
+                    "Section Header Block: version {}.{}, {:?} endian, section length {}",
+                    blk.major_version, blk.minor_version, blk.endianness, blk.section_length
+                )?;
+                for option in &blk.options {
+                    match option {
+                        SectionHeaderOption::Hardware(hardware) => write_option(f, "shb_hardware", format_args!("{hardware:?}"))?,
+                        SectionHeaderOption::OS(os) => write_option(f, "shb_os", format_args!("{os:?}"))?,
+                        SectionHeaderOption::UserApplication(app) => write_option(f, "shb_userappl", format_args!("{app:?}"))?,
+                        SectionHeaderOption::Common(common) => write_common_option(f, common)?,
+                    }
+                }
+                &[]
+            },
+            Block::InterfaceDescription(blk) => {
+                write!(f, "Interface Description Block: linktype {:?}, snaplen {}", blk.linktype, blk.snaplen)?;
+                for option in &blk.options {
+                    write_interface_description_option(f, option)?;
+                }
+                &[]
+            },
+            Block::Packet(blk) => {
+                write!(
+                    f,
+                    "Packet Block: interface {}, timestamp {}, captured {}, original {}, drop count {}",
+                    blk.interface_id,
+                    Timestamp(blk.timestamp),
+                    blk.data.len(),
+                    blk.original_len,
+                    blk.drop_count
+                )?;
+                for option in &blk.options {
+                    match option {
+                        PacketOption::Flags(flags) => write_option(f, "pack_flags", format_args!("{flags:#010x}"))?,
+                        PacketOption::Hash(hash) => write_option(f, "pack_hash", Hex(hash))?,
+                        PacketOption::Common(common) => write_common_option(f, common)?,
+                    }
+                }
+                &blk.data
+            },
+            Block::SimplePacket(blk) => {
+                let data = blk.packet_data(0);
+                write!(f, "Simple Packet Block: captured {}, original {}", data.len(), blk.original_len)?;
+                data
+            },
+            Block::NameResolution(blk) => {
+                write!(f, "Name Resolution Block: {} records", blk.records.iter().filter(|record| !matches!(record, Record::End)).count())?;
+                for record in &blk.records {
+                    match record {
+                        Record::End => {},
+                        Record::Ipv4(record) => write_record(f, Address(&record.ip_addr), &record.names)?,
+                        Record::Ipv6(record) => write_record(f, Address(&record.ip_addr), &record.names)?,
+                        Record::Unknown(record) => write!(f, "\n  record {}: {}", record.type_, Hex(&record.value))?,
+                    }
+                }
+                for option in &blk.options {
+                    match option {
+                        NameResolutionOption::NsDnsName(name) => write_option(f, "ns_dnsname", format_args!("{name:?}"))?,
+                        NameResolutionOption::NsDnsIpv4Addr(addr) => write_option(f, "ns_dnsIP4addr", Address(addr))?,
+                        NameResolutionOption::NsDnsIpv6Addr(addr) => write_option(f, "ns_dnsIP6addr", Address(addr))?,
+                        NameResolutionOption::Common(common) => write_common_option(f, common)?,
+                    }
+                }
+                &[]
+            },
+            Block::InterfaceStatistics(blk) => {
+                write!(f, "Interface Statistics Block: interface {}, timestamp {}", blk.interface_id, Timestamp(blk.timestamp))?;
+                for option in &blk.options {
+                    match option {
+                        InterfaceStatisticsOption::IsbStartTime(time) => write_option(f, "isb_starttime", Timestamp(*time))?,
+                        InterfaceStatisticsOption::IsbEndTime(time) => write_option(f, "isb_endtime", Timestamp(*time))?,
+                        InterfaceStatisticsOption::IsbIfRecv(count) => write_option(f, "isb_ifrecv", count)?,
+                        InterfaceStatisticsOption::IsbIfDrop(count) => write_option(f, "isb_ifdrop", count)?,
+                        InterfaceStatisticsOption::IsbFilterAccept(count) => write_option(f, "isb_filteraccept", count)?,
+                        InterfaceStatisticsOption::IsbOsDrop(count) => write_option(f, "isb_osdrop", count)?,
+                        InterfaceStatisticsOption::IsbUsrDeliv(count) => write_option(f, "isb_usrdeliv", count)?,
+                        InterfaceStatisticsOption::Common(common) => write_common_option(f, common)?,
+                    }
+                }
+                &[]
+            },
+            Block::EnhancedPacket(blk) => {
+                write!(
+                    f,
+                    "Enhanced Packet Block: interface {}, timestamp {}, captured {}, original {}",
+                    blk.interface_id,
+                    Timestamp(blk.timestamp),
+                    blk.data.len(),
+                    blk.original_len
+                )?;
+                for option in &blk.options {
+                    match option {
+                        EnhancedPacketOption::Flags(flags) => write_option(f, "epb_flags", format_args!("{flags:#010x}"))?,
+                        EnhancedPacketOption::Hash(hash) => write_option(f, "epb_hash", Hex(hash))?,
+                        EnhancedPacketOption::DropCount(count) => write_option(f, "epb_dropcount", count)?,
+                        EnhancedPacketOption::Common(common) => write_common_option(f, common)?,
+                    }
+                }
+                &blk.data
+            },
+            Block::SystemdJournalExport(blk) => {
+                write!(f, "Systemd Journal Export Block: {} bytes", blk.journal_entry.len())?;
+                &blk.journal_entry
+            },
+            Block::CustomCopiable(blk) => {
+                write!(f, "Custom Block: copiable, PEN {}, {} bytes", blk.pen, blk.payload.len())?;
+                &blk.payload
+            },
+            Block::CustomNonCopiable(blk) => {
+                write!(f, "Custom Block: non-copiable, PEN {}, {} bytes", blk.pen, blk.payload.len())?;
+                &blk.payload
+            },
+            Block::Unknown(blk) => {
+                write!(f, "Unknown Block: type {:#010x}, {} bytes", blk.type_, blk.value.len())?;
+                &blk.value
+            },
+        };
+
+        if self.with_data && !data.is_empty() {
+            write!(f, "\n{}", HexDump::new(data))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Summary and hex dump of a [`PcapPacket`].
+///
+/// The first line describes the packet, the next ones are the [`HexDump`] of its data.
+#[derive(Copy, Clone, Debug)]
+pub struct PcapPacketDump<'a, 'b> {
+    packet: &'a PcapPacket<'b>,
+    with_data: bool,
+}
+
+impl<'a, 'b> PcapPacketDump<'a, 'b> {
+    /// Creates a new [`PcapPacketDump`] of the packet, with the hex dump of its data.
+    pub fn new(packet: &'a PcapPacket<'b>) -> Self {
+        PcapPacketDump { packet, with_data: true }
+    }
+
+    /// Creates a new [`PcapPacketDump`] of the packet, without the hex dump of its data.
+    pub fn summary(packet: &'a PcapPacket<'b>) -> Self {
+        PcapPacketDump { packet, with_data: false }
+    }
+}
+
+impl Display for PcapPacketDump<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let packet = self.packet;
+        write!(f, "Packet: timestamp {}, captured {}, original {}", Timestamp(packet.timestamp()), packet.len(), packet.orig_len())?;
+
+        if self.with_data && !packet.is_empty() {
+            write!(f, "\n{}", HexDump::new(packet.data()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes an option line.
+fn write_option(f: &mut Formatter<'_>, name: &str, value: impl Display) -> fmt::Result {
+    write!(f, "\n  {name}: {value}")
+}
+
+/// Writes the line of a [`CommonOption`].
+fn write_common_option(f: &mut Formatter<'_>, option: &CommonOption) -> fmt::Result {
+    match option {
+        CommonOption::Comment(comment) => write_option(f, "opt_comment", format_args!("{comment:?}")),
+        CommonOption::CustomBinaryCopiable(custom) => write_option(f, "opt_custom", format_args!("PEN {}, {}", custom.pen, Hex(&custom.value))),
+        CommonOption::CustomBinaryNonCopiable(custom) => {
+            write_option(f, "opt_custom", format_args!("PEN {}, non-copiable, {}", custom.pen, Hex(&custom.value)))
+        },
+        CommonOption::CustomUtf8Copiable(custom) => write_option(f, "opt_custom", format_args!("PEN {}, {:?}", custom.pen, custom.value)),
+        CommonOption::CustomUtf8NonCopiable(custom) => {
+            write_option(f, "opt_custom", format_args!("PEN {}, non-copiable, {:?}", custom.pen, custom.value))
+        },
+        CommonOption::Unknown(unknown) => write!(f, "\n  option {}: {}", unknown.code, Hex(&unknown.value)),
+    }
+}
+
+/// Writes the line of an [`InterfaceDescriptionOption`].
+fn write_interface_description_option(f: &mut Formatter<'_>, option: &InterfaceDescriptionOption) -> fmt::Result {
+    match option {
+        InterfaceDescriptionOption::IfName(name) => write_option(f, "if_name", format_args!("{name:?}")),
+        InterfaceDescriptionOption::IfDescription(description) => write_option(f, "if_description", format_args!("{description:?}")),
+        InterfaceDescriptionOption::IfIpv4Addr(addr) => match addr.get(..8) {
+            Some(addr) => write_option(f, "if_IPv4addr", format_args!("{}/{}", Address(&addr[..4]), Address(&addr[4..]))),
+            None => write_option(f, "if_IPv4addr", Hex(addr)),
+        },
+        InterfaceDescriptionOption::IfIpv6Addr(addr) => match addr.get(..17) {
+            Some(addr) => write_option(f, "if_IPv6addr", format_args!("{}/{}", Address(&addr[..16]), addr[16])),
+            None => write_option(f, "if_IPv6addr", Hex(addr)),
+        },
+        InterfaceDescriptionOption::IfMacAddr(addr) => {
+            let mac = addr.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(":");
+            write_option(f, "if_MACaddr", mac)
+        },
+        InterfaceDescriptionOption::IfEuIAddr(addr) => write_option(f, "if_EUIaddr", format_args!("{addr:016x}")),
+        InterfaceDescriptionOption::IfSpeed(speed) => write_option(f, "if_speed", format_args!("{speed} bps")),
+        InterfaceDescriptionOption::IfTsResol(resolution) => write_option(f, "if_tsresol", resolution),
+        InterfaceDescriptionOption::IfTzone(zone) => write_option(f, "if_tzone", zone),
+        InterfaceDescriptionOption::IfFilter(filter) => write_option(f, "if_filter", Hex(filter)),
+        InterfaceDescriptionOption::IfOs(os) => write_option(f, "if_os", format_args!("{os:?}")),
+        InterfaceDescriptionOption::IfFcsLen(len) => write_option(f, "if_fcslen", len),
+        InterfaceDescriptionOption::IfTsOffset(offset) => write_option(f, "if_tsoffset", offset),
+        InterfaceDescriptionOption::IfHardware(hardware) => write_option(f, "if_hardware", format_args!("{hardware:?}")),
+        InterfaceDescriptionOption::Common(common) => write_common_option(f, common),
+    }
+}
+
+/// Writes the line of a name resolution record.
+fn write_record(f: &mut Formatter<'_>, addr: Address, names: &[impl AsRef<str>]) -> fmt::Result {
+    write!(f, "\n  {addr}:")?;
+    for name in names {
+        write!(f, " {:?}", name.as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// Timestamp displayed as seconds and nanoseconds since the EPOCH.
+struct Timestamp(Duration);
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:09}", self.0.as_secs(), self.0.subsec_nanos())
+    }
+}
+
+/// Bytes displayed as a hexadecimal string.
+struct Hex<'a>(&'a [u8]);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// IPv4 or IPv6 address displayed in its usual notation, or as hexadecimal if its length is neither 4 nor 16.
+struct Address<'a>(&'a [u8]);
+
+impl Display for Address<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Ok(addr) = <[u8; 4]>::try_from(self.0) {
+            Ipv4Addr::from(addr).fmt(f)
+        }
+        else if let Ok(addr) = <[u8; 16]>::try_from(self.0) {
+            Ipv6Addr::from(addr).fmt(f)
+        }
+        else {
+            Hex(self.0).fmt(f)
+        }
+    }
+}
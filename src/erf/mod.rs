@@ -0,0 +1,28 @@
+//! Contains the Endace ERF (Extensible Record Format) record parser and reader.
+//!
+//! An ERF capture has no file header, it is a sequence of [`ErfRecord`]s. Each record has a 64-bit fixed-point
+//! [`ErfTimestamp`], a [`ErfType`] describing its payload, optional [`ErfExtensionHeader`]s and, for some types,
+//! a [`ErfTypeHeader`] before the captured packet.
+//!
+//! To convert an ERF capture into a PcapNg see [`erf_to_pcapng`](crate::convert::erf_to_pcapng).
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::erf::ErfReader;
+//!
+//! let file_in = File::open("test.erf").expect("Error opening file");
+//! let mut erf_reader = ErfReader::new(file_in);
+//!
+//! while let Some(record) = erf_reader.next_record() {
+//!     let record = record.unwrap();
+//!     println!("{:?} port {}: {} bytes", record.timestamp, record.interface(), record.packet().len());
+//! }
+//! ```
+
+mod reader;
+mod record;
+
+pub use reader::*;
+pub use record::*;
@@ -0,0 +1,94 @@
+use std::io::Read;
+
+use crate::erf::ErfRecord;
+use crate::errors::*;
+use crate::read_buffer::ReadBuffer;
+
+/// Reads an ERF capture from a reader.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::erf::ErfReader;
+///
+/// let file_in = File::open("test.erf").expect("Error opening file");
+/// let mut erf_reader = ErfReader::new(file_in);
+///
+/// // Read test.erf
+/// while let Some(record) = erf_reader.next_record() {
+///     //Check if there is no error
+///     let record = record.unwrap();
+///
+///     //Do something
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ErfReader<R: Read> {
+    reader: ReadBuffer<R>,
+}
+
+impl<R: Read> ErfReader<R> {
+    /// Creates a new [`ErfReader`] from an existing reader.
+    ///
+    /// An ERF capture has no header, so nothing is read until the first record is requested.
+    pub fn new(reader: R) -> ErfReader<R> {
+        ErfReader { reader: ReadBuffer::new(reader) }
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Returns the next [`ErfRecord`].
+    pub fn next_record(&mut self) -> Option<Result<ErfRecord<'_>, PcapError>> {
+        match self.reader.has_data_left() {
+            Ok(has_data) => {
+                if has_data {
+                    Some(self.reader.parse_with(ErfRecord::from_slice))
+                } else {
+                    None
+                }
+            },
+            Err(e) => Some(Err(PcapError::IoError(e))),
+        }
+    }
+
+    /// Converts the reader into an [`Iterator`] of owned [`ErfRecord`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_records(self) -> ErfRecords<R> {
+        ErfRecords { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned records of an [`ErfReader`], created by [`ErfReader::into_records`].
+#[derive(Debug)]
+pub struct ErfRecords<R: Read> {
+    reader: ErfReader<R>,
+    done: bool,
+}
+
+impl<R: Read> ErfRecords<R> {
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> ErfReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for ErfRecords<R> {
+    type Item = Result<ErfRecord<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_record()?.map(ErfRecord::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
+}
@@ -0,0 +1,464 @@
+use std::borrow::Cow;
+use std::io::Write;
+use std::time::Duration;
+
+use byteorder_slice::byteorder::WriteBytesExt;
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::{BigEndian, LittleEndian};
+use derive_into_owned::IntoOwned;
+
+use crate::errors::*;
+use crate::DataLink;
+
+
+/// Length of the generic header of an ERF record
+const RECORD_HEADER_LEN: usize = 16;
+
+/// Bit of the type field indicating that an extension header follows
+const EXTENSION_BIT: u8 = 0x80;
+
+/// Fixed-point timestamp of an ERF record.
+///
+/// The upper 32 bits are the seconds since the EPOCH and the lower 32 bits are the binary fraction of a second.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ErfTimestamp(pub u64);
+
+impl ErfTimestamp {
+    /// Returns the seconds since the EPOCH.
+    pub fn seconds(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// Returns the fraction of a second, in units of 2^-32 s.
+    pub fn fraction(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Returns the timestamp as a [`Duration`], truncated to the nanosecond.
+    pub fn to_duration(&self) -> Duration {
+        let nanos = (self.fraction() as u64 * 1_000_000_000) >> 32;
+        Duration::new(self.seconds() as u64, nanos as u32)
+    }
+
+    /// Creates a timestamp from a [`Duration`], rounded down to the nearest 2^-32 s.
+    ///
+    /// Returns [`None`] if the seconds don't fit in 32 bits.
+    pub fn from_duration(duration: Duration) -> Option<Self> {
+        let secs: u32 = duration.as_secs().try_into().ok()?;
+        let fraction = ((duration.subsec_nanos() as u64) << 32) / 1_000_000_000;
+        Some(ErfTimestamp(((secs as u64) << 32) | fraction))
+    }
+}
+
+/// Type of an ERF record, which describes its payload.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ErfType {
+    /// Legacy record
+    LEGACY,
+    /// PoS HDLC
+    HDLC_POS,
+    /// Ethernet
+    ETH,
+    /// ATM cell
+    ATM,
+    /// Reassembled AAL5 frame
+    AAL5,
+    /// Multichannel HDLC
+    MC_HDLC,
+    /// Multichannel raw link data
+    MC_RAW,
+    /// Multichannel ATM cell
+    MC_ATM,
+    /// Multichannel raw channel data
+    MC_RAW_CHANNEL,
+    /// Multichannel reassembled AAL5 frame
+    MC_AAL5,
+    /// PoS HDLC with color
+    COLOR_HDLC_POS,
+    /// Ethernet with color
+    COLOR_ETH,
+    /// Multichannel AAL2 frame
+    MC_AAL2,
+    /// IP counter
+    IP_COUNTER,
+    /// TCP flow counter
+    TCP_FLOW_COUNTER,
+    /// PoS HDLC with DSM color
+    DSM_COLOR_HDLC_POS,
+    /// Ethernet with DSM color
+    DSM_COLOR_ETH,
+    /// Multichannel HDLC with color
+    COLOR_MC_HDLC_POS,
+    /// AAL2 frame
+    AAL2,
+    /// PoS HDLC with color and hash
+    COLOR_HASH_POS,
+    /// Ethernet with color and hash
+    COLOR_HASH_ETH,
+    /// Infiniband
+    INFINIBAND,
+    /// Raw IPv4
+    IPV4,
+    /// Raw IPv6
+    IPV6,
+    /// Raw link data
+    RAW_LINK,
+    /// Infiniband link layer
+    INFINIBAND_LINK,
+    /// Provenance metadata
+    META,
+    /// Padding, to be skipped
+    PAD,
+    /// Unknown record type
+    Unknown(u8),
+}
+
+impl ErfType {
+    /// Returns true if the loss counter field of the records of this type is a color.
+    pub fn has_color(self) -> bool {
+        matches!(
+            self,
+            ErfType::COLOR_HDLC_POS
+                | ErfType::COLOR_ETH
+                | ErfType::DSM_COLOR_HDLC_POS
+                | ErfType::DSM_COLOR_ETH
+                | ErfType::COLOR_MC_HDLC_POS
+                | ErfType::COLOR_HASH_POS
+                | ErfType::COLOR_HASH_ETH
+        )
+    }
+
+    /// Returns the length of the header specific to this type, between the extension headers and the packet.
+    pub fn type_header_len(self) -> usize {
+        match self {
+            ErfType::ETH | ErfType::COLOR_ETH | ErfType::DSM_COLOR_ETH | ErfType::COLOR_HASH_ETH => 2,
+            ErfType::MC_HDLC
+            | ErfType::MC_RAW
+            | ErfType::MC_ATM
+            | ErfType::MC_RAW_CHANNEL
+            | ErfType::MC_AAL5
+            | ErfType::MC_AAL2
+            | ErfType::COLOR_MC_HDLC_POS
+            | ErfType::AAL2 => 4,
+            _ => 0,
+        }
+    }
+
+    /// Returns the [`DataLink`] of the packets of this type, [`None`] if they have no pcap equivalent.
+    ///
+    /// The HDLC records are assumed to carry Cisco HDLC frames, like libpcap does by default.
+    pub fn datalink(self) -> Option<DataLink> {
+        match self {
+            ErfType::ETH | ErfType::COLOR_ETH | ErfType::DSM_COLOR_ETH | ErfType::COLOR_HASH_ETH => Some(DataLink::ETHERNET),
+            ErfType::HDLC_POS
+            | ErfType::COLOR_HDLC_POS
+            | ErfType::DSM_COLOR_HDLC_POS
+            | ErfType::COLOR_HASH_POS
+            | ErfType::MC_HDLC
+            | ErfType::COLOR_MC_HDLC_POS => Some(DataLink::C_HDLC),
+            ErfType::IPV4 => Some(DataLink::IPV4),
+            ErfType::IPV6 => Some(DataLink::IPV6),
+            _ => None,
+        }
+    }
+}
+
+impl From<u8> for ErfType {
+    fn from(n: u8) -> ErfType {
+        match n {
+            0 => ErfType::LEGACY,
+            1 => ErfType::HDLC_POS,
+            2 => ErfType::ETH,
+            3 => ErfType::ATM,
+            4 => ErfType::AAL5,
+            5 => ErfType::MC_HDLC,
+            6 => ErfType::MC_RAW,
+            7 => ErfType::MC_ATM,
+            8 => ErfType::MC_RAW_CHANNEL,
+            9 => ErfType::MC_AAL5,
+            10 => ErfType::COLOR_HDLC_POS,
+            11 => ErfType::COLOR_ETH,
+            12 => ErfType::MC_AAL2,
+            13 => ErfType::IP_COUNTER,
+            14 => ErfType::TCP_FLOW_COUNTER,
+            15 => ErfType::DSM_COLOR_HDLC_POS,
+            16 => ErfType::DSM_COLOR_ETH,
+            17 => ErfType::COLOR_MC_HDLC_POS,
+            18 => ErfType::AAL2,
+            19 => ErfType::COLOR_HASH_POS,
+            20 => ErfType::COLOR_HASH_ETH,
+            21 => ErfType::INFINIBAND,
+            22 => ErfType::IPV4,
+            23 => ErfType::IPV6,
+            24 => ErfType::RAW_LINK,
+            25 => ErfType::INFINIBAND_LINK,
+            27 => ErfType::META,
+            48 => ErfType::PAD,
+            _ => ErfType::Unknown(n),
+        }
+    }
+}
+
+impl From<ErfType> for u8 {
+    fn from(record_type: ErfType) -> u8 {
+        match record_type {
+            ErfType::LEGACY => 0,
+            ErfType::HDLC_POS => 1,
+            ErfType::ETH => 2,
+            ErfType::ATM => 3,
+            ErfType::AAL5 => 4,
+            ErfType::MC_HDLC => 5,
+            ErfType::MC_RAW => 6,
+            ErfType::MC_ATM => 7,
+            ErfType::MC_RAW_CHANNEL => 8,
+            ErfType::MC_AAL5 => 9,
+            ErfType::COLOR_HDLC_POS => 10,
+            ErfType::COLOR_ETH => 11,
+            ErfType::MC_AAL2 => 12,
+            ErfType::IP_COUNTER => 13,
+            ErfType::TCP_FLOW_COUNTER => 14,
+            ErfType::DSM_COLOR_HDLC_POS => 15,
+            ErfType::DSM_COLOR_ETH => 16,
+            ErfType::COLOR_MC_HDLC_POS => 17,
+            ErfType::AAL2 => 18,
+            ErfType::COLOR_HASH_POS => 19,
+            ErfType::COLOR_HASH_ETH => 20,
+            ErfType::INFINIBAND => 21,
+            ErfType::IPV4 => 22,
+            ErfType::IPV6 => 23,
+            ErfType::RAW_LINK => 24,
+            ErfType::INFINIBAND_LINK => 25,
+            ErfType::META => 27,
+            ErfType::PAD => 48,
+            ErfType::Unknown(n) => n,
+        }
+    }
+}
+
+/// Extension header of an ERF record.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ErfExtensionHeader(pub u64);
+
+impl ErfExtensionHeader {
+    /// Type of the host ID extension header
+    pub const HOST_ID: u8 = 6;
+
+    /// Returns the type of the extension header.
+    pub fn header_type(&self) -> u8 {
+        (self.0 >> 56) as u8 & 0x7F
+    }
+
+    /// Returns true if another extension header follows this one.
+    pub fn has_more(&self) -> bool {
+        self.0 >> 63 == 1
+    }
+
+    /// Returns the source ID and the 48-bit host ID of a host ID extension header, [`None`] for the other types.
+    pub fn host_id(&self) -> Option<(u8, u64)> {
+        (self.header_type() == Self::HOST_ID).then_some(((self.0 >> 48) as u8, self.0 & 0xFFFF_FFFF_FFFF))
+    }
+}
+
+/// Header specific to the type of an ERF record, between the extension headers and the packet.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ErfTypeHeader {
+    /// No header
+    None,
+    /// Ethernet header
+    Eth {
+        /// Offset of the frame, unused by recent cards
+        offset: u8,
+        /// Padding
+        pad: u8,
+    },
+    /// Multichannel header, with the connection number in its 10 lower bits
+    MultiChannel(u32),
+    /// AAL2 header
+    Aal2(u32),
+}
+
+impl ErfTypeHeader {
+    /// Returns the connection number of a multichannel header, [`None`] for the other headers.
+    pub fn connection(&self) -> Option<u16> {
+        match self {
+            ErfTypeHeader::MultiChannel(header) => Some((header & 0x3FF) as u16),
+            _ => None,
+        }
+    }
+}
+
+/// An ERF record.
+///
+/// The fields of the record are not validated against each other.
+/// The payload can be owned or borrowed.
+#[derive(Clone, Debug, Eq, IntoOwned, PartialEq)]
+pub struct ErfRecord<'a> {
+    /// Timestamp of the record
+    pub timestamp: ErfTimestamp,
+    /// Type of the record, without the extension header bit
+    pub record_type: ErfType,
+    /// Flags: capture interface in the 2 lower bits, then varying length, truncated, receive error and DS error
+    pub flags: u8,
+    /// Number of records lost before this one, or color for the colored types
+    pub loss_counter: u16,
+    /// Length of the packet on the wire
+    pub wire_len: u16,
+    /// Extension headers
+    pub extension_headers: Vec<ErfExtensionHeader>,
+    /// Header specific to the type of the record
+    pub type_header: ErfTypeHeader,
+    /// Remainder of the record, padding included
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> ErfRecord<'a> {
+    /// Parses a new borrowed [`ErfRecord`] from a slice.
+    pub fn from_slice(mut slice: &'a [u8]) -> PcapResult<(&'a [u8], Self)> {
+        // Check header length
+        if slice.len() < RECORD_HEADER_LEN {
+            return Err(PcapError::IncompleteBuffer(RECORD_HEADER_LEN, slice.len()));
+        }
+
+        // Read record header //
+        // Can unwrap because the length check is done before
+        let timestamp = ErfTimestamp(slice.read_u64::<LittleEndian>().unwrap());
+        let type_ = slice.read_u8().unwrap();
+        let flags = slice.read_u8().unwrap();
+        let record_len = slice.read_u16::<BigEndian>().unwrap() as usize;
+        let loss_counter = slice.read_u16::<BigEndian>().unwrap();
+        let wire_len = slice.read_u16::<BigEndian>().unwrap();
+
+        let Some(body_len) = record_len.checked_sub(RECORD_HEADER_LEN)
+        else {
+            return Err(PcapError::InvalidField("ErfRecord: rlen < 16"));
+        };
+
+        if slice.len() < body_len {
+            return Err(PcapError::IncompleteBuffer(body_len, slice.len()));
+        }
+
+        let (mut body, rem) = slice.split_at(body_len);
+
+        // Read extension headers //
+        let mut extension_headers = vec![];
+        let mut has_more = type_ & EXTENSION_BIT != 0;
+        while has_more {
+            let header = ErfExtensionHeader(body.read_u64::<BigEndian>().map_err(|_| PcapError::InvalidField("ErfRecord: truncated extension header"))?);
+            has_more = header.has_more();
+            extension_headers.push(header);
+        }
+
+        // Read type header //
+        let record_type = ErfType::from(type_ & !EXTENSION_BIT);
+        if body.len() < record_type.type_header_len() {
+            return Err(PcapError::InvalidField("ErfRecord: truncated type header"));
+        }
+
+        let type_header = match record_type.type_header_len() {
+            2 => ErfTypeHeader::Eth { offset: body.read_u8().unwrap(), pad: body.read_u8().unwrap() },
+            4 if record_type == ErfType::AAL2 => ErfTypeHeader::Aal2(body.read_u32::<BigEndian>().unwrap()),
+            4 => ErfTypeHeader::MultiChannel(body.read_u32::<BigEndian>().unwrap()),
+            _ => ErfTypeHeader::None,
+        };
+
+        let record = ErfRecord {
+            timestamp,
+            record_type,
+            flags,
+            loss_counter,
+            wire_len,
+            extension_headers,
+            type_header,
+            data: Cow::Borrowed(body),
+        };
+
+        Ok((rem, record))
+    }
+
+    /// Writes an [`ErfRecord`] to a writer.
+    ///
+    /// The extension header bit of the type and the record length are computed from the record,
+    /// the other fields are not validated.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> PcapResult<usize> {
+        let record_len = self.record_len();
+        let Ok(rlen) = u16::try_from(record_len)
+        else {
+            return Err(PcapError::InvalidField("ErfRecord: rlen > u16::MAX"));
+        };
+
+        let mut type_ = u8::from(self.record_type);
+        if !self.extension_headers.is_empty() {
+            type_ |= EXTENSION_BIT;
+        }
+
+        writer.write_u64::<LittleEndian>(self.timestamp.0).map_err(PcapError::IoError)?;
+        writer.write_u8(type_).map_err(PcapError::IoError)?;
+        writer.write_u8(self.flags).map_err(PcapError::IoError)?;
+        writer.write_u16::<BigEndian>(rlen).map_err(PcapError::IoError)?;
+        writer.write_u16::<BigEndian>(self.loss_counter).map_err(PcapError::IoError)?;
+        writer.write_u16::<BigEndian>(self.wire_len).map_err(PcapError::IoError)?;
+
+        for header in &self.extension_headers {
+            writer.write_u64::<BigEndian>(header.0).map_err(PcapError::IoError)?;
+        }
+
+        match self.type_header {
+            ErfTypeHeader::None => {},
+            ErfTypeHeader::Eth { offset, pad } => writer.write_all(&[offset, pad]).map_err(PcapError::IoError)?,
+            ErfTypeHeader::MultiChannel(header) | ErfTypeHeader::Aal2(header) => {
+                writer.write_u32::<BigEndian>(header).map_err(PcapError::IoError)?
+            },
+        }
+
+        writer.write_all(&self.data).map_err(PcapError::IoError)?;
+
+        Ok(record_len)
+    }
+
+    /// Returns the length of the record, headers and padding included.
+    pub fn record_len(&self) -> usize {
+        let type_header_len = match self.type_header {
+            ErfTypeHeader::None => 0,
+            ErfTypeHeader::Eth { .. } => 2,
+            ErfTypeHeader::MultiChannel(_) | ErfTypeHeader::Aal2(_) => 4,
+        };
+
+        RECORD_HEADER_LEN + 8 * self.extension_headers.len() + type_header_len + self.data.len()
+    }
+
+    /// Returns the captured part of the packet, without the padding.
+    pub fn packet(&self) -> &[u8] {
+        &self.data[..self.data.len().min(self.wire_len as usize)]
+    }
+
+    /// Returns the capture interface, also called port.
+    pub fn interface(&self) -> u8 {
+        self.flags & 0x03
+    }
+
+    /// Returns true if the record has a varying length and can be padded.
+    pub fn is_varying_length(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    /// Returns true if the record was truncated because of a lack of buffer space.
+    pub fn is_truncated(&self) -> bool {
+        self.flags & 0x08 != 0
+    }
+
+    /// Returns true if a receive error, like a bad FCS, was detected on the packet.
+    pub fn has_rx_error(&self) -> bool {
+        self.flags & 0x10 != 0
+    }
+
+    /// Returns true if an internal error was detected by the card.
+    pub fn has_ds_error(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    /// Returns the source ID and the host ID of the first host ID extension header, if any.
+    pub fn host_id(&self) -> Option<(u8, u64)> {
+        self.extension_headers.iter().find_map(ErfExtensionHeader::host_id)
+    }
+}
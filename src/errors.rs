@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::DataLink;
+
 /// Result type for the pcap/pcapng parsing
 pub type PcapResult<T> = Result<T, PcapError>;
 
@@ -49,6 +51,14 @@ pub enum PcapError {
     #[error("Packet's included length ({0}) is bigger than the snaplen of the file ({1})")]
     PacketTooLarge(u32, u32),
 
+    /// The packets of a capture have different data link types but the output format supports only one
+    ///
+    /// # Fields
+    /// - 0: data link type of the output
+    /// - 1: data link type of the packet
+    #[error("Packet's data link type ({1:?}) differs from the data link type of the output ({0:?})")]
+    DataLinkMismatch(DataLink, DataLink),
+
     /// Error in custom conversion.
     #[error("Error in custom conversion for PEN {0}: {1}")]
     CustomConversionError(u32, Box<dyn std::error::Error + Sync + Send>),
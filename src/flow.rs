@@ -0,0 +1,282 @@
+//! Aggregation of the packets of a capture into bidirectional flows.
+//!
+//! A [`FlowTable`] groups the IP packets by [`FiveTuple`], both directions of a conversation being the same flow,
+//! and counts their packets, bytes and TCP flags per direction. The flows are exported as [`FlowRecord`]s,
+//! in order of appearance.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::flow::FlowTable;
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! let table = FlowTable::from_pcapng_reader(&mut pcapng_reader).unwrap();
+//! for flow in table {
+//!     println!("{:?}: {} packets", flow.tuple, flow.forward.packets + flow.backward.packets);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::dissect::{PacketHeaders, TransportHeader};
+use crate::errors::PcapError;
+use crate::pcap::{PcapHeader, PcapPacket, PcapReader};
+use crate::pcapng::{Block, PcapNgReader};
+use crate::DataLink;
+
+
+/// Addresses, ports and protocol of a packet.
+///
+/// The ports are 0 for the protocols without ports and for the IP fragments other than the first one.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FiveTuple {
+    /// Source address
+    pub src: IpAddr,
+    /// Source port
+    pub src_port: u16,
+    /// Destination address
+    pub dst: IpAddr,
+    /// Destination port
+    pub dst_port: u16,
+    /// IP protocol number, following the IPv6 extension headers
+    pub protocol: u8,
+}
+
+impl FiveTuple {
+    /// Returns the [`FiveTuple`] of a packet, [`None`] if it is not an IP packet.
+    pub fn from_headers(headers: &PacketHeaders) -> Option<Self> {
+        let ip = headers.ip?;
+        let transport = headers.transport.as_ref();
+
+        Some(FiveTuple {
+            src: ip.src(),
+            src_port: transport.and_then(TransportHeader::src_port).unwrap_or(0),
+            dst: ip.dst(),
+            dst_port: transport.and_then(TransportHeader::dst_port).unwrap_or(0),
+            protocol: ip.protocol(),
+        })
+    }
+
+    /// Returns the [`FiveTuple`] of the opposite direction.
+    pub fn reversed(&self) -> Self {
+        FiveTuple { src: self.dst, src_port: self.dst_port, dst: self.src, dst_port: self.src_port, protocol: self.protocol }
+    }
+
+    /// Returns the same [`FiveTuple`] for both directions.
+    pub(crate) fn canonical(&self) -> Self {
+        let reversed = self.reversed();
+        if reversed < *self { reversed } else { *self }
+    }
+}
+
+/// Counters of one direction of a flow.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlowDirection {
+    /// Number of packets
+    pub packets: u64,
+    /// Sum of the original lengths of the packets
+    pub bytes: u64,
+    /// Union of the TCP flags of the packets, see the constants of [`TcpHeader`](crate::dissect::TcpHeader)
+    pub tcp_flags: u8,
+}
+
+/// Summary of a bidirectional flow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowRecord {
+    /// Five-tuple of the first packet of the flow, which defines the forward direction
+    pub tuple: FiveTuple,
+    /// Timestamp of the first packet
+    pub first_timestamp: Duration,
+    /// Timestamp of the last packet
+    pub last_timestamp: Duration,
+    /// Packets going from the source to the destination of [`tuple`](Self::tuple)
+    pub forward: FlowDirection,
+    /// Packets going from the destination to the source of [`tuple`](Self::tuple)
+    pub backward: FlowDirection,
+    /// Indexes of the interfaces the packets were captured on, in order of appearance.
+    ///
+    /// The interfaces of all the sections of a pcapng are numbered one after the other, a pcap has a single interface.
+    pub interfaces: Vec<usize>,
+}
+
+impl FlowRecord {
+    /// Returns the time elapsed between the first and the last packet of the flow.
+    pub fn duration(&self) -> Duration {
+        self.last_timestamp.saturating_sub(self.first_timestamp)
+    }
+}
+
+/// Table of the bidirectional flows of a capture, computed by streaming its packets once.
+#[derive(Clone, Debug, Default)]
+pub struct FlowTable {
+    /// Flows, in order of appearance
+    flows: Vec<FlowRecord>,
+    /// Index in `flows` of the flow of each canonical five-tuple
+    index: HashMap<FiveTuple, usize>,
+    /// Link types and snaplens of the interfaces of all the sections
+    interfaces: Vec<(DataLink, u32)>,
+    /// Index in `interfaces` of the first interface of the current section
+    section_start: usize,
+    /// Timestamp of the previous packet
+    last_timestamp: Duration,
+    /// Number of packets which are not IP packets
+    skipped_packets: u64,
+}
+
+impl FlowTable {
+    /// Computes the [`FlowTable`] of all the remaining packets of a [`PcapReader`].
+    pub fn from_pcap_reader<R: Read>(reader: &mut PcapReader<R>) -> Result<Self, PcapError> {
+        let mut table = Self::from_pcap_header(&reader.header());
+
+        while let Some(packet) = reader.next_packet() {
+            table.update_from_pcap_packet(&packet?);
+        }
+
+        Ok(table)
+    }
+
+    /// Computes the [`FlowTable`] of all the remaining blocks of a [`PcapNgReader`].
+    pub fn from_pcapng_reader<R: Read>(reader: &mut PcapNgReader<R>) -> Result<Self, PcapError> {
+        let mut table = Self::new();
+
+        while let Some(block) = reader.next_block() {
+            table.update_from_block(&block?);
+        }
+
+        Ok(table)
+    }
+
+    /// Creates an empty [`FlowTable`] for a pcapng.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty [`FlowTable`] for a pcap, with a single interface described by the header.
+    pub fn from_pcap_header(header: &PcapHeader) -> Self {
+        FlowTable { interfaces: vec![(header.datalink, header.snaplen)], ..Self::new() }
+    }
+
+    /// Updates the [`FlowTable`] with the next packet of a pcap.
+    pub fn update_from_pcap_packet(&mut self, packet: &PcapPacket) {
+        self.add_packet(0, packet.timestamp(), packet.data(), packet.orig_len());
+    }
+
+    /// Updates the [`FlowTable`] with the next block of a pcapng.
+    ///
+    /// See [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) for the timestamp and data
+    /// of the simple packets.
+    pub fn update_from_block(&mut self, block: &Block) {
+        match block {
+            Block::SectionHeader(_) => self.section_start = self.interfaces.len(),
+            Block::InterfaceDescription(blk) => self.interfaces.push((blk.linktype, blk.snaplen)),
+            Block::EnhancedPacket(blk) => self.add_packet(blk.interface_id, blk.timestamp, &blk.data, blk.original_len),
+            Block::Packet(blk) => self.add_packet(blk.interface_id as u32, blk.timestamp, &blk.data, blk.original_len),
+            Block::SimplePacket(blk) => {
+                let snaplen = self.interfaces.get(self.section_start).map_or(0, |&(_, snaplen)| snaplen);
+                self.add_packet(0, self.last_timestamp, blk.packet_data(snaplen), blk.original_len);
+            },
+            _ => {},
+        }
+    }
+
+    /// Records a packet.
+    fn add_packet(&mut self, interface_id: u32, timestamp: Duration, data: &[u8], original_len: u32) {
+        self.last_timestamp = timestamp;
+
+        let interface = self.section_start + interface_id as usize;
+        let tuple = self
+            .interfaces
+            .get(interface)
+            .and_then(|&(linktype, _)| PacketHeaders::parse(linktype, data))
+            .and_then(|headers| Some((FiveTuple::from_headers(&headers)?, headers)));
+
+        let Some((tuple, headers)) = tuple
+        else {
+            self.skipped_packets += 1;
+            return;
+        };
+
+        let flows = &mut self.flows;
+        let index = *self.index.entry(tuple.canonical()).or_insert_with(|| {
+            flows.push(FlowRecord {
+                tuple,
+                first_timestamp: timestamp,
+                last_timestamp: timestamp,
+                forward: FlowDirection::default(),
+                backward: FlowDirection::default(),
+                interfaces: vec![],
+            });
+            flows.len() - 1
+        });
+
+        let flow = &mut self.flows[index];
+        flow.first_timestamp = flow.first_timestamp.min(timestamp);
+        flow.last_timestamp = flow.last_timestamp.max(timestamp);
+        if !flow.interfaces.contains(&interface) {
+            flow.interfaces.push(interface);
+        }
+
+        let direction = if flow.tuple == tuple { &mut flow.forward } else { &mut flow.backward };
+        direction.packets += 1;
+        direction.bytes += original_len as u64;
+        if let Some(TransportHeader::Tcp(tcp)) = headers.transport {
+            direction.tcp_flags |= tcp.flags();
+        }
+    }
+
+    /// Returns the flows, in order of appearance.
+    pub fn flows(&self) -> &[FlowRecord] {
+        &self.flows
+    }
+
+    /// Returns the flow of a five-tuple, in either direction.
+    pub fn get(&self, tuple: &FiveTuple) -> Option<&FlowRecord> {
+        self.index.get(&tuple.canonical()).map(|&index| &self.flows[index])
+    }
+
+    /// Returns the number of flows.
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Returns true if there is no flow.
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    /// Returns the number of packets which were not attributed to a flow because they are not IP packets,
+    /// their link type is not supported or their interface is unknown.
+    pub fn skipped_packets(&self) -> u64 {
+        self.skipped_packets
+    }
+
+    /// Returns an iterator over the flows, in order of appearance.
+    pub fn iter(&self) -> std::slice::Iter<'_, FlowRecord> {
+        self.flows.iter()
+    }
+}
+
+impl IntoIterator for FlowTable {
+    type Item = FlowRecord;
+    type IntoIter = std::vec::IntoIter<FlowRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.flows.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FlowTable {
+    type Item = &'a FlowRecord;
+    type IntoIter = std::slice::Iter<'a, FlowRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.flows.iter()
+    }
+}
@@ -0,0 +1,348 @@
+//! Capture file statistics, like `capinfos`.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::info::CaptureInfo;
+//! use pcap_file::pcapng::PcapNgReader;
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! let info = CaptureInfo::from_pcapng_reader(&mut pcapng_reader).unwrap();
+//! println!("{} packets in {:?}", info.packets, info.duration());
+//! ```
+
+use std::io::Read;
+use std::time::Duration;
+
+use crate::errors::PcapError;
+use crate::pcap::{PcapHeader, PcapPacket, PcapReader, RawPcapPacket};
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use crate::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use crate::pcapng::blocks::interface_statistics::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use crate::pcapng::{Block, PcapNgReader};
+use crate::{DataLink, TsResolution};
+
+
+/// Summary of a capture, computed by streaming its packets once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CaptureInfo {
+    /// Number of packets
+    pub packets: u64,
+    /// Sum of the captured lengths of the packets
+    pub captured_bytes: u64,
+    /// Sum of the original lengths of the packets
+    pub original_bytes: u64,
+    /// Timestamp of the earliest packet
+    pub first_timestamp: Option<Duration>,
+    /// Timestamp of the latest packet
+    pub last_timestamp: Option<Duration>,
+    /// Smallest captured length
+    pub min_packet_size: Option<u32>,
+    /// Biggest captured length
+    pub max_packet_size: Option<u32>,
+    /// Number of packets whose captured length is smaller than their original length
+    pub truncated_packets: u64,
+    /// Number of packets whose captured length exceeds the snaplen of their interface
+    pub snaplen_violations: u64,
+    /// True if the timestamps of the packets never decrease
+    pub strict_time_order: bool,
+    /// Interfaces of the capture, in order of appearance.
+    ///
+    /// A pcap has a single interface built from its header.
+    /// The interfaces of all the sections of a pcapng are listed one after the other.
+    pub interfaces: Vec<InterfaceInfo>,
+
+    /// Index in `interfaces` of the first interface of the current section
+    section_start: usize,
+    /// Timestamp of the previous packet
+    previous_timestamp: Option<Duration>,
+}
+
+/// Summary of the packets of an interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterfaceInfo {
+    /// Link type of the interface
+    pub linktype: DataLink,
+    /// Snaplen of the interface, 0 means no limit
+    pub snaplen: u32,
+    /// Name of the interface (`if_name` option)
+    pub name: Option<String>,
+    /// Number of packets
+    pub packets: u64,
+    /// Sum of the captured lengths of the packets
+    pub captured_bytes: u64,
+    /// Sum of the original lengths of the packets
+    pub original_bytes: u64,
+    /// Timestamp of the earliest packet
+    pub first_timestamp: Option<Duration>,
+    /// Timestamp of the latest packet
+    pub last_timestamp: Option<Duration>,
+    /// Sum of the `epb_dropcount` options of the packets
+    pub packet_drops: u64,
+    /// Packets received, from the last `isb_ifrecv` option
+    pub if_recv: Option<u64>,
+    /// Packets dropped by the interface, from the last `isb_ifdrop` option
+    pub if_drop: Option<u64>,
+    /// Packets dropped by the operating system, from the last `isb_osdrop` option
+    pub os_drop: Option<u64>,
+    /// Packets accepted by the filter, from the last `isb_filteraccept` option
+    pub filter_accept: Option<u64>,
+    /// Packets delivered to the user, from the last `isb_usrdeliv` option
+    pub usr_deliv: Option<u64>,
+}
+
+impl CaptureInfo {
+    /// Computes the [`CaptureInfo`] of all the remaining packets of a [`PcapReader`].
+    ///
+    /// The packets are read raw, so that the packets exceeding the snaplen are counted instead of rejected.
+    pub fn from_pcap_reader<R: Read>(reader: &mut PcapReader<R>) -> Result<Self, PcapError> {
+        let header = reader.header();
+        let mut info = Self::from_pcap_header(&header);
+
+        while let Some(packet) = reader.next_raw_packet() {
+            info.update_from_raw_pcap_packet(&packet?, header.ts_resolution);
+        }
+
+        Ok(info)
+    }
+
+    /// Computes the [`CaptureInfo`] of all the remaining blocks of a [`PcapNgReader`].
+    pub fn from_pcapng_reader<R: Read>(reader: &mut PcapNgReader<R>) -> Result<Self, PcapError> {
+        let mut info = Self::new();
+
+        while let Some(block) = reader.next_block() {
+            info.update_from_block(&block?);
+        }
+
+        Ok(info)
+    }
+
+    /// Creates an empty [`CaptureInfo`] for a pcapng.
+    pub fn new() -> Self {
+        CaptureInfo {
+            packets: 0,
+            captured_bytes: 0,
+            original_bytes: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            min_packet_size: None,
+            max_packet_size: None,
+            truncated_packets: 0,
+            snaplen_violations: 0,
+            strict_time_order: true,
+            interfaces: vec![],
+            section_start: 0,
+            previous_timestamp: None,
+        }
+    }
+
+    /// Creates an empty [`CaptureInfo`] for a pcap, with a single interface described by the header.
+    pub fn from_pcap_header(header: &PcapHeader) -> Self {
+        let interface = InterfaceInfo::new(header.datalink, header.snaplen, None);
+        CaptureInfo { interfaces: vec![interface], ..Self::new() }
+    }
+
+    /// Updates the [`CaptureInfo`] with the next packet of a pcap.
+    pub fn update_from_pcap_packet(&mut self, packet: &PcapPacket) {
+        self.add_packet(0, Some(packet.timestamp()), packet.len(), packet.orig_len());
+    }
+
+    /// Updates the [`CaptureInfo`] with the next raw packet of a pcap, whose fields are not validated.
+    pub fn update_from_raw_pcap_packet(&mut self, packet: &RawPcapPacket, ts_resolution: TsResolution) {
+        let ts_frac = match ts_resolution {
+            TsResolution::MicroSecond => Duration::from_micros(packet.ts_frac as u64),
+            TsResolution::NanoSecond => Duration::from_nanos(packet.ts_frac as u64),
+        };
+        let timestamp = Duration::from_secs(packet.ts_sec as u64) + ts_frac;
+
+        self.add_packet(0, Some(timestamp), packet.data.len() as u32, packet.orig_len);
+    }
+
+    /// Updates the [`CaptureInfo`] with the next block of a pcapng.
+    pub fn update_from_block(&mut self, block: &Block) {
+        match block {
+            Block::SectionHeader(_) => {
+                self.section_start = self.interfaces.len();
+            },
+            Block::InterfaceDescription(blk) => {
+                self.interfaces.push(InterfaceInfo::from_interface(blk));
+            },
+            Block::EnhancedPacket(blk) => {
+                self.add_packet(blk.interface_id, Some(blk.timestamp), blk.data.len() as u32, blk.original_len);
+
+                for opt in &blk.options {
+                    if let EnhancedPacketOption::DropCount(drops) = opt {
+                        if let Some(interface) = self.interfaces.get_mut(self.section_start + blk.interface_id as usize) {
+                            interface.packet_drops = interface.packet_drops.saturating_add(*drops);
+                        }
+                    }
+                }
+            },
+            Block::Packet(blk) => {
+                self.add_packet(blk.interface_id as u32, Some(blk.timestamp), blk.data.len() as u32, blk.original_len);
+
+                if let Some(interface) = self.interfaces.get_mut(self.section_start + blk.interface_id as usize) {
+                    interface.packet_drops = interface.packet_drops.saturating_add(blk.drop_count as u64);
+                }
+            },
+            Block::SimplePacket(blk) => {
+                let snaplen = self.interfaces.get(self.section_start).map_or(0, |interface| interface.snaplen);
+                self.add_packet(0, None, blk.packet_data(snaplen).len() as u32, blk.original_len);
+            },
+            Block::InterfaceStatistics(blk) => {
+                if let Some(interface) = self.interfaces.get_mut(self.section_start + blk.interface_id as usize) {
+                    interface.update_from_statistics(blk);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Records a packet.
+    fn add_packet(&mut self, interface_id: u32, timestamp: Option<Duration>, captured_len: u32, original_len: u32) {
+        self.packets += 1;
+        self.captured_bytes += captured_len as u64;
+        self.original_bytes += original_len as u64;
+        self.min_packet_size = Some(self.min_packet_size.map_or(captured_len, |min| min.min(captured_len)));
+        self.max_packet_size = Some(self.max_packet_size.map_or(captured_len, |max| max.max(captured_len)));
+
+        if captured_len < original_len {
+            self.truncated_packets += 1;
+        }
+
+        if let Some(ts) = timestamp {
+            self.first_timestamp = Some(self.first_timestamp.map_or(ts, |first| first.min(ts)));
+            self.last_timestamp = Some(self.last_timestamp.map_or(ts, |last| last.max(ts)));
+
+            if self.previous_timestamp.is_some_and(|previous| ts < previous) {
+                self.strict_time_order = false;
+            }
+            self.previous_timestamp = Some(ts);
+        }
+
+        let Some(interface) = self.interfaces.get_mut(self.section_start + interface_id as usize)
+        else {
+            return;
+        };
+
+        interface.packets += 1;
+        interface.captured_bytes += captured_len as u64;
+        interface.original_bytes += original_len as u64;
+
+        if interface.snaplen != 0 && captured_len > interface.snaplen {
+            self.snaplen_violations += 1;
+        }
+
+        if let Some(ts) = timestamp {
+            interface.first_timestamp = Some(interface.first_timestamp.map_or(ts, |first| first.min(ts)));
+            interface.last_timestamp = Some(interface.last_timestamp.map_or(ts, |last| last.max(ts)));
+        }
+    }
+
+    /// Returns the time elapsed between the first and the last packet.
+    pub fn duration(&self) -> Option<Duration> {
+        Some(self.last_timestamp? - self.first_timestamp?)
+    }
+
+    /// Returns the average captured length of the packets.
+    pub fn average_packet_size(&self) -> Option<f64> {
+        (self.packets != 0).then(|| self.captured_bytes as f64 / self.packets as f64)
+    }
+
+    /// Returns the average number of captured bytes per second.
+    pub fn data_byte_rate(&self) -> Option<f64> {
+        self.per_second(self.captured_bytes)
+    }
+
+    /// Returns the average number of captured bits per second.
+    pub fn data_bit_rate(&self) -> Option<f64> {
+        self.data_byte_rate().map(|rate| rate * 8.0)
+    }
+
+    /// Returns the average number of packets per second.
+    pub fn packet_rate(&self) -> Option<f64> {
+        self.per_second(self.packets)
+    }
+
+    /// Returns the distinct link types of the interfaces, in order of appearance.
+    pub fn encapsulations(&self) -> Vec<DataLink> {
+        let mut encapsulations = vec![];
+        for interface in &self.interfaces {
+            if !encapsulations.contains(&interface.linktype) {
+                encapsulations.push(interface.linktype);
+            }
+        }
+        encapsulations
+    }
+
+    /// Returns the sum of all the drop counters reported by the packets and the statistics blocks, saturating at [`u64::MAX`].
+    pub fn total_drops(&self) -> u64 {
+        self.interfaces
+            .iter()
+            .map(|i| i.packet_drops.saturating_add(i.if_drop.unwrap_or(0)).saturating_add(i.os_drop.unwrap_or(0)))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Divides a value by the duration of the capture, if it isn't zero.
+    fn per_second(&self, value: u64) -> Option<f64> {
+        let secs = self.duration()?.as_secs_f64();
+        (secs > 0.0).then(|| value as f64 / secs)
+    }
+}
+
+impl Default for CaptureInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterfaceInfo {
+    /// Creates an empty [`InterfaceInfo`].
+    fn new(linktype: DataLink, snaplen: u32, name: Option<String>) -> Self {
+        InterfaceInfo {
+            linktype,
+            snaplen,
+            name,
+            packets: 0,
+            captured_bytes: 0,
+            original_bytes: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            packet_drops: 0,
+            if_recv: None,
+            if_drop: None,
+            os_drop: None,
+            filter_accept: None,
+            usr_deliv: None,
+        }
+    }
+
+    /// Creates an empty [`InterfaceInfo`] from an [`InterfaceDescriptionBlock`].
+    fn from_interface(interface: &InterfaceDescriptionBlock) -> Self {
+        let name = interface.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfName(name) => Some(name.to_string()),
+            _ => None,
+        });
+
+        Self::new(interface.linktype, interface.snaplen, name)
+    }
+
+    /// Updates the counters with the options of an [`InterfaceStatisticsBlock`].
+    ///
+    /// The counters are cumulative since the start of the capture, so the last block wins.
+    fn update_from_statistics(&mut self, statistics: &InterfaceStatisticsBlock) {
+        for opt in &statistics.options {
+            match opt {
+                InterfaceStatisticsOption::IsbIfRecv(a) => self.if_recv = Some(*a),
+                InterfaceStatisticsOption::IsbIfDrop(a) => self.if_drop = Some(*a),
+                InterfaceStatisticsOption::IsbOsDrop(a) => self.os_drop = Some(*a),
+                InterfaceStatisticsOption::IsbFilterAccept(a) => self.filter_accept = Some(*a),
+                InterfaceStatisticsOption::IsbUsrDeliv(a) => self.usr_deliv = Some(*a),
+                _ => {},
+            }
+        }
+    }
+}
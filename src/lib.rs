@@ -8,17 +8,97 @@
 //!
 //! For PcapNg files see the [`pcapng`] module, especially [`PcapNgParser`](pcapng::PcapNgParser),
 //! [`PcapNgReader<R>`](pcapng::PcapNgReader) and [`PcapNgWriter<W>`](pcapng::PcapNgWriter)
+//!
+//! For Snoop files see the [`snoop`] module, especially [`SnoopReader<R>`](snoop::SnoopReader)
+//! and [`SnoopWriter<W>`](snoop::SnoopWriter).
+//!
+//! For Endace ERF files see the [`erf`] module, especially [`ErfReader<R>`](erf::ErfReader).
+//!
+//! For Bluetooth HCI btsnoop files see the [`btsnoop`] module, especially [`BtsnoopReader<R>`](btsnoop::BtsnoopReader)
+//! and [`BtsnoopWriter<W>`](btsnoop::BtsnoopWriter).
+//!
+//! For Microsoft Network Monitor 2.x files see the [`netmon`] module, especially [`NetmonReader<R>`](netmon::NetmonReader).
+//!
+//! To convert a Pcap into a PcapNg, or the other way around, see the [`convert`] module.
+//! It also converts the Snoop, ERF and btsnoop captures into PcapNg.
+//!
+//! To read or write the CAN bus logs of `candump -l`, or convert them to and from PcapNg, see the [`candump`] module.
+//!
+//! To import hex dumps into captures, like `text2pcap`, see the [`text2pcap`] module.
+//!
+//! To extract a time window or a range of packets from a capture see the [`slice`] module.
+//!
+//! To compute statistics about a capture, like `capinfos`, see the [`info`] module.
+//!
+//! To decode the link, IP and transport headers of the packets see the [`dissect`] module.
+//!
+//! To render blocks and packets as hex dumps and readable summaries, like `tshark -x`, see the [`dump`] module.
+//!
+//! To aggregate the packets of a capture into bidirectional flows see the [`flow`] module.
+//!
+//! To reassemble fragmented IPv4 and IPv6 datagrams see the [`defrag`] module.
+//!
+//! To reassemble the TCP streams of a capture, like "Follow TCP stream", see the [`stream`] module.
+//!
+//! To detect out-of-order packets or sort them by timestamp see the [`reorder`] module.
+//!
+//! To remove duplicate packets see the [`dedup`] module.
+//!
+//! To shift or rebase the timestamps of a capture see the [`timeshift`] module.
+//!
+//! To reduce the captured length of the packets see the [`truncate`] module.
+//!
+//! To remove the identifying metadata of a PcapNg, like its comments or interface names, see the [`scrub`] module.
+//!
+//! To anonymise the IP addresses of a capture see the `anonymize` module, behind the `anonymize` feature.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for the headers, packets, blocks and options.
+//! Their bytes, like the data of the packets, are written as hexadecimal strings in the human-readable formats.
+//!
+//! To convert a PcapNg into NDJSON, and back, see the `ndjson` module, behind the `ndjson` feature.
+//!
+//! To export the packet metadata into Arrow record batches or Parquet files see the `columnar` module,
+//! behind the `parquet` feature.
 
 
 pub use common::*;
 pub use errors::*;
+pub use rotation::RotationPolicy;
 
 pub(crate) mod common;
 pub(crate) mod errors;
 pub(crate) mod read_buffer;
+pub(crate) mod rotation;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_hex;
 
+#[cfg(feature = "anonymize")]
+pub mod anonymize;
+pub mod btsnoop;
+pub mod candump;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod convert;
+pub mod defrag;
+pub mod dedup;
+pub mod dissect;
+pub mod dump;
+pub mod erf;
+pub mod flow;
+pub mod info;
+#[cfg(feature = "ndjson")]
+pub mod ndjson;
+pub mod netmon;
 pub mod pcap;
 pub mod pcapng;
+pub mod reorder;
+pub mod scrub;
+pub mod slice;
+pub mod snoop;
+pub mod stream;
+pub mod text2pcap;
+pub mod timeshift;
+pub mod truncate;
 
 
 #[allow(dead_code)]
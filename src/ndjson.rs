@@ -0,0 +1,158 @@
+//! Lossless conversion of a PcapNg to NDJSON, and back, behind the `ndjson` feature.
+//!
+//! Each line of the NDJSON is an [`NdjsonBlock`] holding one block of the PcapNg, with its type, decoded fields,
+//! options and data, serialized as in the `serde` feature:
+//! ```text
+//! {"block":{"InterfaceDescription":{"linktype":"ETHERNET","snaplen":0,"options":[{"IfTsResol":9}]}}}
+//! {"block":{"EnhancedPacket":{"interface_id":0,"timestamp":{"secs":1,"nanos":0},"original_len":2,"data":"0102","options":[]}}}
+//! ```
+//!
+//! The lines can be edited with the usual text tools, then [`ndjson_to_pcapng`] writes them back into a PcapNg.
+//! The unknown blocks and options are kept as [`UnknownBlock`](crate::pcapng::blocks::unknown::UnknownBlock)
+//! and [`UnknownOption`](crate::pcapng::blocks::opt_common::UnknownOption).
+//!
+//! A block which would not be written back into the same bytes, like a block with a non-zero padding or a timestamp
+//! which can't be represented exactly, also holds its [`RawBlock`], which is written instead of the decoded block.
+//! A PcapNg converted into NDJSON and back is therefore byte-identical.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! use pcap_file::ndjson::{ndjson_to_pcapng, pcapng_to_ndjson};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let file_out = File::create("test.ndjson").expect("Error creating file");
+//! pcapng_to_ndjson(file_in, file_out).unwrap();
+//!
+//! let file_in = File::open("test.ndjson").expect("Error opening file");
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! ndjson_to_pcapng(BufReader::new(file_in), file_out).unwrap();
+//! ```
+
+use std::borrow::Cow;
+use std::io::{self, BufRead, Read, Write};
+
+use byteorder_slice::{BigEndian, ByteOrder, LittleEndian};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{PcapError, PcapResult};
+use crate::pcapng::blocks::SECTION_HEADER_BLOCK;
+use crate::pcapng::{Block, PcapNgState, RawBlock};
+use crate::read_buffer::ReadBuffer;
+use crate::Endianness;
+
+
+/// A line of the NDJSON, holding a block of the PcapNg.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NdjsonBlock<'a> {
+    /// Decoded block, [`None`] if the block is invalid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<Block<'a>>,
+    /// Raw block, present if the decoded block is absent or wouldn't be written back into the same bytes.
+    ///
+    /// It is written instead of the decoded block, the edits of the decoded block being ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<RawBlock<'a>>,
+}
+
+/// Converts a PcapNg into NDJSON, one [`NdjsonBlock`] per line.
+///
+/// Returns the wrapped writer.
+pub fn pcapng_to_ndjson<R: Read, W: Write>(reader: R, mut writer: W) -> PcapResult<W> {
+    let mut reader = ReadBuffer::new(reader);
+    let mut state = PcapNgState::default();
+
+    while reader.has_data_left()? {
+        let endianness = state.section().endianness;
+        let raw = match endianness {
+            Endianness::Big => reader.parse_with(RawBlock::from_slice::<BigEndian>)?,
+            Endianness::Little => reader.parse_with(RawBlock::from_slice::<LittleEndian>)?,
+        };
+
+        let line = match raw_endianness(&raw, endianness) {
+            Endianness::Big => decode::<BigEndian>(&mut state, raw)?,
+            Endianness::Little => decode::<LittleEndian>(&mut state, raw)?,
+        };
+
+        serde_json::to_writer(&mut writer, &line).map_err(io::Error::from)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(writer)
+}
+
+/// Converts NDJSON, one [`NdjsonBlock`] per line, into a PcapNg.
+///
+/// The empty lines are skipped. Returns the wrapped writer.
+pub fn ndjson_to_pcapng<R: BufRead, W: Write>(reader: R, mut writer: W) -> PcapResult<W> {
+    let mut state = PcapNgState::default();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let line: NdjsonBlock = serde_json::from_str(&line).map_err(io::Error::from)?;
+        match (&line.raw, &line.block) {
+            (Some(raw), _) => {
+                // The state is updated from a borrowed block, as required by its decoding
+                let raw = RawBlock { body: Cow::Borrowed(&raw.body), ..*raw };
+                match raw_endianness(&raw, state.section().endianness) {
+                    Endianness::Big => {
+                        raw.write_to::<BigEndian, _>(&mut writer)?;
+                        state.update_from_raw_block::<BigEndian>(&raw)?;
+                    },
+                    Endianness::Little => {
+                        raw.write_to::<LittleEndian, _>(&mut writer)?;
+                        state.update_from_raw_block::<LittleEndian>(&raw)?;
+                    },
+                }
+            },
+            (None, Some(block)) => {
+                state.update_from_block(block)?;
+                match state.section().endianness {
+                    Endianness::Big => block.write_to::<BigEndian, _>(&state, &mut writer)?,
+                    Endianness::Little => block.write_to::<LittleEndian, _>(&state, &mut writer)?,
+                };
+            },
+            (None, None) => return Err(PcapError::InvalidField("NdjsonBlock: no block")),
+        }
+    }
+
+    Ok(writer)
+}
+
+/// Returns the endianness of a raw block, given by its magic number for a section header.
+fn raw_endianness(raw: &RawBlock, section_endianness: Endianness) -> Endianness {
+    if raw.type_ != SECTION_HEADER_BLOCK {
+        return section_endianness;
+    }
+
+    match raw.body.get(..4) {
+        Some([0x1A, 0x2B, 0x3C, 0x4D]) => Endianness::Big,
+        Some(_) => Endianness::Little,
+        None => section_endianness,
+    }
+}
+
+/// Decodes a raw block, keeping it if the decoded block doesn't encode back into the same bytes.
+fn decode<'a, B: ByteOrder>(state: &mut PcapNgState, raw: RawBlock<'a>) -> PcapResult<NdjsonBlock<'a>> {
+    let Ok(block) = raw.clone().try_into_block::<B>(state)
+    else {
+        state.update_from_raw_block::<B>(&raw)?;
+        return Ok(NdjsonBlock { block: None, raw: Some(raw) });
+    };
+
+    state.update_from_block(&block)?;
+
+    let mut original = Vec::with_capacity(raw.body.len() + 12);
+    raw.write_to::<B, _>(&mut original)?;
+
+    let mut encoded = Vec::with_capacity(original.len());
+    let identical = block.write_to::<B, _>(state, &mut encoded).is_ok() && encoded == original;
+
+    Ok(NdjsonBlock { block: Some(block), raw: (!identical).then_some(raw) })
+}
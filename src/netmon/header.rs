@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::LittleEndian;
+
+use crate::common::days_from_civil;
+use crate::errors::*;
+use crate::DataLink;
+
+
+/// Identification pattern at the start of a NetMon 2.x file
+const NETMON_2_MAGIC: &[u8; 4] = b"GMBU";
+
+/// Length of the NetMon 2.x header
+pub(crate) const NETMON_HEADER_LEN: usize = 72;
+
+/// Base of the media types wrapping a pcap link type
+const MEDIA_TYPE_PCAP_BASE: u16 = 0xE000;
+
+/// Returns the [`DataLink`] of a NetMon media type, [`None`] if it has no pcap equivalent.
+///
+/// The NDIS media types Ethernet (1), Token Ring (2) and FDDI (3) are supported, as well as the media types
+/// `0xE000 | linktype` used by NetMon 3 to store packets with a pcap link type. The metadata frames,
+/// like the network info or the DNS cache, have no equivalent.
+pub fn netmon_datalink(media_type: u16) -> Option<DataLink> {
+    match media_type {
+        1 => Some(DataLink::ETHERNET),
+        2 => Some(DataLink::IEEE802_5),
+        3 => Some(DataLink::FDDI),
+        0xE000..=0xEFFF => Some(DataLink::from((media_type - MEDIA_TYPE_PCAP_BASE) as u32)),
+        _ => None,
+    }
+}
+
+/// NetMon 2.x header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NetmonHeader {
+    /// Major version number, always 2
+    pub version_major: u8,
+    /// Minor version number, which defines the trailer of the frames
+    pub version_minor: u8,
+    /// Media type of the capture, overridden by the one of each frame from version 2.1
+    pub media_type: u16,
+    /// Start of the capture, since the EPOCH.
+    ///
+    /// The file stores it as a date without time zone, it is interpreted as UTC.
+    pub start_time: Duration,
+    /// Offset of the frame table from the start of the file
+    pub frame_table_offset: u32,
+    /// Length of the frame table in bytes
+    pub frame_table_len: u32,
+    /// Offset of the process info table from the start of the file, 0 if there is none
+    pub process_info_offset: u32,
+    /// Number of entries of the process info table
+    pub process_info_count: u32,
+}
+
+impl NetmonHeader {
+    /// Creates a new [`NetmonHeader`] from a slice of bytes.
+    ///
+    /// Returns an error if the slice doesn't start with a valid NetMon 2.x header.
+    ///
+    /// [`PcapError::IncompleteBuffer`] indicates that there is not enough data in the buffer.
+    pub fn from_slice(mut slice: &[u8]) -> PcapResult<(&[u8], NetmonHeader)> {
+        if slice.len() < NETMON_HEADER_LEN {
+            return Err(PcapError::IncompleteBuffer(NETMON_HEADER_LEN, slice.len()));
+        }
+
+        if &slice[..4] != NETMON_2_MAGIC {
+            return Err(PcapError::InvalidField("NetmonHeader: wrong magic number, only NetMon 2.x is supported"));
+        }
+        slice = &slice[4..];
+
+        // Can unwrap because the length check is done before
+        let version_minor = slice.read_u8().unwrap();
+        let version_major = slice.read_u8().unwrap();
+        if version_major != 2 {
+            return Err(PcapError::InvalidField("NetmonHeader: version_major != 2"));
+        }
+
+        let media_type = slice.read_u16::<LittleEndian>().unwrap();
+
+        let mut system_time = [0_u16; 8];
+        for field in &mut system_time {
+            *field = slice.read_u16::<LittleEndian>().unwrap();
+        }
+        let start_time = system_time_to_duration(system_time)?;
+
+        let frame_table_offset = slice.read_u32::<LittleEndian>().unwrap();
+        let frame_table_len = slice.read_u32::<LittleEndian>().unwrap();
+
+        // Skip the user data and the comments
+        slice = &slice[16..];
+
+        let process_info_offset = slice.read_u32::<LittleEndian>().unwrap();
+        let process_info_count = slice.read_u32::<LittleEndian>().unwrap();
+
+        // Skip the network info and the conversation statistics
+        slice = &slice[16..];
+
+        let header = NetmonHeader {
+            version_major,
+            version_minor,
+            media_type,
+            start_time,
+            frame_table_offset,
+            frame_table_len,
+            process_info_offset,
+            process_info_count,
+        };
+
+        Ok((slice, header))
+    }
+
+    /// Returns the [`DataLink`] of the media type of the capture, see [`netmon_datalink`].
+    pub fn datalink(&self) -> Option<DataLink> {
+        netmon_datalink(self.media_type)
+    }
+
+    /// Returns the number of frames of the frame table.
+    pub fn frame_count(&self) -> usize {
+        self.frame_table_len as usize / 4
+    }
+
+    /// Returns the length of the trailer following the data of each frame, which depends on the minor version.
+    pub fn trailer_len(&self) -> usize {
+        match self.version_minor {
+            0 => 0,
+            1 => 2,
+            2 => 6,
+            _ => 15,
+        }
+    }
+}
+
+/// Converts a Windows `SYSTEMTIME` (year, month, day of week, day, hour, minute, second, millisecond) into
+/// a [`Duration`] since the EPOCH.
+fn system_time_to_duration(time: [u16; 8]) -> PcapResult<Duration> {
+    let [year, month, _, day, hour, minute, second, millis] = time.map(|field| field as i64);
+
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(PcapError::InvalidField("NetmonHeader: invalid start date"));
+    }
+
+    if hour > 23 || minute > 59 || second > 59 || millis > 999 {
+        return Err(PcapError::InvalidField("NetmonHeader: invalid start time"));
+    }
+
+    let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    Ok(Duration::from_millis((secs * 1000 + millis) as u64))
+}
@@ -0,0 +1,30 @@
+//! Contains the Microsoft Network Monitor 2.x reader.
+//!
+//! A NetMon capture starts with a [`NetmonHeader`] pointing to a frame table, which holds the offset of each frame.
+//! The frames are therefore read in the order of the table, which requires a seekable reader.
+//!
+//! Each [`NetmonPacket`] has its own media type, mapped to a [`DataLink`](crate::DataLink) by [`netmon_datalink`],
+//! and can be viewed as a [`PcapPacket`](crate::pcap::PcapPacket) to be processed like the packets of a pcap.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::netmon::NetmonReader;
+//!
+//! let file_in = File::open("test.cap").expect("Error opening file");
+//! let mut netmon_reader = NetmonReader::new(file_in).unwrap();
+//!
+//! while let Some(packet) = netmon_reader.next_packet() {
+//!     let packet = packet.unwrap();
+//!     println!("{:?} {:?}: {} bytes", packet.timestamp, packet.datalink(), packet.data.len());
+//! }
+//! ```
+
+mod header;
+mod packet;
+mod reader;
+
+pub use header::*;
+pub use packet::*;
+pub use reader::*;
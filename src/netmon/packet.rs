@@ -0,0 +1,115 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::LittleEndian;
+
+use super::{netmon_datalink, NetmonHeader};
+use crate::errors::*;
+use crate::pcap::PcapPacket;
+use crate::DataLink;
+
+
+/// Length of the header of a NetMon 2.x frame
+pub(crate) const FRAME_HEADER_LEN: usize = 16;
+
+/// Number of 100 ns intervals between 1601-01-01, the origin of a `FILETIME`, and the EPOCH
+const FILETIME_EPOCH_OFFSET: u64 = 116_444_736_000_000_000;
+
+/// Extended information of a frame, stored in its trailer from NetMon 2.2.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct NetmonFrameInfo {
+    /// Index of the process which sent or received the frame in the process info table, from version 2.2
+    pub process_info_index: Option<u32>,
+    /// Timestamp of the frame as a UTC `FILETIME`, in 100 ns intervals since 1601-01-01, from version 2.3
+    pub utc_timestamp: Option<u64>,
+    /// Index of the time zone of the capture, from version 2.3
+    pub timezone_index: Option<u8>,
+}
+
+/// A NetMon 2.x frame.
+///
+/// The payload can be owned or borrowed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetmonPacket<'a> {
+    /// Timestamp EPOCH of the packet.
+    ///
+    /// It is the UTC timestamp of the frame if there is one, the start time of the capture plus the offset of the frame
+    /// otherwise.
+    pub timestamp: Duration,
+    /// Original length of the packet when captured on the wire
+    pub orig_len: u32,
+    /// Media type of the frame, the one of the header before NetMon 2.1
+    pub media_type: u16,
+    /// Extended information of the frame
+    pub info: NetmonFrameInfo,
+    /// Payload, owned or borrowed, of the packet
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> NetmonPacket<'a> {
+    /// Parses a new borrowed [`NetmonPacket`] from a slice, its trailer included.
+    pub fn from_slice(mut slice: &'a [u8], header: &NetmonHeader) -> PcapResult<(&'a [u8], Self)> {
+        // Check header length
+        if slice.len() < FRAME_HEADER_LEN {
+            return Err(PcapError::IncompleteBuffer(FRAME_HEADER_LEN, slice.len()));
+        }
+
+        // Read frame header //
+        // Can unwrap because the length check is done before
+        let ts_delta = slice.read_u64::<LittleEndian>().unwrap();
+        let orig_len = slice.read_u32::<LittleEndian>().unwrap();
+        let incl_len = slice.read_u32::<LittleEndian>().unwrap() as usize;
+
+        let body_len = incl_len + header.trailer_len();
+        if slice.len() < body_len {
+            return Err(PcapError::IncompleteBuffer(body_len, slice.len()));
+        }
+
+        let (data, mut trailer) = slice[..body_len].split_at(incl_len);
+        let rem = &slice[body_len..];
+
+        // Read trailer //
+        // Can unwrap because the trailer length is checked with the body length
+        let mut media_type = header.media_type;
+        let mut info = NetmonFrameInfo::default();
+        if header.version_minor >= 1 {
+            media_type = trailer.read_u16::<LittleEndian>().unwrap();
+        }
+        if header.version_minor >= 2 {
+            info.process_info_index = Some(trailer.read_u32::<LittleEndian>().unwrap());
+        }
+        if header.version_minor >= 3 {
+            info.utc_timestamp = Some(trailer.read_u64::<LittleEndian>().unwrap());
+            info.timezone_index = Some(trailer.read_u8().unwrap());
+        }
+
+        let timestamp = info
+            .utc_timestamp
+            .and_then(|filetime| filetime.checked_sub(FILETIME_EPOCH_OFFSET))
+            .map(|intervals| Duration::from_nanos(intervals.saturating_mul(100)))
+            .unwrap_or_else(|| header.start_time + Duration::from_micros(ts_delta));
+
+        let packet = NetmonPacket { timestamp, orig_len: orig_len.max(incl_len as u32), media_type, info, data: Cow::Borrowed(data) };
+
+        Ok((rem, packet))
+    }
+
+    /// Returns the [`DataLink`] of the media type of the frame, see [`netmon_datalink`].
+    pub fn datalink(&self) -> Option<DataLink> {
+        netmon_datalink(self.media_type)
+    }
+
+    /// Returns a [`PcapPacket`] borrowing the payload of this frame.
+    ///
+    /// # Errors
+    /// The timestamp is after 2106, which doesn't fit in a pcap.
+    pub fn as_pcap_packet(&self) -> PcapResult<PcapPacket<'_>> {
+        PcapPacket::new(self.timestamp, self.orig_len, &self.data[..])
+    }
+
+    /// Returns an owned version of the packet.
+    pub fn into_owned(self) -> NetmonPacket<'static> {
+        NetmonPacket { data: Cow::Owned(self.data.into_owned()), ..self }
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use super::header::NETMON_HEADER_LEN;
+use super::packet::FRAME_HEADER_LEN;
+use crate::errors::*;
+use crate::netmon::{NetmonHeader, NetmonPacket};
+
+/// Reads a NetMon 2.x capture from a seekable reader.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::netmon::NetmonReader;
+///
+/// let file_in = File::open("test.cap").expect("Error opening file");
+/// let mut netmon_reader = NetmonReader::new(file_in).unwrap();
+///
+/// // Read test.cap
+/// while let Some(pkt) = netmon_reader.next_packet() {
+///     //Check if there is no error
+///     let pkt = pkt.unwrap();
+///
+///     //Do something
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NetmonReader<R: Read + Seek> {
+    header: NetmonHeader,
+    frame_table: Vec<u32>,
+    next_frame: usize,
+    buffer: Vec<u8>,
+    reader: R,
+}
+
+impl<R: Read + Seek> NetmonReader<R> {
+    /// Creates a new [`NetmonReader`] from an existing reader.
+    ///
+    /// This function reads the NetMon header and the frame table.
+    ///
+    /// # Errors
+    /// The data stream is not in a valid NetMon 2.x file format.
+    ///
+    /// The underlying data are not readable.
+    pub fn new(mut reader: R) -> Result<NetmonReader<R>, PcapError> {
+        let mut header_buf = [0_u8; NETMON_HEADER_LEN];
+        reader.read_exact(&mut header_buf).map_err(PcapError::IoError)?;
+        let (_, header) = NetmonHeader::from_slice(&header_buf)?;
+
+        let mut table_buf = vec![];
+        reader.seek(SeekFrom::Start(header.frame_table_offset as u64)).map_err(PcapError::IoError)?;
+        reader.by_ref().take(header.frame_table_len as u64).read_to_end(&mut table_buf).map_err(PcapError::IoError)?;
+        if table_buf.len() < header.frame_table_len as usize {
+            return Err(PcapError::InvalidField("NetmonHeader: frame table out of the file"));
+        }
+
+        let frame_table = table_buf.chunks_exact(4).map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())).collect();
+
+        Ok(NetmonReader { header, frame_table, next_frame: 0, buffer: vec![], reader })
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Returns the next [`NetmonPacket`], in the order of the frame table.
+    pub fn next_packet(&mut self) -> Option<Result<NetmonPacket<'_>, PcapError>> {
+        let offset = *self.frame_table.get(self.next_frame)?;
+        self.next_frame += 1;
+
+        Some(self.read_frame(offset))
+    }
+
+    /// Reads the frame at an offset of the file into the buffer and parses it.
+    fn read_frame(&mut self, offset: u32) -> Result<NetmonPacket<'_>, PcapError> {
+        self.buffer.resize(FRAME_HEADER_LEN, 0);
+        self.reader.seek(SeekFrom::Start(offset as u64)).map_err(PcapError::IoError)?;
+        self.reader.read_exact(&mut self.buffer).map_err(PcapError::IoError)?;
+
+        let incl_len = u32::from_le_bytes(self.buffer[12..16].try_into().unwrap()) as usize;
+        let body_len = incl_len + self.header.trailer_len();
+
+        self.reader.by_ref().take(body_len as u64).read_to_end(&mut self.buffer).map_err(PcapError::IoError)?;
+        if self.buffer.len() < FRAME_HEADER_LEN + body_len {
+            return Err(PcapError::InvalidField("NetmonPacket: frame out of the file"));
+        }
+
+        NetmonPacket::from_slice(&self.buffer, &self.header).map(|(_, packet)| packet)
+    }
+
+    /// Returns the header of the capture.
+    pub fn header(&self) -> NetmonHeader {
+        self.header
+    }
+
+    /// Returns the number of frames of the capture.
+    pub fn frame_count(&self) -> usize {
+        self.frame_table.len()
+    }
+
+    /// Converts the reader into an [`Iterator`] of owned [`NetmonPacket`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_packets(self) -> NetmonPackets<R> {
+        NetmonPackets { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned packets of a [`NetmonReader`], created by [`NetmonReader::into_packets`].
+#[derive(Debug)]
+pub struct NetmonPackets<R: Read + Seek> {
+    reader: NetmonReader<R>,
+    done: bool,
+}
+
+impl<R: Read + Seek> NetmonPackets<R> {
+    /// Returns the header of the capture.
+    pub fn header(&self) -> NetmonHeader {
+        self.reader.header()
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> NetmonReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read + Seek> Iterator for NetmonPackets<R> {
+    type Item = Result<NetmonPacket<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_packet()?.map(NetmonPacket::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
+}
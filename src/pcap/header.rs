@@ -10,6 +10,7 @@ use crate::{DataLink, Endianness, TsResolution};
 
 /// Pcap Global Header
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PcapHeader {
     /// Major version number
     pub version_major: u16,
@@ -4,10 +4,12 @@ mod header;
 mod packet;
 mod parser;
 mod reader;
+mod rotating_writer;
 mod writer;
 
 pub use header::*;
 pub use packet::*;
 pub use parser::*;
 pub use reader::*;
+pub use rotating_writer::*;
 pub use writer::*;
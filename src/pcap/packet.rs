@@ -8,18 +8,23 @@ use byteorder_slice::result::ReadSlice;
 use derive_into_owned::IntoOwned;
 
 use crate::TsResolution;
+use crate::dissect::PacketHeaders;
 use crate::errors::*;
+use crate::DataLink;
 
 /// A valid pcap packet.
 ///
 /// The payload can be owned or borrowed.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PcapPacketFields<'a>"))]
 pub struct PcapPacket<'a> {
     /// Timestamp EPOCH of the packet
     timestamp: Duration,
     /// Original length of the packet when captured on the wire
     orig_len: u32,
     /// Payload, owned or borrowed, of the packet
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     data: Cow<'a, [u8]>,
 }
 
@@ -72,6 +77,12 @@ impl<'a> PcapPacket<'a> {
         &self.data
     }
 
+    /// Parses the headers of the packet, `datalink` being the one of the [`PcapHeader`](crate::pcap::PcapHeader),
+    /// see [`PacketHeaders::parse`].
+    pub fn headers(&self, datalink: DataLink) -> Option<PacketHeaders<'_>> {
+        PacketHeaders::parse(datalink, &self.data)
+    }
+
     /// Returns the packet payload.
     pub fn into_data(self) -> Cow<'a, [u8]> {
         self.data
@@ -156,10 +167,30 @@ impl<'a> PcapPacket<'a> {
     }
 }
 
+/// Fields of a deserialized [`PcapPacket`], validated by [`PcapPacket::new`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PcapPacketFields<'a> {
+    timestamp: Duration,
+    orig_len: u32,
+    #[serde(with = "crate::serde_hex")]
+    data: Cow<'a, [u8]>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<PcapPacketFields<'a>> for PcapPacket<'a> {
+    type Error = PcapError;
+
+    fn try_from(fields: PcapPacketFields<'a>) -> PcapResult<Self> {
+        PcapPacket::new(fields.timestamp, fields.orig_len, fields.data)
+    }
+}
+
 /// Raw Pcap packet with its header and data.
 /// The fields of the packet are not validated.
 /// The payload can be owned or borrowed.
 #[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawPcapPacket<'a> {
     /// Timestamp in seconds
     pub ts_sec: u32,
@@ -170,6 +201,7 @@ pub struct RawPcapPacket<'a> {
     /// Original length of the packet on the wire
     pub orig_len: u32,
     /// Payload, owned or borrowed, of the packet
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub data: Cow<'a, [u8]>,
 }
 
@@ -79,4 +79,45 @@ impl<R: Read> PcapReader<R> {
     pub fn header(&self) -> PcapHeader {
         self.parser.header()
     }
+
+    /// Converts the reader into an [`Iterator`] of owned [`PcapPacket`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_packets(self) -> PcapPackets<R> {
+        PcapPackets { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned packets of a [`PcapReader`], created by [`PcapReader::into_packets`].
+#[derive(Debug)]
+pub struct PcapPackets<R: Read> {
+    reader: PcapReader<R>,
+    done: bool,
+}
+
+impl<R: Read> PcapPackets<R> {
+    /// Returns the global header of the pcap.
+    pub fn header(&self) -> PcapHeader {
+        self.reader.header()
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> PcapReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for PcapPackets<R> {
+    type Item = Result<PcapPacket<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_packet()?.map(PcapPacket::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
 }
@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::RawPcapPacket;
+use crate::errors::*;
+use crate::pcap::{PcapHeader, PcapPacket, PcapWriter};
+use crate::rotation::{FileProgress, RotationFiles, RotationPolicy};
+use crate::TsResolution;
+
+
+/// Writes a pcap to a ring buffer of files, switching to a new file when a threshold of the [`RotationPolicy`] is reached.
+///
+/// Every file starts with the same [`PcapHeader`].
+///
+/// # Example
+/// ```rust,no_run
+/// use std::fs::File;
+/// use std::time::Duration;
+///
+/// use pcap_file::pcap::{PcapReader, RotatingPcapWriter};
+/// use pcap_file::RotationPolicy;
+///
+/// let file_in = File::open("test.pcap").expect("Error opening file");
+/// let mut pcap_reader = PcapReader::new(file_in).unwrap();
+///
+/// // Keep at most 10 files of 1 MB each
+/// let policy = RotationPolicy { max_file_size: Some(1_000_000), max_files: Some(10), ..Default::default() };
+/// let mut pcap_writer = RotatingPcapWriter::with_header("out.pcap", pcap_reader.header(), policy).unwrap();
+///
+/// while let Some(pkt) = pcap_reader.next_packet() {
+///     pcap_writer.write_packet(&pkt.unwrap()).unwrap();
+/// }
+///
+/// let files = pcap_writer.close().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RotatingPcapWriter {
+    header: PcapHeader,
+    policy: RotationPolicy,
+    files: RotationFiles,
+    progress: FileProgress,
+    writer: PcapWriter<BufWriter<File>>,
+}
+
+impl RotatingPcapWriter {
+    /// Creates a new [`RotatingPcapWriter`] writing the default [`PcapHeader`] with the native endianness.
+    ///
+    /// The files are named after `path` with an index inserted before the extension,
+    /// e.g. `out.pcap` gives `out_00001.pcap`, `out_00002.pcap`...
+    ///
+    /// # Errors
+    /// The first file can't be created or written to.
+    pub fn new(path: impl Into<PathBuf>, policy: RotationPolicy) -> PcapResult<Self> {
+        let header = PcapHeader { endianness: crate::Endianness::native(), ..Default::default() };
+        Self::with_header(path, header, policy)
+    }
+
+    /// Creates a new [`RotatingPcapWriter`] with a user defined [`PcapHeader`].
+    ///
+    /// # Errors
+    /// The first file can't be created or written to.
+    pub fn with_header(path: impl Into<PathBuf>, header: PcapHeader, policy: RotationPolicy) -> PcapResult<Self> {
+        let mut files = RotationFiles::new(path.into(), policy.max_files)?;
+        let writer = PcapWriter::with_header(files.next_file()?, header)?;
+        let progress = FileProgress { bytes_written: 24, ..Default::default() };
+
+        Ok(Self { header, policy, files, progress, writer })
+    }
+
+    /// Writes a [`PcapPacket`], switching to a new file first if needed.
+    pub fn write_packet(&mut self, packet: &PcapPacket) -> PcapResult<usize> {
+        self.rotate_if_needed(packet.timestamp())?;
+
+        let len = self.writer.write_packet(packet)?;
+        self.progress.add_packet(Some(packet.timestamp()), len);
+
+        Ok(len)
+    }
+
+    /// Writes a [`RawPcapPacket`], switching to a new file first if needed.
+    ///
+    /// The fields of the packet are not validated, see [`PcapWriter::write_raw_packet`].
+    pub fn write_raw_packet(&mut self, packet: &RawPcapPacket) -> PcapResult<usize> {
+        let ts_nanos = match self.header.ts_resolution {
+            TsResolution::MicroSecond => packet.ts_frac.saturating_mul(1000),
+            TsResolution::NanoSecond => packet.ts_frac,
+        };
+        let timestamp = Duration::new(packet.ts_sec as u64, 0).saturating_add(Duration::from_nanos(ts_nanos as u64));
+
+        self.rotate_if_needed(timestamp)?;
+
+        let len = self.writer.write_raw_packet(packet)?;
+        self.progress.add_packet(Some(timestamp), len);
+
+        Ok(len)
+    }
+
+    /// Switches to a new file.
+    pub fn rotate(&mut self) -> PcapResult<()> {
+        let file = self.files.next_file()?;
+        let old_writer = std::mem::replace(&mut self.writer, PcapWriter::with_header(file, self.header)?);
+
+        self.progress = FileProgress { bytes_written: 24, ..Default::default() };
+
+        old_writer
+            .into_writer()
+            .into_inner()
+            .map_err(|e| PcapError::IoError(e.into_error()))?;
+
+        Ok(())
+    }
+
+    /// Switches to a new file if the packet timestamp or the current file size reaches a threshold.
+    fn rotate_if_needed(&mut self, timestamp: Duration) -> PcapResult<()> {
+        if self.policy.must_rotate(&self.progress, Some(timestamp)) {
+            self.rotate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush data
+    pub fn flush(&mut self) -> PcapResult<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the paths of the files kept on disk, oldest first.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.paths()
+    }
+
+    /// Returns the [`PcapHeader`] written at the start of every file.
+    pub fn header(&self) -> PcapHeader {
+        self.header
+    }
+
+    /// Flushes and closes the current file, returning the paths of the files kept on disk, oldest first.
+    pub fn close(self) -> PcapResult<Vec<PathBuf>> {
+        self.writer
+            .into_writer()
+            .into_inner()
+            .map_err(|e| PcapError::IoError(e.into_error()))?;
+
+        Ok(self.files.paths().map(Path::to_path_buf).collect())
+    }
+}
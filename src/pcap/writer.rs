@@ -5,7 +5,7 @@ use byteorder_slice::{BigEndian, LittleEndian};
 use super::RawPcapPacket;
 use crate::errors::*;
 use crate::pcap::{PcapHeader, PcapPacket};
-use crate::{Endianness, TsResolution};
+use crate::{DataLink, Endianness, TsResolution};
 
 
 /// Writes a pcap to a writer.
@@ -33,6 +33,7 @@ use crate::{Endianness, TsResolution};
 /// ```
 #[derive(Debug)]
 pub struct PcapWriter<W: Write> {
+    datalink: DataLink,
     endianness: Endianness,
     snaplen: u32,
     ts_resolution: TsResolution,
@@ -76,6 +77,7 @@ impl<W: Write> PcapWriter<W> {
         header.write_to(&mut writer)?;
 
         Ok(PcapWriter {
+            datalink: header.datalink,
             endianness: header.endianness,
             snaplen: header.snaplen,
             ts_resolution: header.ts_resolution,
@@ -114,6 +116,11 @@ impl<W: Write> PcapWriter<W> {
         self.writer.flush().map_err(PcapError::IoError)
     }
 
+    /// Returns the data link type written in the header.
+    pub fn datalink(&self) -> DataLink {
+        self.datalink
+    }
+
     /// Returns the endianess used by the writer.
     pub fn endianness(&self) -> Endianness {
         self.endianness
@@ -57,12 +57,14 @@ pub const CUSTOM_BLOCK_NON_COPIABLE: u32 = 0x40000BAD;
 //  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// PcapNg Block
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawBlock<'a> {
     /// Type field
     pub type_: u32,
     /// Initial length field
     pub initial_len: u32,
     /// Body of the block
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub body: Cow<'a, [u8]>,
     /// Trailer length field
     pub trailer_len: u32,
@@ -148,6 +150,7 @@ impl<'a> RawBlock<'a> {
 
 /// PcapNg parsed blocks
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Block<'a> {
     /// Section Header block
     SectionHeader(SectionHeaderBlock<'a>),
@@ -14,10 +14,12 @@ use crate::PcapError;
 
 /// Custom block
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomBlock<'a, const COPIABLE: bool> {
     /// Private Enterprise Number of the entity which defined this block.
     pub pen: u32,
     /// Payload of this block.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub payload: Cow<'a, [u8]>,
 }
 
@@ -11,11 +11,14 @@ use derive_into_owned::IntoOwned;
 
 use super::block_common::{Block, PcapNgBlock};
 use super::opt_common::{CommonOption, PcapNgOption, WriteOptTo};
+use crate::dissect::PacketHeaders;
 use crate::errors::PcapError;
 use crate::pcapng::PcapNgState;
+use crate::DataLink;
 
 /// An Enhanced Packet Block (EPB) is the standard container for storing the packets coming from the network.
 #[derive(Clone, Debug, Default, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnhancedPacketBlock<'a> {
     /// It specifies the interface this packet comes from.
     ///
@@ -30,12 +33,20 @@ pub struct EnhancedPacketBlock<'a> {
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub data: Cow<'a, [u8]>,
 
     /// Options
     pub options: Vec<EnhancedPacketOption<'a>>,
 }
 
+impl EnhancedPacketBlock<'_> {
+    /// Parses the headers of the packet, `linktype` being the one of its interface, see [`PacketHeaders::parse`].
+    pub fn headers(&self, linktype: DataLink) -> Option<PacketHeaders<'_>> {
+        PacketHeaders::parse(linktype, &self.data)
+    }
+}
+
 impl<'a> PcapNgBlock<'a> for EnhancedPacketBlock<'a> {
     fn from_slice<B: ByteOrder>(state: &PcapNgState, mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
         if slice.len() < 20 {
@@ -91,12 +102,13 @@ impl<'a> PcapNgBlock<'a> for EnhancedPacketBlock<'a> {
 
 /// The Enhanced Packet Block (EPB) options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EnhancedPacketOption<'a> {
     /// 32-bit flags word containing link-layer information.
     Flags(u32),
 
     /// Contains a hash of the packet.
-    Hash(Cow<'a, [u8]>),
+    Hash(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// 64-bit integer value specifying the number of packets lost
     /// (by the interface and the operating system) between this packet and the preceding one for
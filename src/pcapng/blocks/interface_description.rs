@@ -22,6 +22,7 @@ use crate::DataLink;
 /// An Interface Description Block (IDB) is the container for information describing an interface
 /// on which packet data is captured.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceDescriptionBlock<'a> {
     /// A value that defines the link layer type of this interface.
     ///
@@ -112,6 +113,7 @@ impl<'a> InterfaceDescriptionBlock<'a> {
 
 /// The Interface Description Block (IDB) options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InterfaceDescriptionOption<'a> {
     /// The if_name option is a UTF-8 string containing the name of the device used to capture data.
     IfName(Cow<'a, str>),
@@ -120,13 +122,13 @@ pub enum InterfaceDescriptionOption<'a> {
     IfDescription(Cow<'a, str>),
 
     /// The if_IPv4addr option is an IPv4 network address and corresponding netmask for the interface.
-    IfIpv4Addr(Cow<'a, [u8]>),
+    IfIpv4Addr(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// The if_IPv6addr option is an IPv6 network address and corresponding prefix length for the interface.
-    IfIpv6Addr(Cow<'a, [u8]>),
+    IfIpv6Addr(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// The if_MACaddr option is the Interface Hardware MAC address (48 bits), if available.
-    IfMacAddr(Cow<'a, [u8]>),
+    IfMacAddr(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// The if_EUIaddr option is the Interface Hardware EUI address (64 bits), if available.
     IfEuIAddr(u64),
@@ -141,7 +143,7 @@ pub enum InterfaceDescriptionOption<'a> {
     IfTzone(u32),
 
     /// The if_filter option identifies the filter (e.g. "capture only TCP traffic") used to capture traffic.
-    IfFilter(Cow<'a, [u8]>),
+    IfFilter(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// The if_os option is a UTF-8 string containing the name of the operating system
     /// of the machine in which this interface is installed.
@@ -262,6 +264,8 @@ impl<'a> PcapNgOption<'a> for InterfaceDescriptionOption<'a> {
 
 /// Timestamp resolution of an interface.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8"))]
 pub struct TsResolution(u8);
 
 impl TsResolution {
@@ -312,6 +316,15 @@ impl TsResolution {
     }
 }
 
+/// Validates the resolution with [`TsResolution::new`].
+impl TryFrom<u8> for TsResolution {
+    type Error = PcapError;
+
+    fn try_from(ts_resol: u8) -> Result<Self, PcapError> {
+        TsResolution::new(ts_resol)
+    }
+}
+
 impl Default for TsResolution {
     /// Default to micro-seconds resolution
     fn default() -> Self {
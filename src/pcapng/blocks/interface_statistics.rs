@@ -16,6 +16,7 @@ use crate::pcapng::PcapNgState;
 
 /// The Interface Statistics Block contains the capture statistics for a given interface and it is optional.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceStatisticsBlock<'a> {
     /// Specifies the interface these statistics refers to.
     /// 
@@ -61,6 +62,7 @@ impl<'a> PcapNgBlock<'a> for InterfaceStatisticsBlock<'a> {
 
 /// The Interface Statistics Block options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InterfaceStatisticsOption<'a> {
     /// The isb_starttime option specifies the time the capture started.
     ///
@@ -17,6 +17,7 @@ use crate::pcapng::PcapNgState;
 /// The Name Resolution Block (NRB) is used to support the correlation of numeric addresses
 /// (present in the captured packets) and their corresponding canonical names and it is optional.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NameResolutionBlock<'a> {
     /// Records
     pub records: Vec<Record<'a>>,
@@ -65,6 +66,7 @@ impl<'a> PcapNgBlock<'a> for NameResolutionBlock<'a> {
 
 /// Resolution block record types
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Record<'a> {
     /// End of the records
     End,
@@ -168,8 +170,10 @@ impl<'a> Record<'a> {
 
 /// Ipv4 records
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ipv4Record<'a> {
     /// IPv4 Addr
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub ip_addr: Cow<'a, [u8]>,
     /// Fqdn
     pub names: Vec<Cow<'a, str>>,
@@ -222,8 +226,10 @@ impl<'a> Ipv4Record<'a> {
 
 /// Ipv6 records
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ipv6Record<'a> {
     /// Ipv6 addr
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub ip_addr: Cow<'a, [u8]>,
     /// Fqdn
     pub names: Vec<Cow<'a, str>>,
@@ -276,10 +282,12 @@ impl<'a> Ipv6Record<'a> {
 
 /// Unknown records
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnknownRecord<'a> {
     /// Records type
     pub type_: u16,
     /// Record body
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub value: Cow<'a, [u8]>,
 }
 
@@ -293,15 +301,16 @@ impl<'a> UnknownRecord<'a> {
 
 /// The Name Resolution Block (NRB) options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NameResolutionOption<'a> {
     /// The ns_dnsname option is a UTF-8 string containing the name of the machine (DNS server) used to perform the name resolution.
     NsDnsName(Cow<'a, str>),
 
     /// The ns_dnsIP4addr option specifies the IPv4 address of the DNS server.
-    NsDnsIpv4Addr(Cow<'a, [u8]>),
+    NsDnsIpv4Addr(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// The ns_dnsIP6addr option specifies the IPv6 address of the DNS server.
-    NsDnsIpv6Addr(Cow<'a, [u8]>),
+    NsDnsIpv6Addr(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// A common option applicable to any block type.
     Common(CommonOption<'a>),
@@ -25,6 +25,7 @@ pub const CUSTOM_BINARY_OPTION_NON_COPIABLE: u16 = 0x4BAD;
 
 /// Common options applicable to all block types.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CommonOption<'a> {
     /// Comment
     Comment(Cow<'a, str>),
@@ -207,10 +208,12 @@ pub(crate) trait PcapNgOption<'a> {
 
 /// Unknown options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnknownOption<'a> {
     /// Option code
     pub code: u16,
     /// Option value
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub value: Cow<'a, [u8]>,
 }
 
@@ -223,10 +226,12 @@ impl<'a> UnknownOption<'a> {
 
 /// Custom binary option
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomBinaryOption<'a, const COPIABLE: bool> {
     /// Option PEN identifier
     pub pen: u32,
     /// Option value
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub value: Cow<'a, [u8]>,
 }
 
@@ -249,6 +254,7 @@ impl<'a, const COPIABLE: bool> CustomBinaryOption<'a, COPIABLE> {
 
 /// Custom string (UTF-8) option
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomUtf8Option<'a, const COPIABLE: bool> {
     /// Option PEN identifier
     pub pen: u32,
@@ -17,6 +17,7 @@ use crate::pcapng::PcapNgState;
 /// The Packet Block is obsolete, and MUST NOT be used in new files.
 /// Use the Enhanced Packet Block or Simple Packet Block instead.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PacketBlock<'a> {
     /// It specifies the interface this packet comes from.
     pub interface_id: u16,
@@ -37,6 +38,7 @@ pub struct PacketBlock<'a> {
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub data: Cow<'a, [u8]>,
 
     /// Options
@@ -102,12 +104,13 @@ impl<'a> PcapNgBlock<'a> for PacketBlock<'a> {
 
 /// Packet Block option
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PacketOption<'a> {
     /// 32-bit flags word containing link-layer information.
     Flags(u32),
 
     /// Contains a hash of the packet.
-    Hash(Cow<'a, [u8]>),
+    Hash(#[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))] Cow<'a, [u8]>),
 
     /// A common option applicable to any block type.
     Common(CommonOption<'a>),
@@ -17,6 +17,7 @@ use crate::Endianness;
 
 /// Section Header Block: it defines the most important characteristics of the capture file.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SectionHeaderBlock<'a> {
     /// Endianness of the section.
     pub endianness: Endianness,
@@ -107,6 +108,7 @@ impl Default for SectionHeaderBlock<'static> {
 
 /// Section Header Block options
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SectionHeaderOption<'a> {
     /// Description of the hardware used to create this section
     Hardware(Cow<'a, str>),
@@ -16,15 +16,36 @@ use crate::pcapng::PcapNgState;
 /// The Simple Packet Block (SPB) is a lightweight container for storing the packets coming from the network.
 /// 
 /// Its presence is optional.
+///
+/// A SPB belongs to the first interface of its section and has no timestamp. The modules of this crate processing
+/// the packets of a capture give it the timestamp of the previous packet, and read its data with
+/// [`packet_data`](Self::packet_data), which removes the padding of the block.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimplePacketBlock<'a> {
     /// Actual length of the packet when it was transmitted on the network.
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub data: Cow<'a, [u8]>,
 }
 
+impl SimplePacketBlock<'_> {
+    /// Returns the data of the packet, given the snaplen of the first interface of the section, 0 meaning no limit.
+    ///
+    /// The block body is padded, the captured length is the min of the original length, the snaplen and the body length.
+    pub fn packet_data(&self, snaplen: u32) -> &[u8] {
+        let snaplen = match snaplen {
+            0 => u32::MAX,
+            snaplen => snaplen,
+        };
+        let captured_len = self.original_len.min(snaplen) as usize;
+
+        &self.data[..captured_len.min(self.data.len())]
+    }
+}
+
 impl<'a> PcapNgBlock<'a> for SimplePacketBlock<'a> {
     fn from_slice<B: ByteOrder>(_state: &PcapNgState, mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
         if slice.len() < 4 {
@@ -13,8 +13,10 @@ use crate::pcapng::PcapNgState;
 
 /// The Systemd Journal Export Block is a lightweight containter for systemd Journal Export Format entry data.
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SystemdJournalExportBlock<'a> {
     /// A journal entry as described in the Journal Export Format documentation.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub journal_entry: Cow<'a, [u8]>,
 }
 
@@ -13,12 +13,14 @@ use crate::PcapError;
 
 /// Unknown block
 #[derive(Clone, Debug, IntoOwned, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnknownBlock<'a> {
     /// Block type
     pub type_: u32,
     /// Block length
     pub length: u32,
     /// Block value
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub value: Cow<'a, [u8]>,
 }
 
@@ -12,5 +12,8 @@ pub use parser::*;
 pub(crate) mod reader;
 pub use reader::*;
 
+pub(crate) mod rotating_writer;
+pub use rotating_writer::*;
+
 pub(crate) mod writer;
 pub use writer::*;
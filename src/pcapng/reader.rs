@@ -118,4 +118,49 @@ impl<R: Read> PcapNgReader<R> {
     pub fn bytes_parsed(&self) -> u64 {
         self.reader.bytes_used
     }
+
+    /// Converts the reader into an [`Iterator`] of owned [`Block`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_blocks(self) -> PcapNgBlocks<R> {
+        PcapNgBlocks { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned blocks of a [`PcapNgReader`], created by [`PcapNgReader::into_blocks`].
+pub struct PcapNgBlocks<R: Read> {
+    reader: PcapNgReader<R>,
+    done: bool,
+}
+
+impl<R: Read> PcapNgBlocks<R> {
+    /// Returns the current [`SectionHeaderBlock`].
+    pub fn section(&self) -> &SectionHeaderBlock<'static> {
+        self.reader.section()
+    }
+
+    /// Returns all the current [`InterfaceDescriptionBlock`].
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
+        self.reader.interfaces()
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> PcapNgReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for PcapNgBlocks<R> {
+    type Item = Result<Block<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_block()?.map(Block::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
 }
@@ -0,0 +1,236 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::blocks::block_common::{Block, PcapNgBlock};
+use super::blocks::interface_description::InterfaceDescriptionBlock;
+use super::blocks::interface_statistics::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use super::blocks::section_header::SectionHeaderBlock;
+use super::{PcapNgState, PcapNgWriter};
+use crate::rotation::{FileProgress, RotationFiles, RotationPolicy};
+use crate::{Endianness, PcapError, PcapResult};
+
+
+/// Writes a PcapNg to a ring buffer of files, switching to a new file when a threshold of the [`RotationPolicy`] is reached.
+///
+/// Every new file starts with the current [`SectionHeaderBlock`] followed by all the current
+/// [`InterfaceDescriptionBlock`], so the interface ids of the packets stay valid across files.
+///
+/// The rotation only happens before a packet block (EPB, SPB or PB).
+///
+/// # Example
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::pcapng::{PcapNgReader, RotatingPcapNgWriter};
+/// use pcap_file::RotationPolicy;
+///
+/// let file_in = File::open("test.pcapng").expect("Error opening file");
+/// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+///
+/// // Keep at most 10 files of 1 MB each
+/// let policy = RotationPolicy { max_file_size: Some(1_000_000), max_files: Some(10), ..Default::default() };
+/// let mut pcapng_writer = RotatingPcapNgWriter::with_section_header("out.pcapng", pcapng_reader.section().clone(), policy).unwrap();
+/// pcapng_writer.set_write_statistics(true);
+///
+/// while let Some(block) = pcapng_reader.next_block() {
+///     pcapng_writer.write_block(&block.unwrap()).unwrap();
+/// }
+///
+/// let files = pcapng_writer.close().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RotatingPcapNgWriter {
+    policy: RotationPolicy,
+    files: RotationFiles,
+    progress: FileProgress,
+    writer: PcapNgWriter<BufWriter<File>>,
+    /// Write an [`InterfaceStatisticsBlock`] per interface when a file is closed
+    write_statistics: bool,
+    /// Statistics of the interfaces for the current file
+    statistics: Vec<InterfaceCounters>,
+}
+
+/// Packets written for an interface in the current file
+#[derive(Copy, Clone, Debug, Default)]
+struct InterfaceCounters {
+    first_timestamp: Option<Duration>,
+    last_timestamp: Option<Duration>,
+    packets: u64,
+}
+
+impl RotatingPcapNgWriter {
+    /// Creates a new [`RotatingPcapNgWriter`] using the native endianness of the CPU.
+    ///
+    /// The files are named after `path` with an index inserted before the extension,
+    /// e.g. `out.pcapng` gives `out_00001.pcapng`, `out_00002.pcapng`...
+    ///
+    /// # Errors
+    /// The first file can't be created or written to.
+    pub fn new(path: impl Into<PathBuf>, policy: RotationPolicy) -> PcapResult<Self> {
+        let section = SectionHeaderBlock { endianness: Endianness::native(), ..Default::default() };
+        Self::with_section_header(path, section, policy)
+    }
+
+    /// Creates a new [`RotatingPcapNgWriter`] with the given section header.
+    ///
+    /// # Errors
+    /// The first file can't be created or written to.
+    pub fn with_section_header(path: impl Into<PathBuf>, section: SectionHeaderBlock<'_>, policy: RotationPolicy) -> PcapResult<Self> {
+        let mut files = RotationFiles::new(path.into(), policy.max_files)?;
+        let writer = PcapNgWriter::with_section_header(files.next_file()?, section)?;
+        let progress = FileProgress { bytes_written: block_len(&writer, &writer.section().clone().into_block()), ..Default::default() };
+
+        Ok(Self { policy, files, progress, writer, write_statistics: false, statistics: vec![] })
+    }
+
+    /// Enables or disables the writing of an [`InterfaceStatisticsBlock`] per interface each time a file is closed.
+    ///
+    /// The statistics only cover the packets of the closed file.
+    pub fn set_write_statistics(&mut self, enable: bool) {
+        self.write_statistics = enable;
+    }
+
+    /// Writes a [`Block`], switching to a new file first if needed.
+    pub fn write_block(&mut self, block: &Block) -> PcapResult<usize> {
+        let packet_info = match block {
+            Block::EnhancedPacket(blk) => Some((blk.interface_id, Some(blk.timestamp))),
+            Block::Packet(blk) => Some((blk.interface_id as u32, Some(blk.timestamp))),
+            Block::SimplePacket(_) => Some((0, None)),
+            _ => None,
+        };
+
+        match packet_info {
+            Some((_, timestamp)) if self.policy.must_rotate(&self.progress, timestamp) => self.rotate()?,
+            // A new section invalidates the statistics of the previous one
+            None if matches!(block, Block::SectionHeader(_)) => self.write_statistics_blocks()?,
+            _ => {},
+        }
+
+        let len = self.writer.write_block(block)?;
+
+        let Some((interface_id, timestamp)) = packet_info
+        else {
+            self.progress.bytes_written += len as u64;
+            return Ok(len);
+        };
+
+        self.progress.add_packet(timestamp, len);
+
+        let interface_id = interface_id as usize;
+        if self.statistics.len() <= interface_id {
+            self.statistics.resize(interface_id + 1, InterfaceCounters::default());
+        }
+
+        let counters = &mut self.statistics[interface_id];
+        counters.packets += 1;
+        if let Some(ts) = timestamp {
+            counters.first_timestamp.get_or_insert(ts);
+            counters.last_timestamp = Some(ts);
+        }
+
+        Ok(len)
+    }
+
+    /// Writes a [`PcapNgBlock`], switching to a new file first if needed.
+    pub fn write_pcapng_block<'a, B: PcapNgBlock<'a>>(&mut self, block: B) -> PcapResult<usize> {
+        self.write_block(&block.into_block())
+    }
+
+    /// Switches to a new file, carrying over the current section header and interfaces.
+    pub fn rotate(&mut self) -> PcapResult<()> {
+        self.write_statistics_blocks()?;
+
+        let section = self.writer.section().clone();
+        let interfaces = self.writer.interfaces().to_vec();
+
+        let new_writer = PcapNgWriter::with_section_header(self.files.next_file()?, section)?;
+        let old_writer = std::mem::replace(&mut self.writer, new_writer);
+
+        self.progress = FileProgress { bytes_written: block_len(&self.writer, &self.writer.section().clone().into_block()), ..Default::default() };
+        for interface in interfaces {
+            self.progress.bytes_written += self.writer.write_pcapng_block(interface)? as u64;
+        }
+
+        old_writer
+            .into_inner()
+            .into_inner()
+            .map_err(|e| PcapError::IoError(e.into_error()))?;
+
+        Ok(())
+    }
+
+    /// Writes the statistics of the current file if enabled, then resets them.
+    fn write_statistics_blocks(&mut self) -> PcapResult<()> {
+        let statistics = std::mem::take(&mut self.statistics);
+
+        if !self.write_statistics {
+            return Ok(());
+        }
+
+        let interfaces: Vec<InterfaceDescriptionBlock<'static>> = self.writer.interfaces().to_vec();
+        for (interface_id, interface) in interfaces.iter().enumerate() {
+            let counters = statistics.get(interface_id).copied().unwrap_or_default();
+
+            let mut options = vec![];
+            if let Some(ts) = counters.first_timestamp {
+                options.push(InterfaceStatisticsOption::IsbStartTime(ts));
+            }
+            if let Some(ts) = counters.last_timestamp {
+                options.push(InterfaceStatisticsOption::IsbEndTime(ts));
+            }
+            options.push(InterfaceStatisticsOption::IsbUsrDeliv(counters.packets));
+
+            let isb = InterfaceStatisticsBlock {
+                interface_id: interface_id as u32,
+                timestamp: counters.last_timestamp.unwrap_or_else(|| interface.ts_offset()),
+                options,
+            };
+
+            self.progress.bytes_written += self.writer.write_pcapng_block(isb)? as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Flush data
+    pub fn flush(&mut self) -> PcapResult<()> {
+        use std::io::Write;
+        self.writer.get_mut().flush().map_err(PcapError::IoError)
+    }
+
+    /// Access the current [`PcapNgState`].
+    pub fn state(&self) -> &PcapNgState {
+        self.writer.state()
+    }
+
+    /// Returns the paths of the files kept on disk, oldest first.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.paths()
+    }
+
+    /// Writes the statistics blocks if enabled, flushes and closes the current file,
+    /// returning the paths of the files kept on disk, oldest first.
+    pub fn close(mut self) -> PcapResult<Vec<PathBuf>> {
+        self.write_statistics_blocks()?;
+
+        self.writer
+            .into_inner()
+            .into_inner()
+            .map_err(|e| PcapError::IoError(e.into_error()))?;
+
+        Ok(self.files.paths().map(Path::to_path_buf).collect())
+    }
+}
+
+/// Computes the length of a block once encoded with the state of the writer.
+fn block_len<W: std::io::Write>(writer: &PcapNgWriter<W>, block: &Block) -> u64 {
+    let state = writer.state();
+    let len = match state.section().endianness {
+        Endianness::Big => block.write_to::<byteorder_slice::BigEndian, _>(state, &mut std::io::sink()),
+        Endianness::Little => block.write_to::<byteorder_slice::LittleEndian, _>(state, &mut std::io::sink()),
+    };
+
+    len.unwrap_or(0) as u64
+}
@@ -32,6 +32,7 @@ use crate::{Endianness, PcapError, PcapResult};
 ///     pcapng_writer.write_block(&block).unwrap();
 /// }
 /// ```
+#[derive(Debug)]
 pub struct PcapNgWriter<W: Write> {
     /// Current state of the pcapng format.
     state: PcapNgState,
@@ -123,15 +124,11 @@ impl<W: Write> PcapNgWriter<W> {
     pub fn write_block(&mut self, block: &Block) -> PcapResult<usize> {
 
         match block {
-            Block::InterfaceStatistics(blk) => {
-                if blk.interface_id as usize >= self.state.interfaces.len() {
-                    return Err(PcapError::InvalidInterfaceId(blk.interface_id));
-                }
+            Block::InterfaceStatistics(blk) if blk.interface_id as usize >= self.state.interfaces.len() => {
+                return Err(PcapError::InvalidInterfaceId(blk.interface_id));
             },
-            Block::EnhancedPacket(blk) => {
-                if blk.interface_id as usize >= self.state.interfaces.len() {
-                    return Err(PcapError::InvalidInterfaceId(blk.interface_id));
-                }
+            Block::EnhancedPacket(blk) if blk.interface_id as usize >= self.state.interfaces.len() => {
+                return Err(PcapError::InvalidInterfaceId(blk.interface_id));
             },
 
             _ => (),
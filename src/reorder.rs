@@ -0,0 +1,434 @@
+//! Detection of out-of-order packets and reordering by timestamp, like `reordercap`.
+//!
+//! [`OrderChecker`], [`find_out_of_order_pcap`] and [`find_out_of_order_pcapng`] report the packets whose
+//! timestamp is earlier than the one of a previous packet.
+//!
+//! [`PcapNgReorder`] sorts the [`EnhancedPacketBlock`] of a stream of blocks, either within a bounded window
+//! or completely by spilling sorted runs to temporary files.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//! use pcap_file::reorder::{PcapNgReorder, ReorderMode};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, blocks.section().clone()).unwrap();
+//!
+//! // Packets are at most 1000 positions away from their sorted position
+//! for block in PcapNgReorder::new(blocks, ReorderMode::Window(1000)) {
+//!     pcapng_writer.write_block(&block.unwrap()).unwrap();
+//! }
+//! ```
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::Duration;
+
+use crate::errors::{PcapError, PcapResult};
+use crate::pcap::PcapReader;
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use crate::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use crate::pcapng::{Block, PcapNgBlocks, PcapNgReader, PcapNgWriter};
+
+
+/// A packet whose timestamp is earlier than the one of a previous packet.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct OutOfOrderPacket {
+    /// 0-based index of the packet among the packets of the capture
+    pub index: u64,
+    /// Timestamp of the packet
+    pub timestamp: Duration,
+    /// Latest timestamp of the packets before this one
+    pub max_timestamp: Duration,
+}
+
+/// Checks that the timestamps of a sequence of packets never decrease.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OrderChecker {
+    index: u64,
+    max_timestamp: Option<Duration>,
+}
+
+impl OrderChecker {
+    /// Creates a new [`OrderChecker`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the next packet, returning an [`OutOfOrderPacket`] if its timestamp is earlier than a previous one.
+    ///
+    /// A packet without a timestamp, like a [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock),
+    /// is counted but never out of order.
+    pub fn check(&mut self, timestamp: Option<Duration>) -> Option<OutOfOrderPacket> {
+        let index = self.index;
+        self.index += 1;
+
+        let timestamp = timestamp?;
+        match self.max_timestamp {
+            Some(max_timestamp) if timestamp < max_timestamp => Some(OutOfOrderPacket { index, timestamp, max_timestamp }),
+            _ => {
+                self.max_timestamp = Some(timestamp);
+                None
+            },
+        }
+    }
+
+    /// Returns the number of packets checked.
+    pub fn packets(&self) -> u64 {
+        self.index
+    }
+}
+
+/// Returns the out-of-order packets among the remaining packets of a [`PcapReader`].
+pub fn find_out_of_order_pcap<R: Read>(reader: &mut PcapReader<R>) -> PcapResult<Vec<OutOfOrderPacket>> {
+    let mut checker = OrderChecker::new();
+    let mut out_of_order = vec![];
+
+    while let Some(packet) = reader.next_packet() {
+        out_of_order.extend(checker.check(Some(packet?.timestamp())));
+    }
+
+    Ok(out_of_order)
+}
+
+/// Returns the out-of-order packets among the remaining blocks of a [`PcapNgReader`].
+///
+/// The enhanced, simple and obsolete packet blocks are counted as packets.
+pub fn find_out_of_order_pcapng<R: Read>(reader: &mut PcapNgReader<R>) -> PcapResult<Vec<OutOfOrderPacket>> {
+    let mut checker = OrderChecker::new();
+    let mut out_of_order = vec![];
+
+    while let Some(block) = reader.next_block() {
+        let timestamp = match block? {
+            Block::EnhancedPacket(blk) => Some(blk.timestamp),
+            Block::Packet(blk) => Some(blk.timestamp),
+            Block::SimplePacket(_) => None,
+            _ => continue,
+        };
+
+        out_of_order.extend(checker.check(timestamp));
+    }
+
+    Ok(out_of_order)
+}
+
+
+/// How [`PcapNgReorder`] buffers the packets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReorderMode {
+    /// Keeps at most this many packets in memory, emitting the earliest one when the window is full.
+    ///
+    /// The output is sorted if no packet is more than this many positions after its sorted position.
+    Window(usize),
+
+    /// Sorts all the packets, spilling sorted runs of `chunk_size` packets to temporary files in `temp_dir`
+    /// and merging them.
+    ///
+    /// The temporary files are deleted once merged, or when the [`PcapNgReorder`] is dropped.
+    External {
+        /// Number of packets kept in memory before being written to a temporary file
+        chunk_size: usize,
+        /// Directory of the temporary files
+        temp_dir: PathBuf,
+    },
+}
+
+/// [`Iterator`] adapter sorting the [`EnhancedPacketBlock`] of a stream of blocks by timestamp.
+///
+/// The other blocks are kept in place and act as barriers: all the packets read before one of them
+/// are emitted before it. This keeps the interfaces, name resolutions and statistics valid for the
+/// packets that follow them.
+/// The simple and obsolete packet blocks have no comparable timestamp and are also kept in place.
+///
+/// Packets with the same timestamp keep their relative order.
+///
+/// After the first error, the iterator ends.
+pub struct PcapNgReorder<I> {
+    inner: I,
+    mode: ReorderMode,
+    /// Packets waiting to be emitted or spilled
+    buffer: BinaryHeap<Reverse<PendingPacket>>,
+    /// Sorted runs spilled to temporary files, in [`ReorderMode::External`]
+    runs: Vec<SortedRun>,
+    /// Merge of the sorted runs in progress
+    merge: Option<RunsMerge>,
+    /// Non-packet block emitted once the packets before it are drained
+    barrier: Option<Block<'static>>,
+    /// Interfaces of the current section, written at the start of the sorted runs
+    interfaces: Vec<InterfaceDescriptionBlock<'static>>,
+    /// Sequence number of the next packet, to keep equal timestamps in order
+    seq: u64,
+    /// Timestamp of the last emitted packet
+    last_timestamp: Option<Duration>,
+    /// Number of packets emitted earlier than a previously emitted one
+    late_packets: u64,
+    inner_done: bool,
+    done: bool,
+}
+
+impl<I> PcapNgReorder<I> {
+    /// Creates a new [`PcapNgReorder`] over an iterator of blocks.
+    pub fn new(inner: I, mode: ReorderMode) -> Self {
+        PcapNgReorder {
+            inner,
+            mode,
+            buffer: BinaryHeap::new(),
+            runs: vec![],
+            merge: None,
+            barrier: None,
+            interfaces: vec![],
+            seq: 0,
+            last_timestamp: None,
+            late_packets: 0,
+            inner_done: false,
+            done: false,
+        }
+    }
+
+    /// Returns the number of packets emitted with a timestamp earlier than a previously emitted packet,
+    /// because the [`ReorderMode::Window`] was too small or because of a barrier.
+    pub fn late_packets(&self) -> u64 {
+        self.late_packets
+    }
+
+    /// Buffers a packet, spilling or emitting the earliest packets if needed.
+    fn push_packet(&mut self, packet: EnhancedPacketBlock<'static>) -> PcapResult<Option<EnhancedPacketBlock<'static>>> {
+        self.buffer.push(Reverse(PendingPacket { seq: self.seq, packet }));
+        self.seq += 1;
+
+        match &self.mode {
+            ReorderMode::Window(size) if self.buffer.len() > *size => Ok(self.buffer.pop().map(|Reverse(p)| p.packet)),
+            ReorderMode::External { chunk_size, temp_dir } if self.buffer.len() >= (*chunk_size).max(1) => {
+                let run = SortedRun::write(temp_dir, &self.interfaces, &mut self.buffer)?;
+                self.runs.push(run);
+                Ok(None)
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the next buffered packet, once the input reached a barrier or its end.
+    fn drain_packet(&mut self) -> PcapResult<Option<EnhancedPacketBlock<'static>>> {
+        if self.merge.is_none() && !self.runs.is_empty() {
+            if let ReorderMode::External { temp_dir, .. } = &self.mode {
+                if !self.buffer.is_empty() {
+                    let run = SortedRun::write(temp_dir, &self.interfaces, &mut self.buffer)?;
+                    self.runs.push(run);
+                }
+            }
+
+            self.merge = Some(RunsMerge::new(std::mem::take(&mut self.runs))?);
+        }
+
+        if let Some(merge) = &mut self.merge {
+            let packet = merge.next()?;
+            if packet.is_none() {
+                self.merge = None;
+            }
+            return Ok(packet);
+        }
+
+        Ok(self.buffer.pop().map(|Reverse(p)| p.packet))
+    }
+
+    /// Records an emitted packet.
+    fn emit(&mut self, packet: EnhancedPacketBlock<'static>) -> Block<'static> {
+        if self.last_timestamp.is_some_and(|last| packet.timestamp < last) {
+            self.late_packets += 1;
+        }
+        self.last_timestamp = Some(packet.timestamp);
+
+        Block::EnhancedPacket(packet)
+    }
+}
+
+impl<I: Iterator<Item = Result<Block<'static>, PcapError>>> PcapNgReorder<I> {
+    /// Returns the next block or error, without the error handling of [`Iterator::next`].
+    fn next_block(&mut self) -> PcapResult<Option<Block<'static>>> {
+        loop {
+            // Drain the packets read before the barrier, then the barrier itself
+            if self.barrier.is_some() || self.inner_done {
+                if let Some(packet) = self.drain_packet()? {
+                    return Ok(Some(self.emit(packet)));
+                }
+                return Ok(self.barrier.take());
+            }
+
+            let Some(block) = self.inner.next()
+            else {
+                self.inner_done = true;
+                continue;
+            };
+
+            match block? {
+                Block::EnhancedPacket(packet) => {
+                    if let Some(packet) = self.push_packet(packet)? {
+                        return Ok(Some(self.emit(packet)));
+                    }
+                },
+                block => {
+                    match &block {
+                        Block::SectionHeader(_) => self.interfaces.clear(),
+                        Block::InterfaceDescription(blk) => self.interfaces.push(blk.clone()),
+                        _ => {},
+                    }
+                    self.barrier = Some(block);
+                },
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Block<'static>, PcapError>>> Iterator for PcapNgReorder<I> {
+    type Item = Result<Block<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_block() {
+            Ok(Some(block)) => Some(Ok(block)),
+            Ok(None) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+
+/// A buffered packet, ordered by timestamp then by arrival.
+#[derive(Debug)]
+struct PendingPacket {
+    seq: u64,
+    packet: EnhancedPacketBlock<'static>,
+}
+
+impl PartialEq for PendingPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PendingPacket {}
+
+impl PartialOrd for PendingPacket {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingPacket {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.packet.timestamp, self.seq).cmp(&(other.packet.timestamp, other.seq))
+    }
+}
+
+
+/// Counter making the names of the temporary files unique within the process
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A temporary pcapng file holding sorted packets, deleted on drop.
+struct SortedRun {
+    path: PathBuf,
+}
+
+impl SortedRun {
+    /// Writes all the packets of the buffer, in order, to a new temporary file.
+    ///
+    /// The interfaces are written first so the timestamps are encoded with their resolution and offset.
+    fn write(temp_dir: &std::path::Path, interfaces: &[InterfaceDescriptionBlock<'static>], buffer: &mut BinaryHeap<Reverse<PendingPacket>>) -> PcapResult<Self> {
+        let id = RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+        let run = SortedRun { path: temp_dir.join(format!("pcap_file_reorder_{}_{id}.pcapng", std::process::id())) };
+
+        let file = File::create(&run.path).map_err(PcapError::IoError)?;
+        let mut writer = PcapNgWriter::new(BufWriter::new(file))?;
+        for interface in interfaces {
+            writer.write_pcapng_block(interface.clone())?;
+        }
+        while let Some(Reverse(pending)) = buffer.pop() {
+            writer.write_pcapng_block(pending.packet)?;
+        }
+
+        writer
+            .into_inner()
+            .into_inner()
+            .map_err(|e| PcapError::IoError(e.into_error()))?;
+
+        Ok(run)
+    }
+}
+
+impl Drop for SortedRun {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// K-way merge of sorted runs.
+struct RunsMerge {
+    /// The runs are kept alive until the end of the merge so their files are deleted afterwards
+    _runs: Vec<SortedRun>,
+    readers: Vec<PcapNgBlocks<BufReader<File>>>,
+    /// Earliest unread packet of each run
+    heads: Vec<Option<EnhancedPacketBlock<'static>>>,
+    /// Timestamps of the heads, the run index keeps equal timestamps in arrival order
+    heap: BinaryHeap<Reverse<(Duration, usize)>>,
+}
+
+impl RunsMerge {
+    fn new(runs: Vec<SortedRun>) -> PcapResult<Self> {
+        let mut readers = vec![];
+        for run in &runs {
+            let file = File::open(&run.path).map_err(PcapError::IoError)?;
+            readers.push(PcapNgReader::new(BufReader::new(file))?.into_blocks());
+        }
+
+        let mut merge = RunsMerge { _runs: runs, heads: vec![None; readers.len()], readers, heap: BinaryHeap::new() };
+        for idx in 0..merge.readers.len() {
+            merge.advance(idx)?;
+        }
+
+        Ok(merge)
+    }
+
+    /// Reads the next packet of a run into its head.
+    fn advance(&mut self, idx: usize) -> PcapResult<()> {
+        for block in &mut self.readers[idx] {
+            if let Block::EnhancedPacket(packet) = block? {
+                self.heap.push(Reverse((packet.timestamp, idx)));
+                self.heads[idx] = Some(packet);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the earliest packet of all the runs.
+    fn next(&mut self) -> PcapResult<Option<EnhancedPacketBlock<'static>>> {
+        let Some(Reverse((_, idx))) = self.heap.pop()
+        else {
+            return Ok(None);
+        };
+
+        let packet = self.heads[idx].take();
+        self.advance(idx)?;
+
+        Ok(packet)
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::errors::PcapError;
+
+
+/// Thresholds controlling when a rotating writer switches to a new file and how many files it keeps.
+///
+/// Mirrors the `-b filesize:..,duration:..,files:..` ring buffer options of `dumpcap`.
+/// A `None` threshold is never reached.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RotationPolicy {
+    /// Switch to a new file once the current one holds at least this many bytes.
+    pub max_file_size: Option<u64>,
+
+    /// Switch to a new file once the packets of the current one span at least this duration.
+    ///
+    /// The duration is measured on the packets timestamps, not on the wall clock.
+    pub max_file_duration: Option<Duration>,
+
+    /// Maximum number of files kept on disk, the oldest ones are deleted beyond this count.
+    pub max_files: Option<usize>,
+}
+
+impl RotationPolicy {
+    /// Returns true if a packet with the given timestamp must be written to a new file.
+    pub(crate) fn must_rotate(&self, file: &FileProgress, timestamp: Option<Duration>) -> bool {
+        // Never rotate an empty file, a packet bigger than the size threshold must land somewhere
+        if file.packets == 0 {
+            return false;
+        }
+
+        if let Some(max_size) = self.max_file_size {
+            if file.bytes_written >= max_size {
+                return true;
+            }
+        }
+
+        if let (Some(max_duration), Some(first_ts), Some(ts)) = (self.max_file_duration, file.first_timestamp, timestamp) {
+            if ts.saturating_sub(first_ts) >= max_duration {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Progress of the file currently written by a rotating writer.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct FileProgress {
+    /// Number of bytes written to the file
+    pub(crate) bytes_written: u64,
+    /// Number of packets written to the file
+    pub(crate) packets: u64,
+    /// Timestamp of the first packet of the file with a timestamp
+    pub(crate) first_timestamp: Option<Duration>,
+}
+
+impl FileProgress {
+    /// Records a packet written to the file.
+    pub(crate) fn add_packet(&mut self, timestamp: Option<Duration>, len: usize) {
+        if self.first_timestamp.is_none() {
+            self.first_timestamp = timestamp;
+        }
+        self.packets += 1;
+        self.bytes_written += len as u64;
+    }
+}
+
+/// Ring buffer of the files created by a rotating writer.
+///
+/// Files are named after the base path with a 5 digits index inserted before the extension,
+/// e.g. `capture.pcapng` gives `capture_00001.pcapng`, `capture_00002.pcapng`...
+#[derive(Debug)]
+pub(crate) struct RotationFiles {
+    /// Base path used to build the file names
+    base: PathBuf,
+    /// Index of the next file
+    next_index: u64,
+    /// Maximum number of files kept on disk
+    max_files: Option<usize>,
+    /// Files currently kept on disk, oldest first
+    files: VecDeque<PathBuf>,
+}
+
+impl RotationFiles {
+    /// Creates a new [`RotationFiles`] from a base path.
+    pub(crate) fn new(base: PathBuf, max_files: Option<usize>) -> Result<Self, PcapError> {
+        if max_files == Some(0) {
+            return Err(PcapError::InvalidField("RotationPolicy: max_files == 0"));
+        }
+
+        Ok(Self { base, next_index: 1, max_files, files: VecDeque::new() })
+    }
+
+    /// Creates the next file of the ring buffer, deleting the oldest ones if needed.
+    pub(crate) fn next_file(&mut self) -> Result<BufWriter<File>, PcapError> {
+        let path = self.file_path(self.next_index);
+        let file = File::create(&path)?;
+
+        self.next_index += 1;
+        self.files.push_back(path);
+
+        if let Some(max_files) = self.max_files {
+            while self.files.len() > max_files {
+                let oldest = self.files.pop_front().unwrap();
+                std::fs::remove_file(oldest)?;
+            }
+        }
+
+        Ok(BufWriter::new(file))
+    }
+
+    /// Returns the paths of the files kept on disk, oldest first.
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(PathBuf::as_path)
+    }
+
+    /// Builds the path of the file with the given index.
+    fn file_path(&self, index: u64) -> PathBuf {
+        let stem = self.base.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+        let name = match self.base.extension() {
+            Some(ext) => format!("{stem}_{index:05}.{}", ext.to_string_lossy()),
+            None => format!("{stem}_{index:05}"),
+        };
+
+        self.base.with_file_name(name)
+    }
+}
@@ -0,0 +1,313 @@
+//! Removal of the identifying metadata of a PcapNg capture.
+//!
+//! A [`ScrubPolicy`] describes what to do with each kind of metadata: the hardware, OS and application of the
+//! section headers, the name, description, hardware and OS of the interfaces, the comments, the custom blocks
+//! and options, the systemd journal blocks and the decryption secrets blocks.
+//! [`PcapNgScrub`] applies it to an [`Iterator`] of blocks. The packets themselves are left untouched.
+//!
+//! A [`PcapNgReader`] consumes the first section header, [`PcapNgScrub::from_reader`] yields it scrubbed before the
+//! other blocks, so that it can be written with [`PcapNgWriter::with_section_header`](crate::pcapng::PcapNgWriter::with_section_header).
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//! use pcap_file::scrub::{PcapNgScrub, ScrubAction, ScrubPolicy};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! // Scrub everything but keep the interface names, renamed
+//! let policy = ScrubPolicy { if_name: ScrubAction::Replace("eth".to_string()), ..Default::default() };
+//! let mut blocks = PcapNgScrub::from_reader(pcapng_reader, policy);
+//!
+//! // The first block is the scrubbed section header of the reader
+//! let section = blocks.next().unwrap().unwrap().into_section_header().unwrap();
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, section).unwrap();
+//!
+//! for block in blocks {
+//!     pcapng_writer.write_block(&block.unwrap()).unwrap();
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::io::Read;
+use std::iter::{Chain, Once};
+
+use crate::errors::PcapError;
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use crate::pcapng::blocks::interface_description::InterfaceDescriptionOption;
+use crate::pcapng::blocks::interface_statistics::InterfaceStatisticsOption;
+use crate::pcapng::blocks::name_resolution::NameResolutionOption;
+use crate::pcapng::blocks::opt_common::CommonOption;
+use crate::pcapng::blocks::packet::PacketOption;
+use crate::pcapng::blocks::section_header::SectionHeaderOption;
+use crate::pcapng::{Block, PcapNgBlocks, PcapNgReader};
+
+
+/// Block type of a Decryption Secrets Block, which is read as an
+/// [`UnknownBlock`](crate::pcapng::blocks::unknown::UnknownBlock).
+const DECRYPTION_SECRETS_BLOCK: u32 = 0x0000000A;
+
+/// Action applied to a string option.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ScrubAction {
+    /// Keeps the option
+    #[default]
+    Keep,
+    /// Removes the option
+    Remove,
+    /// Replaces the value of the option, an absent option is not added
+    Replace(String),
+}
+
+impl ScrubAction {
+    /// Applies the action to the value of an option, returns [`None`] if the option must be removed.
+    pub fn apply<'a>(&self, value: Cow<'a, str>) -> Option<Cow<'a, str>> {
+        match self {
+            ScrubAction::Keep => Some(value),
+            ScrubAction::Remove => None,
+            ScrubAction::Replace(new) => Some(Cow::Owned(new.clone())),
+        }
+    }
+}
+
+/// Selection of Private Enterprise Numbers (PEN).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum PenSelection {
+    /// No PEN
+    #[default]
+    None,
+    /// All the PENs
+    All,
+    /// Only these PENs
+    Pens(Vec<u32>),
+}
+
+impl PenSelection {
+    /// Returns true if the PEN is selected.
+    pub fn contains(&self, pen: u32) -> bool {
+        match self {
+            PenSelection::None => false,
+            PenSelection::All => true,
+            PenSelection::Pens(pens) => pens.contains(&pen),
+        }
+    }
+}
+
+/// Policy of a metadata scrubbing.
+///
+/// The [`Default`] policy removes all the supported metadata, [`ScrubPolicy::keep_all`] removes nothing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScrubPolicy {
+    /// Section header `shb_hardware` option
+    pub shb_hardware: ScrubAction,
+    /// Section header `shb_os` option
+    pub shb_os: ScrubAction,
+    /// Section header `shb_userappl` option
+    pub shb_user_application: ScrubAction,
+    /// Interface description `if_name` option
+    pub if_name: ScrubAction,
+    /// Interface description `if_description` option
+    pub if_description: ScrubAction,
+    /// Interface description `if_hardware` option
+    pub if_hardware: ScrubAction,
+    /// Interface description `if_os` option
+    pub if_os: ScrubAction,
+    /// `opt_comment` option of all the blocks
+    pub comments: ScrubAction,
+    /// PENs of the custom blocks and custom options to remove
+    pub custom: PenSelection,
+    /// Removes the systemd journal export blocks
+    pub remove_systemd_journal: bool,
+    /// Removes the decryption secrets blocks
+    pub remove_decryption_secrets: bool,
+}
+
+impl Default for ScrubPolicy {
+    fn default() -> Self {
+        ScrubPolicy {
+            shb_hardware: ScrubAction::Remove,
+            shb_os: ScrubAction::Remove,
+            shb_user_application: ScrubAction::Remove,
+            if_name: ScrubAction::Remove,
+            if_description: ScrubAction::Remove,
+            if_hardware: ScrubAction::Remove,
+            if_os: ScrubAction::Remove,
+            comments: ScrubAction::Remove,
+            custom: PenSelection::All,
+            remove_systemd_journal: true,
+            remove_decryption_secrets: true,
+        }
+    }
+}
+
+impl ScrubPolicy {
+    /// Creates a [`ScrubPolicy`] which keeps all the metadata.
+    pub fn keep_all() -> Self {
+        ScrubPolicy {
+            shb_hardware: ScrubAction::Keep,
+            shb_os: ScrubAction::Keep,
+            shb_user_application: ScrubAction::Keep,
+            if_name: ScrubAction::Keep,
+            if_description: ScrubAction::Keep,
+            if_hardware: ScrubAction::Keep,
+            if_os: ScrubAction::Keep,
+            comments: ScrubAction::Keep,
+            custom: PenSelection::None,
+            remove_systemd_journal: false,
+            remove_decryption_secrets: false,
+        }
+    }
+
+    /// Scrubs a block, returns [`None`] if the whole block must be removed.
+    pub fn scrub_block<'a>(&self, block: Block<'a>) -> Option<Block<'a>> {
+        let block = match block {
+            Block::SectionHeader(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        SectionHeaderOption::Hardware(v) => self.shb_hardware.apply(v).map(SectionHeaderOption::Hardware),
+                        SectionHeaderOption::OS(v) => self.shb_os.apply(v).map(SectionHeaderOption::OS),
+                        SectionHeaderOption::UserApplication(v) => {
+                            self.shb_user_application.apply(v).map(SectionHeaderOption::UserApplication)
+                        },
+                        SectionHeaderOption::Common(opt) => self.scrub_common(opt).map(SectionHeaderOption::Common),
+                    })
+                    .collect();
+                Block::SectionHeader(blk)
+            },
+            Block::InterfaceDescription(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        InterfaceDescriptionOption::IfName(v) => self.if_name.apply(v).map(InterfaceDescriptionOption::IfName),
+                        InterfaceDescriptionOption::IfDescription(v) => {
+                            self.if_description.apply(v).map(InterfaceDescriptionOption::IfDescription)
+                        },
+                        InterfaceDescriptionOption::IfHardware(v) => self.if_hardware.apply(v).map(InterfaceDescriptionOption::IfHardware),
+                        InterfaceDescriptionOption::IfOs(v) => self.if_os.apply(v).map(InterfaceDescriptionOption::IfOs),
+                        InterfaceDescriptionOption::Common(opt) => self.scrub_common(opt).map(InterfaceDescriptionOption::Common),
+                        opt => Some(opt),
+                    })
+                    .collect();
+                Block::InterfaceDescription(blk)
+            },
+            Block::EnhancedPacket(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        EnhancedPacketOption::Common(opt) => self.scrub_common(opt).map(EnhancedPacketOption::Common),
+                        opt => Some(opt),
+                    })
+                    .collect();
+                Block::EnhancedPacket(blk)
+            },
+            Block::Packet(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        PacketOption::Common(opt) => self.scrub_common(opt).map(PacketOption::Common),
+                        opt => Some(opt),
+                    })
+                    .collect();
+                Block::Packet(blk)
+            },
+            Block::NameResolution(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        NameResolutionOption::Common(opt) => self.scrub_common(opt).map(NameResolutionOption::Common),
+                        opt => Some(opt),
+                    })
+                    .collect();
+                Block::NameResolution(blk)
+            },
+            Block::InterfaceStatistics(mut blk) => {
+                blk.options = blk
+                    .options
+                    .into_iter()
+                    .filter_map(|opt| match opt {
+                        InterfaceStatisticsOption::Common(opt) => self.scrub_common(opt).map(InterfaceStatisticsOption::Common),
+                        opt => Some(opt),
+                    })
+                    .collect();
+                Block::InterfaceStatistics(blk)
+            },
+            Block::CustomCopiable(blk) if self.custom.contains(blk.pen) => return None,
+            Block::CustomNonCopiable(blk) if self.custom.contains(blk.pen) => return None,
+            Block::SystemdJournalExport(_) if self.remove_systemd_journal => return None,
+            Block::Unknown(blk) if blk.type_ == DECRYPTION_SECRETS_BLOCK && self.remove_decryption_secrets => return None,
+            block => block,
+        };
+
+        Some(block)
+    }
+
+    /// Scrubs a common option, returns [`None`] if the option must be removed.
+    fn scrub_common<'a>(&self, opt: CommonOption<'a>) -> Option<CommonOption<'a>> {
+        match opt {
+            CommonOption::Comment(v) => self.comments.apply(v).map(CommonOption::Comment),
+            CommonOption::CustomBinaryCopiable(o) if self.custom.contains(o.pen) => None,
+            CommonOption::CustomBinaryNonCopiable(o) if self.custom.contains(o.pen) => None,
+            CommonOption::CustomUtf8Copiable(o) if self.custom.contains(o.pen) => None,
+            CommonOption::CustomUtf8NonCopiable(o) if self.custom.contains(o.pen) => None,
+            opt => Some(opt),
+        }
+    }
+}
+
+
+/// Iterator adapter scrubbing the metadata of the blocks of a PcapNg, see [`ScrubPolicy`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgScrub<I> {
+    inner: I,
+    policy: ScrubPolicy,
+}
+
+impl<I> PcapNgScrub<I> {
+    /// Creates a new [`PcapNgScrub`] over an iterator of blocks.
+    pub fn new(inner: I, policy: ScrubPolicy) -> Self {
+        PcapNgScrub { inner, policy }
+    }
+
+    /// Returns the policy of the scrubbing.
+    pub fn policy(&self) -> &ScrubPolicy {
+        &self.policy
+    }
+}
+
+impl<R: Read> PcapNgScrub<Chain<Once<Result<Block<'static>, PcapError>>, PcapNgBlocks<R>>> {
+    /// Creates a new [`PcapNgScrub`] over the blocks of a [`PcapNgReader`].
+    ///
+    /// The current section header of the reader, already consumed by it, is yielded first.
+    pub fn from_reader(reader: PcapNgReader<R>, policy: ScrubPolicy) -> Self {
+        let section = Block::SectionHeader(reader.section().clone());
+        Self::new(std::iter::once(Ok(section)).chain(reader.into_blocks()), policy)
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgScrub<I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let block = match self.inner.next()? {
+                Ok(block) => block,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if let Some(block) = self.policy.scrub_block(block) {
+                return Some(Ok(block));
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Serialization of the byte fields, behind the `serde` feature.
+//!
+//! The bytes are written as a hexadecimal string in the human-readable formats, like JSON or YAML,
+//! and as bytes in the other ones.
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use serde::de::{Error, SeqAccess, Unexpected, Visitor};
+use serde::{Deserializer, Serializer};
+
+
+/// Serializes bytes as a hexadecimal string or as bytes.
+pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        for byte in bytes {
+            write!(hex, "{byte:02x}").unwrap();
+        }
+        serializer.serialize_str(&hex)
+    }
+    else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+/// Deserializes bytes from a hexadecimal string, bytes or a sequence of bytes.
+pub(crate) fn deserialize<'de, 'a, D: Deserializer<'de>>(deserializer: D) -> Result<Cow<'a, [u8]>, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(BytesVisitor).map(Cow::Owned)
+    }
+    else {
+        deserializer.deserialize_bytes(BytesVisitor).map(Cow::Owned)
+    }
+}
+
+/// Visitor of the bytes.
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a hexadecimal string or bytes")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        if !v.len().is_multiple_of(2) || !v.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(E::invalid_value(Unexpected::Str(v), &self));
+        }
+
+        Ok((0..v.len()).step_by(2).map(|i| u8::from_str_radix(&v[i..i + 2], 16).unwrap()).collect())
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,259 @@
+//! Extraction of a time window or of a range of packets from a capture.
+//!
+//! A [`PacketSlice`] describes the packets to keep, [`PcapSlice`] and [`PcapNgSlice`] apply it to an
+//! [`Iterator`] of packets or blocks, and [`slice_pcap`] and [`slice_pcapng`] copy the slice of a reader into a writer.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//! use pcap_file::slice::{slice_pcapng, PacketSlice};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, pcapng_reader.section().clone()).unwrap();
+//!
+//! // The 5 minutes around the alert
+//! let alert = Duration::from_secs(1_700_000_000);
+//! let slice = PacketSlice::around(alert, Duration::from_secs(150), Duration::from_secs(150));
+//!
+//! slice_pcapng(&mut pcapng_reader, &mut pcapng_writer, &slice).unwrap();
+//! ```
+
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::errors::PcapError;
+use crate::pcap::{PcapPacket, PcapReader, PcapWriter};
+use crate::pcapng::{Block, PcapNgReader, PcapNgWriter};
+
+
+/// Packets selected by their timestamp and/or their index in the capture.
+///
+/// Both bounds are inclusive for the start and exclusive for the end.
+/// The index of a packet is its 0-based position among the packets of the capture,
+/// non-packet blocks are not counted.
+///
+/// A packet is in the slice if it satisfies all the bounds.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PacketSlice {
+    /// First timestamp of the slice, inclusive
+    pub start_time: Option<Duration>,
+    /// Last timestamp of the slice, exclusive
+    pub end_time: Option<Duration>,
+    /// First packet index of the slice, inclusive
+    pub start_index: Option<u64>,
+    /// Last packet index of the slice, exclusive
+    pub end_index: Option<u64>,
+    /// Stop reading at the first packet whose timestamp is after the end of the slice.
+    ///
+    /// Only correct if the packets are sorted by timestamp, but avoids reading the rest of huge captures.
+    /// The reading always stops after `end_index`.
+    pub sorted: bool,
+}
+
+impl PacketSlice {
+    /// Creates a [`PacketSlice`] keeping the packets whose timestamp is in `[start, end)`.
+    pub fn between(start: Duration, end: Duration) -> Self {
+        PacketSlice { start_time: Some(start), end_time: Some(end), ..Default::default() }
+    }
+
+    /// Creates a [`PacketSlice`] keeping the packets whose timestamp is in `[instant - before, instant + after)`.
+    pub fn around(instant: Duration, before: Duration, after: Duration) -> Self {
+        Self::between(instant.saturating_sub(before), instant.saturating_add(after))
+    }
+
+    /// Creates a [`PacketSlice`] keeping the packets whose index is in `range`.
+    pub fn indexes(range: Range<u64>) -> Self {
+        PacketSlice { start_index: Some(range.start), end_index: Some(range.end), ..Default::default() }
+    }
+
+    /// Returns true if the packet with the given index and timestamp is in the slice.
+    pub fn contains(&self, index: u64, timestamp: Duration) -> bool {
+        self.start_index.is_none_or(|start| index >= start)
+            && self.end_index.is_none_or(|end| index < end)
+            && self.start_time.is_none_or(|start| timestamp >= start)
+            && self.end_time.is_none_or(|end| timestamp < end)
+    }
+
+    /// Returns true if no packet after the one with the given index and timestamp can be in the slice.
+    pub fn is_past(&self, index: u64, timestamp: Duration) -> bool {
+        self.end_index.is_some_and(|end| index >= end) || (self.sorted && self.end_time.is_some_and(|end| timestamp >= end))
+    }
+}
+
+
+/// [`Iterator`] adapter keeping only the [`PcapPacket`] of a [`PacketSlice`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapSlice<I> {
+    inner: I,
+    slice: PacketSlice,
+    index: u64,
+    done: bool,
+}
+
+impl<I> PcapSlice<I> {
+    /// Creates a new [`PcapSlice`] over an iterator of packets.
+    pub fn new(inner: I, slice: PacketSlice) -> Self {
+        PcapSlice { inner, slice, index: 0, done: false }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapSlice<I> {
+    type Item = Result<PcapPacket<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let packet = match self.inner.next()? {
+                Ok(packet) => packet,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let index = self.index;
+            self.index += 1;
+
+            if self.slice.is_past(index, packet.timestamp()) {
+                self.done = true;
+            }
+            else if self.slice.contains(index, packet.timestamp()) {
+                return Some(Ok(packet));
+            }
+        }
+
+        None
+    }
+}
+
+
+/// [`Iterator`] adapter keeping only the packet blocks of a [`PacketSlice`].
+///
+/// All the non-packet blocks (section headers, interface descriptions, name resolutions, decryption secrets...)
+/// read before the end of the slice are kept, so the output remains a valid pcapng.
+///
+/// See [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock) for the timestamp of the
+/// simple packets.
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgSlice<I> {
+    inner: I,
+    slice: PacketSlice,
+    index: u64,
+    last_timestamp: Duration,
+    done: bool,
+}
+
+impl<I> PcapNgSlice<I> {
+    /// Creates a new [`PcapNgSlice`] over an iterator of blocks.
+    pub fn new(inner: I, slice: PacketSlice) -> Self {
+        PcapNgSlice { inner, slice, index: 0, last_timestamp: Duration::ZERO, done: false }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgSlice<I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let block = match self.inner.next()? {
+                Ok(block) => block,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let Some(timestamp) = packet_timestamp(&block, self.last_timestamp)
+            else {
+                return Some(Ok(block));
+            };
+
+            let index = self.index;
+            self.index += 1;
+            self.last_timestamp = timestamp;
+
+            if self.slice.is_past(index, timestamp) {
+                self.done = true;
+            }
+            else if self.slice.contains(index, timestamp) {
+                return Some(Ok(block));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the timestamp of a packet block, or None if the block isn't a packet.
+fn packet_timestamp(block: &Block, last_timestamp: Duration) -> Option<Duration> {
+    match block {
+        Block::EnhancedPacket(blk) => Some(blk.timestamp),
+        Block::Packet(blk) => Some(blk.timestamp),
+        Block::SimplePacket(_) => Some(last_timestamp),
+        _ => None,
+    }
+}
+
+
+/// Copies the packets of a [`PacketSlice`] from a [`PcapReader`] to a [`PcapWriter`].
+///
+/// Returns the number of packets written.
+pub fn slice_pcap<R: Read, W: Write>(reader: &mut PcapReader<R>, writer: &mut PcapWriter<W>, slice: &PacketSlice) -> Result<u64, PcapError> {
+    let mut index = 0;
+    let mut written = 0;
+
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        if slice.is_past(index, packet.timestamp()) {
+            break;
+        }
+
+        if slice.contains(index, packet.timestamp()) {
+            writer.write_packet(&packet)?;
+            written += 1;
+        }
+
+        index += 1;
+    }
+
+    Ok(written)
+}
+
+/// Copies the packet blocks of a [`PacketSlice`] and the non-packet blocks from a [`PcapNgReader`] to a [`PcapNgWriter`].
+///
+/// The writer must have been created with the section header of the reader.
+///
+/// Returns the number of packets written.
+pub fn slice_pcapng<R: Read, W: Write>(reader: &mut PcapNgReader<R>, writer: &mut PcapNgWriter<W>, slice: &PacketSlice) -> Result<u64, PcapError> {
+    let mut index = 0;
+    let mut written = 0;
+    let mut last_timestamp = Duration::ZERO;
+
+    while let Some(block) = reader.next_block() {
+        let block = block?;
+
+        let Some(timestamp) = packet_timestamp(&block, last_timestamp)
+        else {
+            writer.write_block(&block)?;
+            continue;
+        };
+
+        last_timestamp = timestamp;
+
+        if slice.is_past(index, timestamp) {
+            break;
+        }
+
+        if slice.contains(index, timestamp) {
+            writer.write_block(&block)?;
+            written += 1;
+        }
+
+        index += 1;
+    }
+
+    Ok(written)
+}
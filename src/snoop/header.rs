@@ -0,0 +1,165 @@
+use std::io::Write;
+
+use byteorder_slice::byteorder::WriteBytesExt;
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::BigEndian;
+
+use crate::errors::*;
+use crate::DataLink;
+
+
+/// Identification pattern at the start of a snoop file
+const SNOOP_MAGIC: &[u8; 8] = b"snoop\0\0\0";
+
+/// Snoop Global Header
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SnoopHeader {
+    /// Version number, should always be 2
+    pub version: u32,
+
+    /// DataLink type (first layer in the packet)
+    pub datalink: SnoopDataLink,
+}
+
+impl SnoopHeader {
+    /// Creates a new [`SnoopHeader`] from a slice of bytes.
+    ///
+    /// Returns an error if the slice doesn't start with a valid snoop header.
+    ///
+    /// [`PcapError::IncompleteBuffer`] indicates that there is not enough data in the buffer.
+    pub fn from_slice(mut slice: &[u8]) -> PcapResult<(&[u8], SnoopHeader)> {
+        if slice.len() < 16 {
+            return Err(PcapError::IncompleteBuffer(16, slice.len()));
+        }
+
+        if &slice[..8] != SNOOP_MAGIC {
+            return Err(PcapError::InvalidField("SnoopHeader: wrong identification pattern"));
+        }
+        slice = &slice[8..];
+
+        let header = SnoopHeader {
+            version: slice.read_u32::<BigEndian>().unwrap(),
+            datalink: SnoopDataLink::from(slice.read_u32::<BigEndian>().unwrap()),
+        };
+
+        Ok((slice, header))
+    }
+
+    /// Writes a [`SnoopHeader`] to a writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> PcapResult<usize> {
+        writer.write_all(SNOOP_MAGIC).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.version).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.datalink.into()).map_err(PcapError::IoError)?;
+
+        Ok(16)
+    }
+}
+
+/// Creates a new [`SnoopHeader`] with these parameters:
+///
+/// ```rust,ignore
+/// SnoopHeader {
+///     version: 2,
+///     datalink: SnoopDataLink::ETHERNET,
+/// };
+/// ```
+impl Default for SnoopHeader {
+    fn default() -> Self {
+        SnoopHeader { version: 2, datalink: SnoopDataLink::ETHERNET }
+    }
+}
+
+/// Data link type of a snoop file, see [RFC 1761](https://www.rfc-editor.org/rfc/rfc1761).
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SnoopDataLink {
+    /// IEEE 802.3
+    IEEE802_3,
+    /// IEEE 802.4 Token Bus
+    IEEE802_4,
+    /// IEEE 802.5 Token Ring
+    IEEE802_5,
+    /// IEEE 802.6 Metro Net
+    IEEE802_6,
+    /// Ethernet
+    ETHERNET,
+    /// HDLC
+    HDLC,
+    /// Character Synchronous
+    CHARACTER_SYNCHRONOUS,
+    /// IBM Channel-to-Channel
+    IBM_CHANNEL_TO_CHANNEL,
+    /// FDDI
+    FDDI,
+    /// Other
+    OTHER,
+    /// IP over Infiniband, used by Solaris
+    IPOIB,
+    /// Unknown data link
+    Unknown(u32),
+}
+
+impl SnoopDataLink {
+    /// Returns the equivalent pcap [`DataLink`], [`None`] if there is none.
+    ///
+    /// IEEE 802.3 and Ethernet are both [`DataLink::ETHERNET`], FDDI is [`DataLink::FDDI`] with the bits of the
+    /// addresses in the canonical order.
+    pub fn to_datalink(self) -> Option<DataLink> {
+        match self {
+            SnoopDataLink::IEEE802_3 | SnoopDataLink::ETHERNET => Some(DataLink::ETHERNET),
+            SnoopDataLink::IEEE802_5 => Some(DataLink::IEEE802_5),
+            SnoopDataLink::FDDI => Some(DataLink::FDDI),
+            SnoopDataLink::IPOIB => Some(DataLink::IPOIB),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`SnoopDataLink`] equivalent to a pcap [`DataLink`], [`None`] if there is none.
+    pub fn from_datalink(datalink: DataLink) -> Option<Self> {
+        match datalink {
+            DataLink::ETHERNET => Some(SnoopDataLink::ETHERNET),
+            DataLink::IEEE802_5 => Some(SnoopDataLink::IEEE802_5),
+            DataLink::FDDI => Some(SnoopDataLink::FDDI),
+            DataLink::IPOIB => Some(SnoopDataLink::IPOIB),
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for SnoopDataLink {
+    fn from(n: u32) -> SnoopDataLink {
+        match n {
+            0 => SnoopDataLink::IEEE802_3,
+            1 => SnoopDataLink::IEEE802_4,
+            2 => SnoopDataLink::IEEE802_5,
+            3 => SnoopDataLink::IEEE802_6,
+            4 => SnoopDataLink::ETHERNET,
+            5 => SnoopDataLink::HDLC,
+            6 => SnoopDataLink::CHARACTER_SYNCHRONOUS,
+            7 => SnoopDataLink::IBM_CHANNEL_TO_CHANNEL,
+            8 => SnoopDataLink::FDDI,
+            9 => SnoopDataLink::OTHER,
+            18 => SnoopDataLink::IPOIB,
+            _ => SnoopDataLink::Unknown(n),
+        }
+    }
+}
+
+impl From<SnoopDataLink> for u32 {
+    fn from(link: SnoopDataLink) -> u32 {
+        match link {
+            SnoopDataLink::IEEE802_3 => 0,
+            SnoopDataLink::IEEE802_4 => 1,
+            SnoopDataLink::IEEE802_5 => 2,
+            SnoopDataLink::IEEE802_6 => 3,
+            SnoopDataLink::ETHERNET => 4,
+            SnoopDataLink::HDLC => 5,
+            SnoopDataLink::CHARACTER_SYNCHRONOUS => 6,
+            SnoopDataLink::IBM_CHANNEL_TO_CHANNEL => 7,
+            SnoopDataLink::FDDI => 8,
+            SnoopDataLink::OTHER => 9,
+            SnoopDataLink::IPOIB => 18,
+            SnoopDataLink::Unknown(n) => n,
+        }
+    }
+}
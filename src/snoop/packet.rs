@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+use std::io::Write;
+use std::time::Duration;
+
+use byteorder_slice::byteorder::WriteBytesExt;
+use byteorder_slice::result::ReadSlice;
+use byteorder_slice::BigEndian;
+
+use crate::errors::*;
+
+
+/// Length of the header of a snoop packet record
+const RECORD_HEADER_LEN: usize = 24;
+
+/// A valid snoop packet.
+///
+/// The payload can be owned or borrowed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SnoopPacket<'a> {
+    /// Timestamp EPOCH of the packet, with a microsecond resolution
+    timestamp: Duration,
+    /// Original length of the packet when captured on the wire
+    orig_len: u32,
+    /// Number of packets dropped since the start of the capture
+    cumulative_drops: u32,
+    /// Payload, owned or borrowed, of the packet
+    data: Cow<'a, [u8]>,
+}
+
+impl<'a> SnoopPacket<'a> {
+    /// Creates a new [`SnoopPacket`] with the given parameters.
+    ///
+    /// The sub-microsecond part of the timestamp is truncated when the packet is written.
+    pub fn new(timestamp: Duration, orig_len: u32, cumulative_drops: u32, data: impl Into<Cow<'a, [u8]>>) -> PcapResult<SnoopPacket<'a>> {
+        let data = data.into();
+
+        // Validate inputs //
+        if timestamp.as_secs() > u32::MAX as u64 {
+            return Err(PcapError::InvalidField("timestamp_secs > u32::MAX"));
+        }
+
+        if data.len() > u32::MAX as usize - RECORD_HEADER_LEN - 3 {
+            return Err(PcapError::InvalidField("data_len > u32::MAX"));
+        }
+
+        if data.len() as u32 > orig_len {
+            return Err(PcapError::InvalidField("orig_len < data_len"));
+        }
+
+        Ok(SnoopPacket { timestamp, orig_len, cumulative_drops, data })
+    }
+
+    /// Parses a new borrowed [`SnoopPacket`] from a slice.
+    ///
+    /// Returns the remainder, after the padding of the record, and the packet.
+    pub fn from_slice(mut slice: &'a [u8]) -> PcapResult<(&'a [u8], Self)> {
+        // Check header length
+        if slice.len() < RECORD_HEADER_LEN {
+            return Err(PcapError::IncompleteBuffer(RECORD_HEADER_LEN, slice.len()));
+        }
+
+        // Read record header //
+        // Can unwrap because the length check is done before
+        let orig_len = slice.read_u32::<BigEndian>().unwrap();
+        let incl_len = slice.read_u32::<BigEndian>().unwrap();
+        let record_len = slice.read_u32::<BigEndian>().unwrap();
+        let cumulative_drops = slice.read_u32::<BigEndian>().unwrap();
+        let ts_sec = slice.read_u32::<BigEndian>().unwrap();
+        let ts_usec = slice.read_u32::<BigEndian>().unwrap();
+
+        // Validate fields //
+        if incl_len > orig_len {
+            return Err(PcapError::InvalidField("SnoopPacket: incl_len > orig_len"));
+        }
+
+        let Some(body_len) = (record_len as usize).checked_sub(RECORD_HEADER_LEN)
+        else {
+            return Err(PcapError::InvalidField("SnoopPacket: record_len < 24"));
+        };
+
+        if (incl_len as usize) > body_len {
+            return Err(PcapError::InvalidField("SnoopPacket: incl_len > record_len - 24"));
+        }
+
+        if ts_usec >= 1_000_000 {
+            return Err(PcapError::InvalidField("SnoopPacket: ts_microsecond >= 1_000_000"));
+        }
+
+        if slice.len() < body_len {
+            return Err(PcapError::IncompleteBuffer(body_len, slice.len()));
+        }
+
+        let packet = SnoopPacket {
+            timestamp: Duration::new(ts_sec as u64, ts_usec * 1000),
+            orig_len,
+            cumulative_drops,
+            data: Cow::Borrowed(&slice[..incl_len as usize]),
+        };
+
+        Ok((&slice[body_len..], packet))
+    }
+
+    /// Writes a [`SnoopPacket`] to a writer, padding the record to a multiple of 4 bytes.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> PcapResult<usize> {
+        let pad_len = (4 - self.data.len() % 4) % 4;
+        let record_len = RECORD_HEADER_LEN + self.data.len() + pad_len;
+
+        writer.write_u32::<BigEndian>(self.orig_len).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.len()).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(record_len as u32).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.cumulative_drops).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.timestamp.as_secs() as u32).map_err(PcapError::IoError)?;
+        writer.write_u32::<BigEndian>(self.timestamp.subsec_micros()).map_err(PcapError::IoError)?;
+        writer.write_all(&self.data).map_err(PcapError::IoError)?;
+        writer.write_all(&[0_u8; 3][..pad_len]).map_err(PcapError::IoError)?;
+
+        Ok(record_len)
+    }
+
+    /// Returns the packet timestamp.
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Returns the original length of the packet.
+    pub fn orig_len(&self) -> u32 {
+        self.orig_len
+    }
+
+    /// Returns the number of packets dropped between the start of the capture and this packet.
+    pub fn cumulative_drops(&self) -> u32 {
+        self.cumulative_drops
+    }
+
+    /// Returns the length of the packet.
+    pub fn len(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    /// Returns true if the packet has no payload.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the packet payload as a slice.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the packet payload.
+    pub fn into_data(self) -> Cow<'a, [u8]> {
+        self.data
+    }
+
+    /// Returns an owned version of the packet.
+    pub fn into_owned(self) -> SnoopPacket<'static> {
+        SnoopPacket {
+            timestamp: self.timestamp,
+            orig_len: self.orig_len,
+            cumulative_drops: self.cumulative_drops,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::errors::*;
+use crate::snoop::{SnoopHeader, SnoopPacket};
+
+/// Parses a snoop from a slice of bytes.
+///
+/// You can match on [`PcapError::IncompleteBuffer`](crate::errors::PcapError) to known if the parser need more data.
+///
+/// # Example
+/// ```no_run
+/// use pcap_file::snoop::SnoopParser;
+/// use pcap_file::PcapError;
+///
+/// let snoop = vec![0_u8; 0];
+/// let mut src = &snoop[..];
+///
+/// // Creates a new parser and parse the snoop header
+/// let (rem, snoop_parser) = SnoopParser::new(&snoop[..]).unwrap();
+/// src = rem;
+///
+/// loop {
+///     match snoop_parser.next_packet(src) {
+///         Ok((rem, packet)) => {
+///             // Do something
+///
+///             // Don't forget to update src
+///             src = rem;
+///
+///             // No more data, if no more incoming either then this is the end of the file
+///             if rem.is_empty() {
+///                 break;
+///             }
+///         },
+///         Err(PcapError::IncompleteBuffer(_,_)) => {}, // Load more data into src
+///         Err(_) => {},                           // Parsing error
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SnoopParser {
+    header: SnoopHeader,
+}
+
+impl SnoopParser {
+    /// Creates a new [`SnoopParser`].
+    ///
+    /// Returns the remainder and the parser.
+    pub fn new(slice: &[u8]) -> PcapResult<(&[u8], SnoopParser)> {
+        let (slice, header) = SnoopHeader::from_slice(slice)?;
+        let parser = SnoopParser { header };
+        Ok((slice, parser))
+    }
+
+    /// Returns the remainder and the next [`SnoopPacket`].
+    pub fn next_packet<'a>(&self, slice: &'a [u8]) -> PcapResult<(&'a [u8], SnoopPacket<'a>)> {
+        SnoopPacket::from_slice(slice)
+    }
+
+    /// Returns the header of the snoop file.
+    pub fn header(&self) -> SnoopHeader {
+        self.header
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::Read;
+
+use super::SnoopParser;
+use crate::errors::*;
+use crate::read_buffer::ReadBuffer;
+use crate::snoop::{SnoopHeader, SnoopPacket};
+
+/// Reads a snoop from a reader.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::snoop::SnoopReader;
+///
+/// let file_in = File::open("test.snoop").expect("Error opening file");
+/// let mut snoop_reader = SnoopReader::new(file_in).unwrap();
+///
+/// // Read test.snoop
+/// while let Some(pkt) = snoop_reader.next_packet() {
+///     //Check if there is no error
+///     let pkt = pkt.unwrap();
+///
+///     //Do something
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SnoopReader<R: Read> {
+    parser: SnoopParser,
+    reader: ReadBuffer<R>,
+}
+
+impl<R: Read> SnoopReader<R> {
+    /// Creates a new [`SnoopReader`] from an existing reader.
+    ///
+    /// This function reads the snoop file header to verify its integrity.
+    ///
+    /// The underlying reader must point to a valid snoop file/stream.
+    ///
+    /// # Errors
+    /// The data stream is not in a valid snoop file format.
+    ///
+    /// The underlying data are not readable.
+    pub fn new(reader: R) -> Result<SnoopReader<R>, PcapError> {
+        let mut reader = ReadBuffer::new(reader);
+        let parser = reader.parse_with(SnoopParser::new)?;
+
+        Ok(SnoopReader { parser, reader })
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Returns the next [`SnoopPacket`].
+    pub fn next_packet(&mut self) -> Option<Result<SnoopPacket<'_>, PcapError>> {
+        match self.reader.has_data_left() {
+            Ok(has_data) => {
+                if has_data {
+                    Some(self.reader.parse_with(|src| self.parser.next_packet(src)))
+                } else {
+                    None
+                }
+            },
+            Err(e) => Some(Err(PcapError::IoError(e))),
+        }
+    }
+
+    /// Returns the header of the snoop.
+    pub fn header(&self) -> SnoopHeader {
+        self.parser.header()
+    }
+
+    /// Converts the reader into an [`Iterator`] of owned [`SnoopPacket`].
+    ///
+    /// The iteration stops after the first error.
+    pub fn into_packets(self) -> SnoopPackets<R> {
+        SnoopPackets { reader: self, done: false }
+    }
+}
+
+/// [`Iterator`] over the owned packets of a [`SnoopReader`], created by [`SnoopReader::into_packets`].
+#[derive(Debug)]
+pub struct SnoopPackets<R: Read> {
+    reader: SnoopReader<R>,
+    done: bool,
+}
+
+impl<R: Read> SnoopPackets<R> {
+    /// Returns the header of the snoop.
+    pub fn header(&self) -> SnoopHeader {
+        self.reader.header()
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> SnoopReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for SnoopPackets<R> {
+    type Item = Result<SnoopPacket<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.reader.next_packet()?.map(SnoopPacket::into_owned);
+        self.done = res.is_err();
+
+        Some(res)
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use crate::errors::*;
+use crate::snoop::{SnoopDataLink, SnoopHeader, SnoopPacket};
+
+
+/// Writes a snoop to a writer.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use pcap_file::snoop::{SnoopReader, SnoopWriter};
+///
+/// let file_in = File::open("test.snoop").expect("Error opening file");
+/// let mut snoop_reader = SnoopReader::new(file_in).unwrap();
+///
+/// let file_out = File::create("out.snoop").expect("Error creating file out");
+/// let mut snoop_writer = SnoopWriter::with_header(file_out, snoop_reader.header()).expect("Error writing file");
+///
+/// // Read test.snoop
+/// while let Some(pkt) = snoop_reader.next_packet() {
+///     //Check if there is no error
+///     let pkt = pkt.unwrap();
+///
+///     //Write each packet of test.snoop in out.snoop
+///     snoop_writer.write_packet(&pkt).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SnoopWriter<W: Write> {
+    datalink: SnoopDataLink,
+    writer: W,
+}
+
+impl<W: Write> SnoopWriter<W> {
+    /// Creates a new [`SnoopWriter`] from an existing writer.
+    ///
+    /// Writes this default snoop header to the file:
+    /// ```rust, ignore
+    /// SnoopHeader {
+    ///     version: 2,
+    ///     datalink: SnoopDataLink::ETHERNET,
+    /// };
+    /// ```
+    ///
+    /// # Errors
+    /// The writer can't be written to.
+    pub fn new(writer: W) -> PcapResult<SnoopWriter<W>> {
+        SnoopWriter::with_header(writer, SnoopHeader::default())
+    }
+
+    /// Creates a new [`SnoopWriter`] from an existing writer with a user defined [`SnoopHeader`].
+    ///
+    /// It also writes the snoop header to the file.
+    ///
+    /// # Errors
+    /// The writer can't be written to.
+    pub fn with_header(mut writer: W, header: SnoopHeader) -> PcapResult<SnoopWriter<W>> {
+        header.write_to(&mut writer)?;
+
+        Ok(SnoopWriter { datalink: header.datalink, writer })
+    }
+
+    /// Consumes [`Self`], returning the wrapped writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Writes a [`SnoopPacket`].
+    pub fn write_packet(&mut self, packet: &SnoopPacket) -> PcapResult<usize> {
+        packet.write_to(&mut self.writer)
+    }
+
+    /// Flush data
+    pub fn flush(&mut self) -> PcapResult<()> {
+        self.writer.flush().map_err(PcapError::IoError)
+    }
+
+    /// Returns the data link type written in the header.
+    pub fn datalink(&self) -> SnoopDataLink {
+        self.datalink
+    }
+}
@@ -0,0 +1,493 @@
+//! Reassembly of the TCP streams of a capture, like Wireshark's "Follow TCP stream" or `tcpflow`.
+//!
+//! A [`TcpReassembler`] follows the sequence numbers of each direction of the TCP connections: retransmissions
+//! and overlaps are delivered once, out-of-order segments are buffered until the missing data arrives, and the
+//! data which never arrives is reported as a gap. The byte streams are delivered in order to a [`StreamHandler`]:
+//! [`StreamCollector`] keeps them in memory, [`StreamFileWriter`] writes them to one file per direction.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::pcapng::PcapNgReader;
+//! use pcap_file::stream::{StreamConfig, StreamFileWriter, TcpReassembler};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+//!
+//! let mut reassembler = TcpReassembler::new(StreamFileWriter::new("streams"), StreamConfig::default());
+//! reassembler.process_pcapng_reader(&mut pcapng_reader).unwrap();
+//! reassembler.finish().unwrap();
+//! ```
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::dissect::{PacketHeaders, TcpHeader, TransportHeader};
+use crate::errors::PcapError;
+use crate::flow::FiveTuple;
+use crate::pcap::PcapReader;
+use crate::pcapng::{Block, PcapNgReader};
+use crate::DataLink;
+
+
+/// Direction of the data in a TCP stream.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// From the client, which opened the connection, to the server
+    ClientToServer,
+    /// From the server to the client
+    ServerToClient,
+}
+
+/// Receiver of the reassembled TCP streams.
+///
+/// The streams are identified by the [`FiveTuple`] of their client to server direction.
+/// The offsets count the bytes of one direction from the first byte of data, or from the first segment seen if
+/// the connection establishment was not captured. An error stops the reassembly and is returned to the caller.
+pub trait StreamHandler {
+    /// Receives the next bytes of a direction of a stream.
+    fn on_data(&mut self, stream: &FiveTuple, direction: Direction, offset: u64, data: &[u8]) -> Result<(), PcapError> {
+        let _ = (stream, direction, offset, data);
+        Ok(())
+    }
+
+    /// Receives the bytes missing in a direction of a stream, the next data starts after them.
+    fn on_gap(&mut self, stream: &FiveTuple, direction: Direction, offset: u64, len: u64) -> Result<(), PcapError> {
+        let _ = (stream, direction, offset, len);
+        Ok(())
+    }
+
+    /// Signals the end of a stream, after a FIN in both directions, a RST or the end of the capture.
+    fn on_close(&mut self, stream: &FiveTuple) -> Result<(), PcapError> {
+        let _ = stream;
+        Ok(())
+    }
+}
+
+/// Configuration of a [`TcpReassembler`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StreamConfig {
+    /// Maximum number of out-of-order bytes buffered per direction.
+    ///
+    /// When it is exceeded, the missing data is reported as a gap and the buffered data is delivered.
+    pub max_buffered: usize,
+    /// Maximum number of bytes between the next byte to deliver and the start of a segment.
+    ///
+    /// The segments further ahead, outside any plausible receive window, are dropped.
+    pub max_window: u32,
+    /// Maximum number of streams which are not closed.
+    ///
+    /// When it is exceeded, the least recently active stream is flushed and closed.
+    pub max_streams: usize,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        StreamConfig { max_buffered: 1024 * 1024, max_window: 16 * 1024 * 1024, max_streams: 65536 }
+    }
+}
+
+/// One direction of a TCP stream.
+#[derive(Default)]
+struct HalfStream {
+    /// Sequence number of the next byte to deliver, [`None`] until the first segment
+    next_seq: Option<u32>,
+    /// Stream offset of the next byte to deliver
+    offset: u64,
+    /// Out-of-order segments, by stream offset
+    segments: BTreeMap<u64, Vec<u8>>,
+    buffered: usize,
+    /// Stream offset of the FIN
+    fin: Option<u64>,
+}
+
+impl HalfStream {
+    /// Returns true if the data up to the FIN was delivered.
+    fn is_closed(&self) -> bool {
+        self.fin.is_some_and(|fin| self.offset >= fin)
+    }
+
+    /// Adds a segment and delivers the data which became contiguous.
+    fn add_segment<H: StreamHandler>(&mut self, tcp: &TcpHeader, payload: &[u8], config: &StreamConfig, ctx: (&mut H, &FiveTuple, Direction)) -> Result<(), PcapError> {
+        let syn = tcp.has_flags(TcpHeader::SYN);
+        // The SYN takes one sequence number
+        let seq = tcp.seq().wrapping_add(syn as u32);
+        let next_seq = *self.next_seq.get_or_insert(seq);
+
+        let start = self.offset as i64 + seq.wrapping_sub(next_seq) as i32 as i64;
+        if start - self.offset as i64 > config.max_window as i64 {
+            return Ok(());
+        }
+
+        let end = start + payload.len() as i64;
+        if tcp.has_flags(TcpHeader::FIN) && end >= 0 {
+            self.fin = Some(end as u64);
+        }
+
+        // Retransmitted data is skipped
+        if end > self.offset as i64 {
+            let skip = (self.offset as i64 - start).max(0) as usize;
+            let start = (start + skip as i64) as u64;
+            let data = &payload[skip..];
+
+            match self.segments.get(&start) {
+                Some(buffered) if buffered.len() >= data.len() => {},
+                _ => {
+                    self.buffered += data.len();
+                    if let Some(old) = self.segments.insert(start, data.to_vec()) {
+                        self.buffered -= old.len();
+                    }
+                },
+            }
+        }
+
+        self.deliver(ctx.0, ctx.1, ctx.2)?;
+        if self.buffered > config.max_buffered {
+            self.flush(ctx.0, ctx.1, ctx.2)?;
+        }
+
+        Ok(())
+    }
+
+    /// Delivers the buffered data which is contiguous.
+    fn deliver<H: StreamHandler>(&mut self, handler: &mut H, stream: &FiveTuple, direction: Direction) -> Result<(), PcapError> {
+        while let Some(entry) = self.segments.first_entry() {
+            if *entry.key() > self.offset {
+                break;
+            }
+
+            let start = *entry.key();
+            let data = entry.remove();
+            self.buffered -= data.len();
+
+            // Overlapping data is delivered once
+            let skip = (self.offset - start) as usize;
+            if skip < data.len() {
+                handler.on_data(stream, direction, self.offset, &data[skip..])?;
+                self.advance((data.len() - skip) as u64);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports the missing data as gaps and delivers all the buffered data.
+    fn flush<H: StreamHandler>(&mut self, handler: &mut H, stream: &FiveTuple, direction: Direction) -> Result<(), PcapError> {
+        while let Some(&start) = self.segments.keys().next() {
+            if start > self.offset {
+                handler.on_gap(stream, direction, self.offset, start - self.offset)?;
+                self.advance(start - self.offset);
+            }
+            self.deliver(handler, stream, direction)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the next byte to deliver forward.
+    fn advance(&mut self, len: u64) {
+        self.offset += len;
+        self.next_seq = self.next_seq.map(|seq| seq.wrapping_add(len as u32));
+    }
+}
+
+/// TCP connection being reassembled.
+struct TcpStream {
+    /// Five-tuple of the client to server direction
+    tuple: FiveTuple,
+    client: HalfStream,
+    server: HalfStream,
+    /// Number of the last packet of the stream, to find the least recently active one
+    last_packet: u64,
+}
+
+impl TcpStream {
+    /// Flushes both directions and closes the stream.
+    fn close<H: StreamHandler>(mut self, handler: &mut H) -> Result<(), PcapError> {
+        self.client.flush(handler, &self.tuple, Direction::ClientToServer)?;
+        self.server.flush(handler, &self.tuple, Direction::ServerToClient)?;
+        handler.on_close(&self.tuple)
+    }
+}
+
+/// Reassembles the TCP streams of a capture and delivers them to a [`StreamHandler`].
+pub struct TcpReassembler<H> {
+    handler: H,
+    config: StreamConfig,
+    /// Streams, by canonical five-tuple
+    streams: BTreeMap<FiveTuple, TcpStream>,
+    /// Link types and snaplens of the interfaces of all the sections
+    interfaces: Vec<(DataLink, u32)>,
+    /// Index in `interfaces` of the first interface of the current section
+    section_start: usize,
+    /// Number of TCP segments processed
+    packets: u64,
+}
+
+impl<H: StreamHandler> TcpReassembler<H> {
+    /// Creates a new [`TcpReassembler`].
+    pub fn new(handler: H, config: StreamConfig) -> Self {
+        TcpReassembler { handler, config, streams: BTreeMap::new(), interfaces: vec![], section_start: 0, packets: 0 }
+    }
+
+    /// Returns the handler.
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Returns the number of streams which are not closed.
+    pub fn open_streams(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Processes all the remaining packets of a [`PcapReader`].
+    pub fn process_pcap_reader<R: Read>(&mut self, reader: &mut PcapReader<R>) -> Result<(), PcapError> {
+        let datalink = reader.header().datalink;
+
+        while let Some(packet) = reader.next_packet() {
+            self.add_packet(datalink, packet?.data())?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes all the remaining blocks of a [`PcapNgReader`].
+    pub fn process_pcapng_reader<R: Read>(&mut self, reader: &mut PcapNgReader<R>) -> Result<(), PcapError> {
+        while let Some(block) = reader.next_block() {
+            self.update_from_block(&block?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes the next block of a pcapng.
+    pub fn update_from_block(&mut self, block: &Block) -> Result<(), PcapError> {
+        let (interface_id, data) = match block {
+            Block::SectionHeader(_) => {
+                self.section_start = self.interfaces.len();
+                return Ok(());
+            },
+            Block::InterfaceDescription(blk) => {
+                self.interfaces.push((blk.linktype, blk.snaplen));
+                return Ok(());
+            },
+            Block::EnhancedPacket(blk) => (blk.interface_id, &blk.data[..]),
+            Block::Packet(blk) => (blk.interface_id as u32, &blk.data[..]),
+            Block::SimplePacket(blk) => {
+                let snaplen = self.interfaces.get(self.section_start).map_or(0, |&(_, snaplen)| snaplen);
+                (0, blk.packet_data(snaplen))
+            },
+            _ => return Ok(()),
+        };
+
+        let (linktype, _) = *self
+            .interfaces
+            .get(self.section_start + interface_id as usize)
+            .ok_or(PcapError::InvalidInterfaceId(interface_id))?;
+
+        self.add_packet(linktype, data)
+    }
+
+    /// Processes a packet, the packets which are not TCP segments are ignored.
+    pub fn add_packet(&mut self, linktype: DataLink, data: &[u8]) -> Result<(), PcapError> {
+        let Some(headers) = PacketHeaders::parse(linktype, data)
+        else {
+            return Ok(());
+        };
+        let (Some(tuple), Some(TransportHeader::Tcp(tcp))) = (FiveTuple::from_headers(&headers), headers.transport)
+        else {
+            return Ok(());
+        };
+
+        let key = tuple.canonical();
+        let rst = tcp.has_flags(TcpHeader::RST);
+        if rst && !self.streams.contains_key(&key) {
+            return Ok(());
+        }
+
+        if !self.streams.contains_key(&key) && self.streams.len() >= self.config.max_streams.max(1) {
+            self.close_least_recent()?;
+        }
+
+        self.packets += 1;
+        let packets = self.packets;
+        let stream = self.streams.entry(key).or_insert_with(|| {
+            // The SYN-ACK is sent by the server
+            let client = if tcp.has_flags(TcpHeader::SYN | TcpHeader::ACK) { tuple.reversed() } else { tuple };
+            TcpStream { tuple: client, client: HalfStream::default(), server: HalfStream::default(), last_packet: packets }
+        });
+        stream.last_packet = packets;
+
+        let (half, direction) = match stream.tuple == tuple {
+            true => (&mut stream.client, Direction::ClientToServer),
+            false => (&mut stream.server, Direction::ServerToClient),
+        };
+        half.add_segment(&tcp, tcp.payload(), &self.config, (&mut self.handler, &stream.tuple, direction))?;
+
+        if rst || (stream.client.is_closed() && stream.server.is_closed()) {
+            let stream = self.streams.remove(&key).expect("The stream exists");
+            stream.close(&mut self.handler)?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes and closes the least recently active stream.
+    fn close_least_recent(&mut self) -> Result<(), PcapError> {
+        let oldest = self
+            .streams
+            .iter()
+            .min_by_key(|(_, stream)| stream.last_packet)
+            .map(|(key, _)| *key)
+            .expect("There are open streams");
+
+        let stream = self.streams.remove(&oldest).expect("The stream exists");
+        stream.close(&mut self.handler)
+    }
+
+    /// Flushes and closes all the remaining streams, then returns the handler.
+    pub fn finish(mut self) -> Result<H, PcapError> {
+        for (_, stream) in std::mem::take(&mut self.streams) {
+            stream.close(&mut self.handler)?;
+        }
+
+        Ok(self.handler)
+    }
+}
+
+
+/// Both directions of a TCP stream collected by a [`StreamCollector`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CollectedStream {
+    /// Data from the client to the server, without the gaps
+    pub client_data: Vec<u8>,
+    /// Ranges of stream offsets of the client to server data which were not captured
+    pub client_gaps: Vec<Range<u64>>,
+    /// Data from the server to the client, without the gaps
+    pub server_data: Vec<u8>,
+    /// Ranges of stream offsets of the server to client data which were not captured
+    pub server_gaps: Vec<Range<u64>>,
+    /// True if the stream was closed
+    pub closed: bool,
+}
+
+impl CollectedStream {
+    /// Returns the data and gaps of a direction.
+    fn direction_mut(&mut self, direction: Direction) -> (&mut Vec<u8>, &mut Vec<Range<u64>>) {
+        match direction {
+            Direction::ClientToServer => (&mut self.client_data, &mut self.client_gaps),
+            Direction::ServerToClient => (&mut self.server_data, &mut self.server_gaps),
+        }
+    }
+}
+
+/// [`StreamHandler`] keeping the streams in memory.
+#[derive(Clone, Debug, Default)]
+pub struct StreamCollector {
+    /// Streams in order of appearance
+    streams: Vec<(FiveTuple, CollectedStream)>,
+    index: HashMap<FiveTuple, usize>,
+}
+
+impl StreamCollector {
+    /// Creates an empty [`StreamCollector`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the streams, identified by their client to server five-tuple, in order of appearance.
+    ///
+    /// A five-tuple reused by a new connection appears once per connection.
+    pub fn streams(&self) -> &[(FiveTuple, CollectedStream)] {
+        &self.streams
+    }
+
+    /// Returns the open stream of a five-tuple, creating it if needed.
+    fn stream_mut(&mut self, tuple: &FiveTuple) -> &mut CollectedStream {
+        let streams = &mut self.streams;
+        let index = *self.index.entry(*tuple).or_insert_with(|| {
+            streams.push((*tuple, CollectedStream::default()));
+            streams.len() - 1
+        });
+
+        &mut self.streams[index].1
+    }
+}
+
+impl StreamHandler for StreamCollector {
+    fn on_data(&mut self, stream: &FiveTuple, direction: Direction, _offset: u64, data: &[u8]) -> Result<(), PcapError> {
+        self.stream_mut(stream).direction_mut(direction).0.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn on_gap(&mut self, stream: &FiveTuple, direction: Direction, offset: u64, len: u64) -> Result<(), PcapError> {
+        self.stream_mut(stream).direction_mut(direction).1.push(offset..offset + len);
+        Ok(())
+    }
+
+    fn on_close(&mut self, stream: &FiveTuple) -> Result<(), PcapError> {
+        self.stream_mut(stream).closed = true;
+        self.index.remove(stream);
+        Ok(())
+    }
+}
+
+/// [`StreamHandler`] writing each direction of the streams to a file, like `tcpflow`.
+///
+/// The files are named `{src_ip}.{src_port}-{dst_ip}.{dst_port}` after the direction they contain, the `:` of
+/// the IPv6 addresses being replaced by `_`. The data is written at its stream offset, so the gaps, which are always
+/// followed by data, read as zeros.
+/// A file is overwritten by a new connection with the same five-tuple.
+pub struct StreamFileWriter {
+    dir: PathBuf,
+    files: HashMap<(FiveTuple, Direction), File>,
+}
+
+impl StreamFileWriter {
+    /// Creates a new [`StreamFileWriter`] writing to an existing directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        StreamFileWriter { dir: dir.into(), files: HashMap::new() }
+    }
+
+    /// Returns the path of the file of a direction of a stream.
+    pub fn path(&self, stream: &FiveTuple, direction: Direction) -> PathBuf {
+        let tuple = match direction {
+            Direction::ClientToServer => *stream,
+            Direction::ServerToClient => stream.reversed(),
+        };
+
+        let name = format!("{}.{}-{}.{}", tuple.src, tuple.src_port, tuple.dst, tuple.dst_port);
+        self.dir.join(name.replace(':', "_"))
+    }
+
+    /// Returns the file of a direction of a stream, creating it if needed.
+    fn file(&mut self, stream: &FiveTuple, direction: Direction) -> Result<&mut File, PcapError> {
+        let path = self.path(stream, direction);
+        let file = match self.files.entry((*stream, direction)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(File::create(path)?),
+        };
+
+        Ok(file)
+    }
+}
+
+impl StreamHandler for StreamFileWriter {
+    fn on_data(&mut self, stream: &FiveTuple, direction: Direction, offset: u64, data: &[u8]) -> Result<(), PcapError> {
+        let file = self.file(stream, direction)?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(data)?;
+        Ok(())
+    }
+
+    fn on_close(&mut self, stream: &FiveTuple) -> Result<(), PcapError> {
+        self.files.remove(&(*stream, Direction::ClientToServer));
+        self.files.remove(&(*stream, Direction::ServerToClient));
+        Ok(())
+    }
+}
@@ -0,0 +1,602 @@
+//! Import of hex dumps into captures, like `text2pcap`.
+//!
+//! A [`HexDumpReader`] reads the packets of a hex dump, as written by `od -Ax -tx1` or by the "Export as hex"
+//! of Wireshark. Each line starts with the offset of its first byte in the packet, a line with the offset 0 starting
+//! a new packet, followed by the bytes in hexadecimal and an optional ASCII dump:
+//! ```text
+//! I 2024-01-02 03:04:05.678 0000  00 11 22 33 44 55 66 77  88 99 aa bb cc dd ee ff   .."3DUfw........
+//! 0010  01 02                                              ..
+//! ```
+//!
+//! The first line of a packet can be preceded by a direction marker, `I` for inbound or `O` for outbound,
+//! and by a timestamp whose format is given to the reader.
+//!
+//! [`hex_to_pcap`] and [`hex_to_pcapng`] write the packets into a capture, optionally wrapping them into dummy
+//! Ethernet, IP, UDP, TCP or SCTP headers, see [`DummyHeaders`].
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! use pcap_file::text2pcap::{hex_to_pcapng, DummyHeaders, Text2PcapConfig};
+//!
+//! let file_in = File::open("dump.txt").expect("Error opening file");
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//!
+//! // The dump holds the payloads of UDP datagrams between the ports 5060
+//! let config = Text2PcapConfig { headers: DummyHeaders::Udp { src_port: 5060, dst_port: 5060 }, ..Default::default() };
+//! hex_to_pcapng(BufReader::new(file_in), file_out, &config).unwrap();
+//! ```
+
+use std::borrow::Cow;
+use std::io::{BufRead, Write};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+use crate::common::days_from_civil;
+use crate::convert::MAXIMUM_SNAPLEN;
+use crate::dissect::{fold, sum};
+use crate::errors::{PcapError, PcapResult};
+use crate::pcap::{PcapHeader, PcapPacket, PcapWriter};
+use crate::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use crate::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption, TsResolution as NgTsResolution};
+use crate::pcapng::blocks::section_header::SectionHeaderBlock;
+use crate::pcapng::PcapNgWriter;
+use crate::{DataLink, Endianness, TsResolution};
+
+
+/// Source MAC address of the dummy Ethernet header
+const SRC_MAC: [u8; 6] = [0x0A, 0x02, 0x02, 0x02, 0x02, 0x01];
+/// Destination MAC address of the dummy Ethernet header
+const DST_MAC: [u8; 6] = [0x0A, 0x02, 0x02, 0x02, 0x02, 0x02];
+
+/// Direction of a packet, given by the marker at the start of its first line.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PacketDirection {
+    /// Packet received, marked with `I`
+    Inbound,
+    /// Packet sent, marked with `O`
+    Outbound,
+}
+
+/// A packet of a hex dump.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HexDumpPacket {
+    /// Timestamp EPOCH of the packet, if its first line has one
+    pub timestamp: Option<Duration>,
+    /// Direction of the packet, if its first line has a marker
+    pub direction: Option<PacketDirection>,
+    /// Bytes of the packet
+    pub data: Vec<u8>,
+}
+
+/// Reads the packets of a hex dump.
+///
+/// The lines which don't start with an offset, like the comments starting with `#`, are skipped. A line whose offset
+/// is lower than the length of the packet overwrites its last bytes, which drops the ASCII dumps parsed as bytes.
+/// A line whose offset is after the end of the packet, like a comment starting with a hexadecimal word, is skipped.
+/// The bytes of a line end at the first token which isn't a 2-digit hexadecimal number or after 3 spaces.
+#[derive(Debug)]
+pub struct HexDumpReader<R: BufRead> {
+    reader: R,
+    timestamp_format: Option<String>,
+    line: String,
+    current: Option<HexDumpPacket>,
+}
+
+impl<R: BufRead> HexDumpReader<R> {
+    /// Creates a new [`HexDumpReader`] for a dump without timestamps.
+    pub fn new(reader: R) -> Self {
+        HexDumpReader { reader, timestamp_format: None, line: String::new(), current: None }
+    }
+
+    /// Creates a new [`HexDumpReader`] for a dump whose packets are preceded by a timestamp.
+    ///
+    /// The format is a subset of the `strptime` one: `%Y`, `%m`, `%d`, `%H`, `%M`, `%S`, `%T` (`%H:%M:%S`),
+    /// `%f` (fraction of a second, up to 9 digits), `%s` (seconds since the EPOCH) and `%%`.
+    /// A space matches any number of spaces. The missing fields of the date default to 1970-01-01,
+    /// the [`timeshift`](crate::timeshift) module can move the packets to the right date.
+    pub fn with_timestamp_format(reader: R, timestamp_format: &str) -> Self {
+        HexDumpReader { timestamp_format: Some(timestamp_format.to_string()), ..Self::new(reader) }
+    }
+
+    /// Returns the next [`HexDumpPacket`].
+    pub fn next_packet(&mut self) -> Option<PcapResult<HexDumpPacket>> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return self.current.take().filter(|packet| !packet.data.is_empty()).map(Ok),
+                Ok(_) => {},
+                Err(e) => return Some(Err(PcapError::IoError(e))),
+            }
+
+            let Some((direction, timestamp, offset, bytes)) = parse_line(&self.line, self.timestamp_format.as_deref())
+            else {
+                continue;
+            };
+
+            if offset == 0 {
+                let next = HexDumpPacket { timestamp, direction, data: bytes };
+                match self.current.replace(next) {
+                    Some(packet) if !packet.data.is_empty() => return Some(Ok(packet)),
+                    _ => continue,
+                }
+            }
+
+            let Some(packet) = &mut self.current
+            else {
+                continue;
+            };
+
+            // Like text2pcap, the inconsistent offsets are ignored
+            if offset > packet.data.len() {
+                continue;
+            }
+
+            packet.data.truncate(offset);
+            packet.data.extend_from_slice(&bytes);
+        }
+    }
+
+    /// Consumes [`Self`], returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for HexDumpReader<R> {
+    type Item = PcapResult<HexDumpPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet()
+    }
+}
+
+/// Dummy headers wrapping the packets of a hex dump.
+///
+/// The headers other than [`DummyHeaders::None`] start with an Ethernet header. The IP headers use the addresses of
+/// the [`Text2PcapConfig`], the addresses and ports of the inbound packets being swapped. The checksums are computed.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DummyHeaders {
+    /// No header, the packets are written as they are
+    #[default]
+    None,
+    /// Ethernet header
+    Ethernet {
+        /// Type of the payload
+        ethertype: u16,
+    },
+    /// Ethernet and IP headers
+    Ip {
+        /// Protocol of the payload
+        protocol: u8,
+    },
+    /// Ethernet, IP and UDP headers
+    Udp {
+        /// Source port
+        src_port: u16,
+        /// Destination port
+        dst_port: u16,
+    },
+    /// Ethernet, IP and TCP headers, with sequence numbers following the length of the payloads
+    Tcp {
+        /// Source port
+        src_port: u16,
+        /// Destination port
+        dst_port: u16,
+    },
+    /// Ethernet, IP and SCTP common headers
+    Sctp {
+        /// Source port
+        src_port: u16,
+        /// Destination port
+        dst_port: u16,
+        /// Verification tag
+        tag: u32,
+    },
+    /// Ethernet, IP and SCTP headers, with a DATA chunk whose TSN is incremented for each packet
+    SctpData {
+        /// Source port
+        src_port: u16,
+        /// Destination port
+        dst_port: u16,
+        /// Verification tag
+        tag: u32,
+        /// Payload protocol identifier
+        ppi: u32,
+    },
+}
+
+/// Configuration of the import of a hex dump.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Text2PcapConfig {
+    /// Link type of the packets without dummy headers, Ethernet otherwise
+    pub datalink: DataLink,
+    /// Dummy headers wrapping the packets
+    pub headers: DummyHeaders,
+    /// Source address of the dummy IP header, both addresses must be of the same version
+    pub src_ip: IpAddr,
+    /// Destination address of the dummy IP header
+    pub dst_ip: IpAddr,
+    /// Format of the timestamps preceding the packets, see [`HexDumpReader::with_timestamp_format`]
+    pub timestamp_format: Option<String>,
+    /// Timestamp of the first packet if it has none.
+    ///
+    /// The packets without timestamp are 1 µs after the previous one.
+    pub start_time: Duration,
+}
+
+/// Creates a new [`Text2PcapConfig`] with these parameters:
+///
+/// ```rust,ignore
+/// Text2PcapConfig {
+///     datalink: DataLink::ETHERNET,
+///     headers: DummyHeaders::None,
+///     src_ip: IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)),
+///     dst_ip: IpAddr::V4(Ipv4Addr::new(2, 2, 2, 2)),
+///     timestamp_format: None,
+///     start_time: Duration::ZERO,
+/// };
+/// ```
+impl Default for Text2PcapConfig {
+    fn default() -> Self {
+        Text2PcapConfig {
+            datalink: DataLink::ETHERNET,
+            headers: DummyHeaders::None,
+            src_ip: IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)),
+            dst_ip: IpAddr::V4(Ipv4Addr::new(2, 2, 2, 2)),
+            timestamp_format: None,
+            start_time: Duration::ZERO,
+        }
+    }
+}
+
+impl Text2PcapConfig {
+    /// Returns the link type of the imported packets.
+    pub fn linktype(&self) -> DataLink {
+        match self.headers {
+            DummyHeaders::None => self.datalink,
+            _ => DataLink::ETHERNET,
+        }
+    }
+}
+
+/// Imports a hex dump into a Pcap.
+///
+/// The timestamp resolution is nanosecond if the timestamp format has a fraction of a second, microsecond otherwise.
+pub fn hex_to_pcap<R: BufRead, W: Write>(reader: R, writer: W, config: &Text2PcapConfig) -> PcapResult<PcapWriter<W>> {
+    let ts_resolution = match &config.timestamp_format {
+        Some(format) if format.contains("%f") => TsResolution::NanoSecond,
+        _ => TsResolution::MicroSecond,
+    };
+
+    let header = PcapHeader {
+        snaplen: MAXIMUM_SNAPLEN,
+        datalink: config.linktype(),
+        ts_resolution,
+        endianness: Endianness::native(),
+        ..Default::default()
+    };
+    let mut writer = PcapWriter::with_header(writer, header)?;
+
+    import(reader, config, |timestamp, _, data| {
+        writer.write_packet(&PcapPacket::new(timestamp, data.len() as u32, data)?)?;
+        Ok(())
+    })?;
+
+    Ok(writer)
+}
+
+/// Imports a hex dump into a PcapNg.
+///
+/// Writes a section header with the native endianness, an [`InterfaceDescriptionBlock`] with a nanosecond resolution
+/// and an [`EnhancedPacketBlock`] per packet. The direction of the packets is stored in the `epb_flags` option.
+pub fn hex_to_pcapng<R: BufRead, W: Write>(reader: R, writer: W, config: &Text2PcapConfig) -> PcapResult<PcapNgWriter<W>> {
+    let section = SectionHeaderBlock { endianness: Endianness::native(), ..Default::default() };
+    let mut writer = PcapNgWriter::with_section_header(writer, section)?;
+
+    let interface = InterfaceDescriptionBlock {
+        linktype: config.linktype(),
+        snaplen: 0,
+        options: vec![InterfaceDescriptionOption::IfTsResol(NgTsResolution::NANO.to_raw())],
+    };
+    writer.write_pcapng_block(interface)?;
+
+    import(reader, config, |timestamp, direction, data| {
+        let options = match direction {
+            Some(PacketDirection::Inbound) => vec![EnhancedPacketOption::Flags(0x01)],
+            Some(PacketDirection::Outbound) => vec![EnhancedPacketOption::Flags(0x02)],
+            None => vec![],
+        };
+
+        let block = EnhancedPacketBlock { interface_id: 0, timestamp, original_len: data.len() as u32, data: Cow::Borrowed(data), options };
+        writer.write_pcapng_block(block)?;
+        Ok(())
+    })?;
+
+    Ok(writer)
+}
+
+/// Reads the packets of a hex dump and calls `f` with their timestamp, direction and data wrapped into the headers.
+fn import<R, F>(reader: R, config: &Text2PcapConfig, mut f: F) -> PcapResult<()>
+where
+    R: BufRead,
+    F: FnMut(Duration, Option<PacketDirection>, &[u8]) -> PcapResult<()>,
+{
+    if config.src_ip.is_ipv4() != config.dst_ip.is_ipv4() {
+        return Err(PcapError::InvalidField("Text2PcapConfig: src_ip and dst_ip have different versions"));
+    }
+
+    let mut reader = match &config.timestamp_format {
+        Some(format) => HexDumpReader::with_timestamp_format(reader, format),
+        None => HexDumpReader::new(reader),
+    };
+
+    let mut encapsulator = Encapsulator { config, ip_id: 0, tcp_seq: [0; 2], sctp_tsn: 0 };
+    let mut last_timestamp = None;
+
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        let timestamp = match (packet.timestamp, last_timestamp) {
+            (Some(timestamp), _) => timestamp,
+            (None, Some(last)) => last + Duration::from_micros(1),
+            (None, None) => config.start_time,
+        };
+        last_timestamp = Some(timestamp);
+
+        let data = encapsulator.wrap(&packet.data, packet.direction)?;
+        f(timestamp, packet.direction, &data)?;
+    }
+
+    Ok(())
+}
+
+/// Builds the dummy headers, keeping the state of the counters.
+struct Encapsulator<'a> {
+    config: &'a Text2PcapConfig,
+    ip_id: u16,
+    tcp_seq: [u32; 2],
+    sctp_tsn: u32,
+}
+
+impl Encapsulator<'_> {
+    /// Wraps a payload into the dummy headers.
+    fn wrap(&mut self, payload: &[u8], direction: Option<PacketDirection>) -> PcapResult<Vec<u8>> {
+        let inbound = direction == Some(PacketDirection::Inbound);
+        let (src_ip, dst_ip) = if inbound { (self.config.dst_ip, self.config.src_ip) } else { (self.config.src_ip, self.config.dst_ip) };
+        let swap = |src_port: u16, dst_port: u16| if inbound { (dst_port, src_port) } else { (src_port, dst_port) };
+
+        let (protocol, transport) = match self.config.headers {
+            DummyHeaders::None => return Ok(payload.to_vec()),
+            DummyHeaders::Ethernet { ethertype } => return Ok(ethernet(ethertype, inbound, payload)),
+            DummyHeaders::Ip { protocol } => (protocol, payload.to_vec()),
+            DummyHeaders::Udp { src_port, dst_port } => {
+                let (src_port, dst_port) = swap(src_port, dst_port);
+                let len = u16::try_from(8 + payload.len()).map_err(|_| PcapError::InvalidField("Text2Pcap: UDP payload too long"))?;
+
+                let mut udp = [&src_port.to_be_bytes()[..], &dst_port.to_be_bytes(), &len.to_be_bytes(), &[0, 0], payload].concat();
+                let checksum = match !fold(sum(pseudo_header_sum(src_ip, dst_ip, 17, udp.len()), &udp)) {
+                    0 => 0xFFFF,
+                    checksum => checksum,
+                };
+                udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+                (17, udp)
+            },
+            DummyHeaders::Tcp { src_port, dst_port } => {
+                let (src_port, dst_port) = swap(src_port, dst_port);
+                let seq = &mut self.tcp_seq[inbound as usize];
+
+                let mut tcp = [
+                    &src_port.to_be_bytes()[..],
+                    &dst_port.to_be_bytes(),
+                    &seq.to_be_bytes(),
+                    &[0, 0, 0, 0], // Acknowledgment number
+                    &[0x50, 0x18], // Header length, PSH and ACK
+                    &[0x20, 0x00], // Window
+                    &[0, 0, 0, 0], // Checksum and urgent pointer
+                    payload,
+                ]
+                .concat();
+                *seq = seq.wrapping_add(payload.len() as u32);
+
+                let checksum = !fold(sum(pseudo_header_sum(src_ip, dst_ip, 6, tcp.len()), &tcp));
+                tcp[16..18].copy_from_slice(&checksum.to_be_bytes());
+                (6, tcp)
+            },
+            DummyHeaders::Sctp { src_port, dst_port, tag } => (132, sctp(swap(src_port, dst_port), tag, payload)),
+            DummyHeaders::SctpData { src_port, dst_port, tag, ppi } => {
+                let chunk_len = u16::try_from(16 + payload.len()).map_err(|_| PcapError::InvalidField("Text2Pcap: SCTP payload too long"))?;
+                let padding = [0_u8; 3];
+
+                let chunk = [
+                    &[0x00, 0x03][..], // DATA chunk, beginning and end of the message
+                    &chunk_len.to_be_bytes(),
+                    &self.sctp_tsn.to_be_bytes(),
+                    &[0, 0, 0, 0], // Stream identifier and sequence number
+                    &ppi.to_be_bytes(),
+                    payload,
+                    &padding[..(4 - payload.len() % 4) % 4],
+                ]
+                .concat();
+                self.sctp_tsn = self.sctp_tsn.wrapping_add(1);
+
+                (132, sctp(swap(src_port, dst_port), tag, &chunk))
+            },
+        };
+
+        let ip = match (src_ip, dst_ip) {
+            (IpAddr::V4(src), IpAddr::V4(dst)) => {
+                let total_len = u16::try_from(20 + transport.len()).map_err(|_| PcapError::InvalidField("Text2Pcap: IPv4 payload too long"))?;
+
+                let mut ip = [
+                    &[0x45, 0x00][..],
+                    &total_len.to_be_bytes(),
+                    &self.ip_id.to_be_bytes(),
+                    &[0x00, 0x00, 0xFF, protocol, 0x00, 0x00], // Fragment, TTL, protocol and checksum
+                    &src.octets(),
+                    &dst.octets(),
+                ]
+                .concat();
+                self.ip_id = self.ip_id.wrapping_add(1);
+
+                let checksum = !fold(sum(0, &ip));
+                ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+                ip.extend_from_slice(&transport);
+                ip
+            },
+            (IpAddr::V6(src), IpAddr::V6(dst)) => {
+                let payload_len = u16::try_from(transport.len()).map_err(|_| PcapError::InvalidField("Text2Pcap: IPv6 payload too long"))?;
+
+                [
+                    &[0x60, 0x00, 0x00, 0x00][..],
+                    &payload_len.to_be_bytes(),
+                    &[protocol, 0xFF], // Next header and hop limit
+                    &src.octets(),
+                    &dst.octets(),
+                    &transport,
+                ]
+                .concat()
+            },
+            _ => unreachable!("the versions of the addresses are checked before the import"),
+        };
+
+        let ethertype = if src_ip.is_ipv4() { 0x0800 } else { 0x86DD };
+        Ok(ethernet(ethertype, inbound, &ip))
+    }
+}
+
+/// Wraps a payload into an Ethernet header.
+fn ethernet(ethertype: u16, inbound: bool, payload: &[u8]) -> Vec<u8> {
+    let (src, dst) = if inbound { (DST_MAC, SRC_MAC) } else { (SRC_MAC, DST_MAC) };
+    [&dst[..], &src, &ethertype.to_be_bytes(), payload].concat()
+}
+
+/// Wraps chunks into an SCTP common header, with its CRC32c checksum.
+fn sctp((src_port, dst_port): (u16, u16), tag: u32, chunks: &[u8]) -> Vec<u8> {
+    let mut sctp = [&src_port.to_be_bytes()[..], &dst_port.to_be_bytes(), &tag.to_be_bytes(), &[0, 0, 0, 0], chunks].concat();
+
+    let mut crc = !0_u32;
+    for &byte in &sctp {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0x82F63B78 & (crc & 1).wrapping_neg());
+        }
+    }
+    sctp[8..12].copy_from_slice(&(!crc).to_le_bytes());
+
+    sctp
+}
+
+/// Returns the sum of the IP pseudo-header of a transport protocol.
+fn pseudo_header_sum(src: IpAddr, dst: IpAddr, protocol: u8, len: usize) -> u32 {
+    let addresses = match (src, dst) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => sum(sum(0, &src.octets()), &dst.octets()),
+        (IpAddr::V6(src), IpAddr::V6(dst)) => sum(sum(0, &src.octets()), &dst.octets()),
+        _ => unreachable!("the versions of the addresses are checked before the import"),
+    };
+
+    addresses + protocol as u32 + (len >> 16) as u32 + (len & 0xFFFF) as u32
+}
+
+/// Parses a line of a hex dump into its direction, timestamp, offset and bytes.
+///
+/// Returns [`None`] if the line doesn't start with an offset.
+#[allow(clippy::type_complexity)]
+fn parse_line(line: &str, timestamp_format: Option<&str>) -> Option<(Option<PacketDirection>, Option<Duration>, usize, Vec<u8>)> {
+    let mut rest = line.trim_start();
+    if rest.starts_with('#') {
+        return None;
+    }
+
+    let mut direction = None;
+    for (marker, dir) in [("I", PacketDirection::Inbound), ("O", PacketDirection::Outbound)] {
+        if let Some(after) = rest.strip_prefix(marker).filter(|after| after.starts_with(char::is_whitespace)) {
+            direction = Some(dir);
+            rest = after.trim_start();
+        }
+    }
+
+    let mut timestamp = None;
+    if let Some((ts, after)) = timestamp_format.and_then(|format| parse_timestamp(format, rest)) {
+        timestamp = Some(ts);
+        rest = after.trim_start();
+    }
+
+    let (offset, after) = rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+    if offset.is_empty() || !offset.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let offset = usize::from_str_radix(offset, 16).ok()?;
+    rest = after;
+
+    let mut bytes = vec![];
+    loop {
+        let token = rest.trim_start();
+        let spaces = rest.len() - token.len();
+        if token.is_empty() || (!bytes.is_empty() && spaces >= 3) {
+            break;
+        }
+
+        let (byte, after) = token.split_at(token.find(char::is_whitespace).unwrap_or(token.len()));
+        if byte.len() != 2 || !byte.bytes().all(|b| b.is_ascii_hexdigit()) {
+            break;
+        }
+        bytes.push(u8::from_str_radix(byte, 16).unwrap());
+        rest = after;
+    }
+
+    Some((direction, timestamp, offset, bytes))
+}
+
+/// Parses a timestamp at the start of a string, see [`HexDumpReader::with_timestamp_format`].
+///
+/// Returns the timestamp and the remainder of the string.
+fn parse_timestamp<'s>(format: &str, mut s: &'s str) -> Option<(Duration, &'s str)> {
+    let (mut year, mut month, mut day, mut hour, mut minute, mut second, mut nanos) = (1970, 1, 1, 0, 0, 0, 0);
+    let mut epoch = None;
+
+    // Parses a number of at most `max` digits
+    let number = |s: &mut &'s str, max: usize| -> Option<(i64, usize)> {
+        let len = s.bytes().take(max).take_while(u8::is_ascii_digit).count();
+        let value = s[..len].parse().ok()?;
+        *s = &s[len..];
+        Some((value, len))
+    };
+
+    let format = format.replace("%T", "%H:%M:%S");
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => match chars.next()? {
+                'Y' => year = number(&mut s, 4)?.0,
+                'm' => month = number(&mut s, 2)?.0,
+                'd' => day = number(&mut s, 2)?.0,
+                'H' => hour = number(&mut s, 2)?.0,
+                'M' => minute = number(&mut s, 2)?.0,
+                'S' => second = number(&mut s, 2)?.0,
+                's' => epoch = Some(number(&mut s, 20)?.0),
+                'f' => {
+                    let (fraction, digits) = number(&mut s, 9)?;
+                    nanos = fraction * 10_i64.pow(9 - digits as u32);
+                },
+                '%' => s = s.strip_prefix('%')?,
+                _ => return None,
+            },
+            c if c.is_whitespace() => s = s.trim_start(),
+            c => s = s.strip_prefix(c)?,
+        }
+    }
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let secs = match epoch {
+        Some(epoch) => epoch,
+        None => days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second,
+    };
+
+    Some((Duration::new(secs.try_into().ok()?, nanos as u32), s))
+}
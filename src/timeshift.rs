@@ -0,0 +1,333 @@
+//! Rewriting of the timestamps of a capture, like `editcap -t`.
+//!
+//! A [`TimeShift`] describes how to move the timestamps: by a fixed [`TimeOffset`], so the first packet starts
+//! at a given instant, and/or by a per-interface offset. [`PcapTimeShift`] and [`PcapNgTimeShift`] apply it to
+//! an [`Iterator`] of packets or blocks.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+//! use pcap_file::timeshift::{PcapNgTimeShift, TimeOffset, TimeShift};
+//!
+//! let file_in = File::open("test.pcapng").expect("Error opening file");
+//! let blocks = PcapNgReader::new(file_in).unwrap().into_blocks();
+//!
+//! let file_out = File::create("out.pcapng").expect("Error creating file");
+//! let mut pcapng_writer = PcapNgWriter::with_section_header(file_out, blocks.section().clone()).unwrap();
+//!
+//! // The clock of the host of the second interface is 1.5 s late
+//! let shift = TimeShift { interface_offsets: vec![TimeOffset::ZERO, TimeOffset::forward(Duration::from_millis(1500))], ..Default::default() };
+//!
+//! for block in PcapNgTimeShift::new(blocks, shift) {
+//!     pcapng_writer.write_block(&block.unwrap()).unwrap();
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::ops::Add;
+use std::time::Duration;
+
+use crate::errors::{PcapError, PcapResult};
+use crate::pcap::PcapPacket;
+use crate::pcapng::blocks::interface_description::InterfaceDescriptionOption;
+use crate::pcapng::blocks::interface_statistics::InterfaceStatisticsOption;
+use crate::pcapng::Block;
+
+
+/// A signed offset between two timestamps.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TimeOffset {
+    /// Absolute value of the offset
+    pub duration: Duration,
+    /// True if the offset moves the timestamps backward
+    pub negative: bool,
+}
+
+impl TimeOffset {
+    /// The offset leaving the timestamps unchanged.
+    pub const ZERO: TimeOffset = TimeOffset { duration: Duration::ZERO, negative: false };
+
+    /// Creates a [`TimeOffset`] moving the timestamps forward.
+    pub fn forward(duration: Duration) -> Self {
+        TimeOffset { duration, negative: false }
+    }
+
+    /// Creates a [`TimeOffset`] moving the timestamps backward.
+    pub fn backward(duration: Duration) -> Self {
+        TimeOffset { duration, negative: !duration.is_zero() }
+    }
+
+    /// Creates the [`TimeOffset`] moving `from` to `to`.
+    pub fn between(from: Duration, to: Duration) -> Self {
+        if to >= from {
+            Self::forward(to - from)
+        }
+        else {
+            Self::backward(from - to)
+        }
+    }
+
+    /// Applies the offset to a timestamp.
+    ///
+    /// # Errors
+    /// The result is before 1970-01-01 00:00:00 UTC or doesn't fit in a [`Duration`].
+    pub fn apply(self, timestamp: Duration) -> PcapResult<Duration> {
+        let res = if self.negative { timestamp.checked_sub(self.duration) } else { timestamp.checked_add(self.duration) };
+        res.ok_or(PcapError::InvalidField("shifted timestamp out of range"))
+    }
+
+    /// Applies the whole seconds of the offset, rounded down, to an `if_tsoffset` value, saturating at 0.
+    ///
+    /// The timestamps of an interface remain greater than its `if_tsoffset` once both are shifted.
+    fn apply_to_ts_offset(self, ts_offset: u64) -> u64 {
+        if self.negative {
+            ts_offset.saturating_sub(self.duration.as_secs() + (self.duration.subsec_nanos() != 0) as u64)
+        }
+        else {
+            ts_offset.saturating_add(self.duration.as_secs())
+        }
+    }
+}
+
+impl Add for TimeOffset {
+    type Output = TimeOffset;
+
+    fn add(self, other: TimeOffset) -> TimeOffset {
+        match (self.negative, other.negative) {
+            (false, false) => Self::forward(self.duration + other.duration),
+            (true, true) => Self::backward(self.duration + other.duration),
+            (false, true) => Self::between(other.duration, self.duration),
+            (true, false) => Self::between(self.duration, other.duration),
+        }
+    }
+}
+
+
+/// Timestamps rewriting of a capture.
+///
+/// The offset applied to a packet is the sum of the offset of its interface and of the global offset.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimeShift {
+    /// Global offset, applied to all the timestamps
+    pub offset: TimeOffset,
+    /// If set, the global offset is computed so that the first packet, once shifted by the offset of its
+    /// interface, starts at this instant. Replaces `offset`.
+    pub rebase: Option<Duration>,
+    /// Offsets of the interfaces, indexed by interface id within a section.
+    ///
+    /// The missing interfaces have no offset. A pcap has a single interface, with id 0.
+    pub interface_offsets: Vec<TimeOffset>,
+}
+
+impl TimeShift {
+    /// Creates a [`TimeShift`] moving all the timestamps by `offset`.
+    pub fn shift(offset: TimeOffset) -> Self {
+        TimeShift { offset, ..Default::default() }
+    }
+
+    /// Creates a [`TimeShift`] moving all the timestamps so that the first packet starts at `instant`.
+    pub fn rebase(instant: Duration) -> Self {
+        TimeShift { rebase: Some(instant), ..Default::default() }
+    }
+
+    /// Returns the offset of an interface.
+    fn interface_offset(&self, interface_id: u32) -> TimeOffset {
+        self.interface_offsets.get(interface_id as usize).copied().unwrap_or_default()
+    }
+
+    /// Returns the global offset for a capture whose first packet has the given interface and timestamp.
+    fn global_offset(&self, first_packet: Option<(u32, Duration)>) -> PcapResult<TimeOffset> {
+        match (self.rebase, first_packet) {
+            (Some(instant), Some((interface_id, timestamp))) => {
+                let timestamp = self.interface_offset(interface_id).apply(timestamp)?;
+                Ok(TimeOffset::between(timestamp, instant))
+            },
+            (Some(_), None) => Ok(TimeOffset::ZERO),
+            (None, _) => Ok(self.offset),
+        }
+    }
+}
+
+
+/// [`Iterator`] adapter rewriting the timestamps of [`PcapPacket`].
+///
+/// After the first error, the iterator ends.
+pub struct PcapTimeShift<I> {
+    inner: I,
+    shift: TimeShift,
+    /// Total offset of the packets, known once the first packet is read
+    offset: Option<TimeOffset>,
+    done: bool,
+}
+
+impl<I> PcapTimeShift<I> {
+    /// Creates a new [`PcapTimeShift`] over an iterator of packets.
+    pub fn new(inner: I, shift: TimeShift) -> Self {
+        PcapTimeShift { inner, shift, offset: None, done: false }
+    }
+
+    /// Shifts the timestamp of a packet.
+    fn shift_packet<'a>(&mut self, packet: PcapPacket<'a>) -> PcapResult<PcapPacket<'a>> {
+        let offset = match self.offset {
+            Some(offset) => offset,
+            None => {
+                let global = self.shift.global_offset(Some((0, packet.timestamp())))?;
+                *self.offset.insert(self.shift.interface_offset(0) + global)
+            },
+        };
+
+        shift_pcap_packet(packet, offset)
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapTimeShift<I> {
+    type Item = Result<PcapPacket<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.inner.next()?.and_then(|packet| self.shift_packet(packet));
+        self.done = res.is_err();
+        Some(res)
+    }
+}
+
+
+/// [`Iterator`] adapter rewriting the timestamps of the enhanced packet, obsolete packet and
+/// interface statistics blocks.
+///
+/// The `if_tsoffset` option of the interfaces is shifted by the whole seconds of their offset,
+/// so the packets can still be written with it.
+///
+/// With [`TimeShift::rebase`], the blocks before the first packet are buffered until it is read.
+///
+/// After the first error, the iterator ends.
+pub struct PcapNgTimeShift<'a, I> {
+    inner: I,
+    shift: TimeShift,
+    /// Global offset, known once the first packet is read
+    global_offset: Option<TimeOffset>,
+    /// Blocks read before the global offset is known
+    pending: VecDeque<Block<'a>>,
+    /// Number of interfaces of the current section
+    interface_count: u32,
+    done: bool,
+}
+
+impl<I> PcapNgTimeShift<'_, I> {
+    /// Creates a new [`PcapNgTimeShift`] over an iterator of blocks.
+    pub fn new(inner: I, shift: TimeShift) -> Self {
+        let global_offset = if shift.rebase.is_some() { None } else { Some(shift.offset) };
+        PcapNgTimeShift { inner, shift, global_offset, pending: VecDeque::new(), interface_count: 0, done: false }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> PcapNgTimeShift<'a, I> {
+    /// Returns the next block, shifted, without the error handling of [`Iterator::next`].
+    fn next_block(&mut self) -> PcapResult<Option<Block<'a>>> {
+        // Buffer the blocks until the first packet gives the global offset
+        while self.global_offset.is_none() {
+            let Some(block) = self.inner.next().transpose()?
+            else {
+                self.global_offset = Some(self.shift.global_offset(None)?);
+                break;
+            };
+
+            let first_packet = match &block {
+                Block::EnhancedPacket(blk) => Some((blk.interface_id, blk.timestamp)),
+                Block::Packet(blk) => Some((blk.interface_id as u32, blk.timestamp)),
+                _ => None,
+            };
+
+            self.pending.push_back(block);
+
+            if first_packet.is_some() {
+                self.global_offset = Some(self.shift.global_offset(first_packet)?);
+            }
+        }
+
+        let block = match self.pending.pop_front() {
+            Some(block) => block,
+            None => match self.inner.next().transpose()? {
+                Some(block) => block,
+                None => return Ok(None),
+            },
+        };
+
+        self.shift_block(block).map(Some)
+    }
+
+    /// Shifts the timestamps of a block.
+    fn shift_block(&mut self, mut block: Block<'a>) -> PcapResult<Block<'a>> {
+        let global = self.global_offset.unwrap_or_default();
+
+        match &mut block {
+            Block::EnhancedPacket(blk) => {
+                blk.timestamp = (self.shift.interface_offset(blk.interface_id) + global).apply(blk.timestamp)?;
+            },
+            Block::Packet(blk) => {
+                blk.timestamp = (self.shift.interface_offset(blk.interface_id as u32) + global).apply(blk.timestamp)?;
+            },
+            Block::InterfaceStatistics(blk) => {
+                let offset = self.shift.interface_offset(blk.interface_id) + global;
+                blk.timestamp = offset.apply(blk.timestamp)?;
+
+                for opt in &mut blk.options {
+                    match opt {
+                        InterfaceStatisticsOption::IsbStartTime(ts) | InterfaceStatisticsOption::IsbEndTime(ts) => *ts = offset.apply(*ts)?,
+                        _ => {},
+                    }
+                }
+            },
+            Block::InterfaceDescription(blk) => {
+                // The interface id is the position of the interface in its section
+                let offset = self.shift.interface_offset(self.interface_count) + global;
+                self.interface_count += 1;
+
+                for opt in &mut blk.options {
+                    if let InterfaceDescriptionOption::IfTsOffset(ts_offset) = opt {
+                        *ts_offset = offset.apply_to_ts_offset(*ts_offset);
+                    }
+                }
+            },
+            Block::SectionHeader(_) => self.interface_count = 0,
+            _ => {},
+        }
+
+        Ok(block)
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgTimeShift<'a, I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_block() {
+            Ok(Some(block)) => Some(Ok(block)),
+            Ok(None) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// Shifts the timestamp of a [`PcapPacket`] by an offset.
+pub fn shift_pcap_packet<'a>(packet: PcapPacket<'a>, offset: TimeOffset) -> PcapResult<PcapPacket<'a>> {
+    let timestamp = offset.apply(packet.timestamp())?;
+    let orig_len = packet.orig_len();
+    PcapPacket::new(timestamp, orig_len, packet.into_data())
+}
@@ -0,0 +1,300 @@
+//! Truncation of the packets of a capture, like `editcap -s`.
+//!
+//! A [`Truncation`] caps the captured length of the packets to a new snaplen and/or to the length of their
+//! link, network and transport headers. The original length of the packets is preserved.
+//! [`PcapTruncate`] and [`PcapNgTruncate`] apply it to an [`Iterator`] of packets or blocks.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//!
+//! use pcap_file::pcap::{PcapReader, PcapWriter};
+//! use pcap_file::truncate::{PcapTruncate, Truncation};
+//!
+//! let file_in = File::open("test.pcap").expect("Error opening file");
+//! let packets = PcapReader::new(file_in).unwrap().into_packets();
+//! let header = packets.header();
+//!
+//! // Keep only the headers of the packets
+//! let truncation = Truncation { snaplen: None, headers_only: true };
+//!
+//! let file_out = File::create("out.pcap").expect("Error creating file");
+//! let mut pcap_writer = PcapWriter::with_header(file_out, truncation.pcap_header(header)).unwrap();
+//!
+//! for packet in PcapTruncate::new(packets, header.datalink, truncation) {
+//!     pcap_writer.write_packet(&packet.unwrap()).unwrap();
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use crate::dissect::{Ipv6Header, LinkHeader, NetworkProtocol};
+use crate::errors::PcapError;
+use crate::pcap::{PcapHeader, PcapPacket};
+use crate::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use crate::pcapng::Block;
+use crate::DataLink;
+
+
+/// Captured length reduction of the packets.
+///
+/// The captured length of a packet is the min of the snaplen and, in headers-only mode, of the length
+/// of its headers.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Truncation {
+    /// New maximum captured length of the packets
+    pub snaplen: Option<u32>,
+    /// Keeps only the link, network and transport headers of the packets.
+    ///
+    /// The supported link types are the ones of [`LinkHeader::parse`](crate::dissect::LinkHeader::parse),
+    /// the supported transports are TCP, UDP, SCTP, ICMP and ICMPv6.
+    /// The headers are cut after the last supported layer: the packets of an unsupported link type are cut to 0 byte,
+    /// the packets of an unsupported network protocol are cut after their link header...
+    pub headers_only: bool,
+}
+
+impl Truncation {
+    /// Creates a [`Truncation`] capping the captured length of the packets to `snaplen`.
+    pub fn snaplen(snaplen: u32) -> Self {
+        Truncation { snaplen: Some(snaplen), headers_only: false }
+    }
+
+    /// Returns the new snaplen of an interface, 0 meaning no limit.
+    pub fn interface_snaplen(&self, snaplen: u32) -> u32 {
+        match self.snaplen {
+            Some(new) if snaplen == 0 || new < snaplen => new,
+            _ => snaplen,
+        }
+    }
+
+    /// Returns the [`PcapHeader`] with its snaplen updated.
+    pub fn pcap_header(&self, header: PcapHeader) -> PcapHeader {
+        PcapHeader { snaplen: self.interface_snaplen(header.snaplen), ..header }
+    }
+
+    /// Returns the new captured length of a packet.
+    pub fn captured_len(&self, linktype: DataLink, data: &[u8]) -> usize {
+        let mut len = data.len();
+
+        if let Some(snaplen) = self.snaplen {
+            len = len.min(snaplen as usize);
+        }
+
+        if self.headers_only {
+            len = len.min(headers_len(linktype, data));
+        }
+
+        len
+    }
+}
+
+/// Truncates the data of a packet.
+fn truncate_data<'a>(data: Cow<'a, [u8]>, len: usize) -> Cow<'a, [u8]> {
+    match data {
+        Cow::Borrowed(data) => Cow::Borrowed(&data[..len]),
+        Cow::Owned(mut data) => {
+            data.truncate(len);
+            Cow::Owned(data)
+        },
+    }
+}
+
+
+/// [`Iterator`] adapter truncating [`PcapPacket`].
+///
+/// The writer of the packets should use the header returned by [`Truncation::pcap_header`].
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapTruncate<I> {
+    inner: I,
+    truncation: Truncation,
+    datalink: DataLink,
+}
+
+impl<I> PcapTruncate<I> {
+    /// Creates a new [`PcapTruncate`] over an iterator of packets of the given [`DataLink`].
+    pub fn new(inner: I, datalink: DataLink, truncation: Truncation) -> Self {
+        PcapTruncate { inner, truncation, datalink }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<PcapPacket<'a>, PcapError>>> Iterator for PcapTruncate<I> {
+    type Item = Result<PcapPacket<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = match self.inner.next()? {
+            Ok(packet) => packet,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let len = self.truncation.captured_len(self.datalink, packet.data());
+        let (timestamp, orig_len) = (packet.timestamp(), packet.orig_len());
+
+        Some(PcapPacket::new(timestamp, orig_len, truncate_data(packet.into_data(), len)))
+    }
+}
+
+
+/// [`Iterator`] adapter truncating the packet blocks and updating the snaplen of the interfaces.
+///
+/// In headers-only mode, a [`SimplePacketBlock`](crate::pcapng::blocks::simple_packet::SimplePacketBlock)
+/// can't hold a captured length smaller than the snaplen, it is converted to an [`EnhancedPacketBlock`]
+/// of the interface 0 with the timestamp of the previous packet.
+///
+/// The errors of the inner iterator are forwarded.
+pub struct PcapNgTruncate<I> {
+    inner: I,
+    truncation: Truncation,
+    /// Link type and original snaplen of the interfaces of the current section
+    interfaces: Vec<(DataLink, u32)>,
+    last_timestamp: Duration,
+}
+
+impl<I> PcapNgTruncate<I> {
+    /// Creates a new [`PcapNgTruncate`] over an iterator of blocks.
+    pub fn new(inner: I, truncation: Truncation) -> Self {
+        PcapNgTruncate { inner, truncation, interfaces: vec![], last_timestamp: Duration::ZERO }
+    }
+
+    /// Returns the link type of an interface.
+    fn linktype(&self, interface_id: u32) -> Result<DataLink, PcapError> {
+        self.interfaces
+            .get(interface_id as usize)
+            .map(|(linktype, _)| *linktype)
+            .ok_or(PcapError::InvalidInterfaceId(interface_id))
+    }
+
+    /// Truncates a block.
+    fn truncate_block<'a>(&mut self, block: Block<'a>) -> Result<Block<'a>, PcapError> {
+        let block = match block {
+            Block::SectionHeader(blk) => {
+                self.interfaces.clear();
+                Block::SectionHeader(blk)
+            },
+            Block::InterfaceDescription(mut blk) => {
+                self.interfaces.push((blk.linktype, blk.snaplen));
+                blk.snaplen = self.truncation.interface_snaplen(blk.snaplen);
+                Block::InterfaceDescription(blk)
+            },
+            Block::EnhancedPacket(mut blk) => {
+                self.last_timestamp = blk.timestamp;
+                let len = self.truncation.captured_len(self.linktype(blk.interface_id)?, &blk.data);
+                blk.data = truncate_data(blk.data, len);
+                Block::EnhancedPacket(blk)
+            },
+            Block::Packet(mut blk) => {
+                self.last_timestamp = blk.timestamp;
+                let len = self.truncation.captured_len(self.linktype(blk.interface_id as u32)?, &blk.data);
+                blk.data = truncate_data(blk.data, len);
+                blk.captured_len = len as u32;
+                Block::Packet(blk)
+            },
+            Block::SimplePacket(mut blk) => {
+                let (linktype, snaplen) = *self.interfaces.first().ok_or(PcapError::InvalidInterfaceId(0))?;
+
+                let captured_len = blk.packet_data(snaplen).len();
+                let data = truncate_data(blk.data, captured_len);
+                let len = self.truncation.captured_len(linktype, &data);
+
+                if self.truncation.headers_only {
+                    let epb = EnhancedPacketBlock {
+                        interface_id: 0,
+                        timestamp: self.last_timestamp,
+                        original_len: blk.original_len,
+                        data: truncate_data(data, len),
+                        options: vec![],
+                    };
+                    Block::EnhancedPacket(epb)
+                }
+                else {
+                    blk.data = truncate_data(data, len);
+                    Block::SimplePacket(blk)
+                }
+            },
+            block => block,
+        };
+
+        Ok(block)
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Block<'a>, PcapError>>> Iterator for PcapNgTruncate<I> {
+    type Item = Result<Block<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = match self.inner.next()? {
+            Ok(block) => block,
+            Err(e) => return Some(Err(e)),
+        };
+
+        Some(self.truncate_block(block))
+    }
+}
+
+
+/// Returns the length of the supported headers of a packet, see [`Truncation::headers_only`].
+fn headers_len(linktype: DataLink, data: &[u8]) -> usize {
+    let Some(link) = LinkHeader::parse(linktype, data)
+    else {
+        return 0;
+    };
+
+    let network = data.get(link.payload_offset..).unwrap_or_default();
+    let network_len = match link.protocol {
+        NetworkProtocol::Ipv4 => ipv4_headers_len(network),
+        NetworkProtocol::Ipv6 => ipv6_headers_len(network),
+        _ => 0,
+    };
+
+    (link.payload_offset + network_len).min(data.len())
+}
+
+/// Returns the length of an IPv4 header and of its transport header.
+fn ipv4_headers_len(data: &[u8]) -> usize {
+    let Some(&version_ihl) = data.first()
+    else {
+        return 0;
+    };
+
+    let len = (version_ihl & 0x0F) as usize * 4;
+    let (Some(&protocol), Some(flags_offset)) = (data.get(9), data.get(6..8))
+    else {
+        return len;
+    };
+
+    // Only the first fragment holds the transport header
+    if u16::from_be_bytes([flags_offset[0], flags_offset[1]]) & 0x1FFF != 0 {
+        return len;
+    }
+
+    len + transport_header_len(protocol, data.get(len..).unwrap_or_default())
+}
+
+/// Returns the length of an IPv6 header, of its extension headers and of its transport header.
+fn ipv6_headers_len(data: &[u8]) -> usize {
+    let Some(ip) = Ipv6Header::parse(data)
+    else {
+        return 40;
+    };
+
+    // Only the first fragment holds the transport header
+    if ip.fragment_offset() != 0 {
+        return ip.header_len();
+    }
+
+    ip.header_len() + transport_header_len(ip.protocol(), &data[ip.header_len()..])
+}
+
+/// Returns the length of a transport header, 0 if the protocol isn't supported.
+fn transport_header_len(protocol: u8, data: &[u8]) -> usize {
+    match protocol {
+        // TCP
+        6 => data.get(12).map_or(20, |offset| (offset >> 4) as usize * 4),
+        // UDP, ICMP and ICMPv6
+        17 | 1 | 58 => 8,
+        // SCTP common header
+        132 => 12,
+        _ => 0,
+    }
+}
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use pcap_file::anonymize::{Anonymizer, CryptoPan, PcapNgAnonymize};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::name_resolution::{Ipv4Record, NameResolutionBlock, Record};
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use pcap_file::DataLink;
+
+use crate::ones_complement_sum;
+
+
+/// Key of the sample trace of the Crypto-PAn reference implementation
+const KEY: [u8; 32] = [
+    21, 34, 23, 141, 51, 164, 207, 128, 19, 10, 91, 22, 73, 144, 125, 16, 216, 152, 143, 131, 121, 121, 101, 39, 98, 87, 76, 45, 42, 132, 34, 2,
+];
+
+#[test]
+fn cryptopan_reference_vectors() {
+    let cryptopan = CryptoPan::new(&KEY);
+
+    let vectors = [
+        ("128.11.68.132", "135.242.180.132"),
+        ("129.118.74.4", "134.136.186.123"),
+        ("130.132.252.244", "133.68.164.234"),
+        ("141.223.7.43", "141.167.8.160"),
+    ];
+
+    for (raw, anonymized) in vectors {
+        assert_eq!(cryptopan.anonymize_ipv4(raw.parse().unwrap()), anonymized.parse::<Ipv4Addr>().unwrap(), "{raw}");
+    }
+}
+
+#[test]
+fn cryptopan_prefix_preserving_ipv6() {
+    let cryptopan = CryptoPan::new(&KEY);
+
+    let a = cryptopan.anonymize_ipv6("2001:db8:1234::1".parse().unwrap()).octets();
+    let b = cryptopan.anonymize_ipv6("2001:db8:1234::8000:0:0:1".parse().unwrap()).octets();
+    let c = cryptopan.anonymize_ipv6("2001:db9::1".parse().unwrap()).octets();
+
+    // Same 64 bits prefix, different 65th bit
+    assert_eq!(a[..8], b[..8]);
+    assert_ne!(a[8] & 0x80, b[8] & 0x80);
+    // Same 31 bits prefix
+    assert_eq!(a[..3], c[..3]);
+    assert_eq!(a[3] & 0xFE, c[3] & 0xFE);
+    assert_ne!(a[3], c[3]);
+    assert_ne!(Ipv6Addr::from(a), "2001:db8:1234::1".parse::<Ipv6Addr>().unwrap());
+}
+
+/// Builds an Ethernet + IPv4 + UDP packet with valid checksums.
+fn ethernet_ipv4_udp(src: [u8; 4], dst: [u8; 4]) -> Vec<u8> {
+    let payload = b"hello world";
+    let udp_len = 8 + payload.len() as u16;
+
+    let mut ip = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, 17, 0, 0];
+    ip[2..4].copy_from_slice(&(20 + udp_len).to_be_bytes());
+    ip.extend(src);
+    ip.extend(dst);
+    let checksum = !ones_complement_sum(&[&ip]);
+    ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut udp = vec![0x12, 0x34, 0x00, 0x35];
+    udp.extend(udp_len.to_be_bytes());
+    udp.extend([0, 0]);
+    udp.extend(payload);
+    let pseudo = [&src[..], &dst[..], &[0, 17], &udp_len.to_be_bytes()].concat();
+    let checksum = !ones_complement_sum(&[&pseudo, &udp]);
+    udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut packet = vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0x08, 0x00];
+    packet.extend(ip);
+    packet.extend(udp);
+    packet
+}
+
+#[test]
+fn anonymize_packet_fixes_checksums() {
+    let cryptopan = CryptoPan::new(&KEY);
+    let mut anonymizer = Anonymizer::new(&KEY);
+
+    let mut packet = ethernet_ipv4_udp([128, 11, 68, 132], [129, 118, 74, 4]);
+    anonymizer.anonymize_packet(DataLink::ETHERNET, &mut packet);
+
+    assert_eq!(packet[26..30], cryptopan.anonymize_ipv4("128.11.68.132".parse().unwrap()).octets());
+    assert_eq!(packet[30..34], cryptopan.anonymize_ipv4("129.118.74.4".parse().unwrap()).octets());
+    assert_eq!(packet[0..6], cryptopan.anonymize_mac([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]));
+
+    let (ip, udp) = (&packet[14..34], &packet[34..]);
+    assert_eq!(ones_complement_sum(&[ip]), 0xFFFF);
+
+    let pseudo = [&ip[12..20], &[0, 17], &(udp.len() as u16).to_be_bytes()].concat();
+    assert_eq!(ones_complement_sum(&[&pseudo, udp]), 0xFFFF);
+}
+
+#[test]
+fn anonymize_pcapng_metadata() {
+    let cryptopan = CryptoPan::new(&KEY);
+
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    let mut interface = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0);
+    interface.options.push(InterfaceDescriptionOption::IfIpv4Addr(Cow::Owned(vec![128, 11, 68, 132, 255, 255, 255, 0])));
+    interface.options.push(InterfaceDescriptionOption::IfMacAddr(Cow::Owned(vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55])));
+    writer.write_pcapng_block(interface).unwrap();
+
+    let record = Ipv4Record { ip_addr: Cow::Owned(vec![129, 118, 74, 4]), names: vec![Cow::Borrowed("host.example.com")] };
+    writer.write_pcapng_block(NameResolutionBlock { records: vec![Record::Ipv4(record), Record::End], options: vec![] }).unwrap();
+
+    let data = ethernet_ipv4_udp([128, 11, 68, 132], [129, 118, 74, 4]);
+    let packet = EnhancedPacketBlock { interface_id: 0, timestamp: Duration::ZERO, original_len: data.len() as u32, data: Cow::Owned(data), options: vec![] };
+    writer.write_pcapng_block(packet).unwrap();
+    let pcapng = writer.into_inner();
+
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let blocks: Vec<Block> = PcapNgAnonymize::new(blocks, Anonymizer::new(&KEY)).map(Result::unwrap).collect();
+
+    let ip = cryptopan.anonymize_ipv4("128.11.68.132".parse().unwrap()).octets();
+    let mac = cryptopan.anonymize_mac([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    let options = &blocks[0].as_interface_description().unwrap().options;
+    assert_eq!(options[0], InterfaceDescriptionOption::IfIpv4Addr(Cow::Owned([&ip[..], &[255, 255, 255, 0]].concat())));
+    assert_eq!(options[1], InterfaceDescriptionOption::IfMacAddr(Cow::Owned(mac.to_vec())));
+
+    let Record::Ipv4(record) = &blocks[1].as_name_resolution().unwrap().records[0]
+    else {
+        panic!("The first record should be an IPv4 record");
+    };
+    assert_eq!(record.ip_addr[..], cryptopan.anonymize_ipv4("129.118.74.4".parse().unwrap()).octets());
+    assert_eq!(record.names, vec!["host.example.com"]);
+
+    // The packet addresses match the metadata ones
+    assert_eq!(blocks[2].as_enhanced_packet().unwrap().data[26..30], ip);
+}
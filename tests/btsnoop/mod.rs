@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use pcap_file::btsnoop::{BtsnoopDataLink, BtsnoopHeader, BtsnoopPacket, BtsnoopReader, BtsnoopWriter};
+use pcap_file::convert::btsnoop_to_pcapng;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::PcapNgReader;
+use pcap_file::{DataLink, PcapError};
+
+
+/// Writes a btsnoop with a sent command, a received event and received ACL data.
+fn capture(datalink: BtsnoopDataLink) -> Vec<u8> {
+    let header = BtsnoopHeader { version: 1, datalink };
+    let mut writer = BtsnoopWriter::with_header(Vec::new(), header).unwrap();
+
+    let packets = [
+        BtsnoopPacket::new(Duration::new(1700000000, 1000), 3, BtsnoopPacket::COMMAND_OR_EVENT, 0, &[0x03, 0x0C, 0x00][..]).unwrap(),
+        BtsnoopPacket::new(Duration::new(1700000000, 2000), 2, 0x03, 0, &[0x0E, 0x00][..]).unwrap(),
+        BtsnoopPacket::new(Duration::new(1700000001, 0), 8, BtsnoopPacket::RECEIVED, 4, &[0x01, 0x20][..]).unwrap(),
+    ];
+    for packet in &packets {
+        writer.write_packet(packet).unwrap();
+    }
+
+    writer.into_writer()
+}
+
+#[test]
+fn roundtrip() {
+    let data = capture(BtsnoopDataLink::H1);
+    assert_eq!(&data[..8], b"btsnoop\0");
+    assert_eq!(&data[12..16], &1001_u32.to_be_bytes());
+
+    // Microseconds since 0 AD
+    assert_eq!(&data[32..40], &(1700000000000001_i64 + 0x00DCDDB30F2F8000).to_be_bytes());
+
+    let reader = BtsnoopReader::new(&data[..]).unwrap();
+    assert_eq!(reader.header().datalink, BtsnoopDataLink::H1);
+
+    let packets = reader.into_packets().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 3);
+    assert_eq!(packets[0].timestamp(), Duration::new(1700000000, 1000));
+    assert!(!packets[0].is_received() && packets[0].is_command_or_event());
+    assert!(packets[1].is_received() && packets[1].is_command_or_event());
+    assert!(packets[2].is_received() && !packets[2].is_command_or_event());
+    assert_eq!(packets[2].orig_len(), 8);
+    assert_eq!(packets[2].cumulative_drops(), 4);
+    assert_eq!(packets[2].data(), &[0x01, 0x20]);
+
+    // Timestamps before the EPOCH are rejected
+    let mut invalid = data.clone();
+    invalid[32..40].copy_from_slice(&0_i64.to_be_bytes());
+    let mut reader = BtsnoopReader::new(&invalid[..]).unwrap();
+    assert!(matches!(reader.next_packet(), Some(Err(PcapError::InvalidField(_)))));
+}
+
+#[test]
+fn convert_to_pcapng() {
+    let read_packets = |data: &[u8]| {
+        let pcapng = btsnoop_to_pcapng(&mut BtsnoopReader::new(data).unwrap(), Vec::new()).unwrap().into_inner();
+
+        let mut pcapng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+        let mut packets = vec![];
+        while let Some(block) = pcapng_reader.next_block() {
+            let block = block.unwrap();
+            if let Some(interface) = block.as_interface_description() {
+                assert_eq!(interface.linktype, DataLink::BLUETOOTH_HCI_H4_WITH_PHDR);
+            }
+            if let Some(packet) = block.as_enhanced_packet() {
+                packets.push(packet.clone().into_owned());
+            }
+        }
+        packets
+    };
+
+    // The H4 packet type is deduced from the flags
+    let packets = read_packets(&capture(BtsnoopDataLink::H1));
+    assert_eq!(packets.len(), 3);
+    assert_eq!(&packets[0].data[..], &[0, 0, 0, 0, 0x01, 0x03, 0x0C, 0x00]);
+    assert_eq!(&packets[1].data[..], &[0, 0, 0, 1, 0x04, 0x0E, 0x00]);
+    assert_eq!(&packets[2].data[..], &[0, 0, 0, 1, 0x02, 0x01, 0x20]);
+    assert_eq!(packets[2].original_len, 13);
+    assert_eq!(packets[2].timestamp, Duration::new(1700000001, 0));
+    assert_eq!(packets[2].options, vec![EnhancedPacketOption::DropCount(4)]);
+
+    // The H4 packets already start with their type
+    let packets = read_packets(&capture(BtsnoopDataLink::H4));
+    assert_eq!(&packets[0].data[..], &[0, 0, 0, 0, 0x03, 0x0C, 0x00]);
+
+    let data = capture(BtsnoopDataLink::BCSP);
+    assert!(matches!(btsnoop_to_pcapng(&mut BtsnoopReader::new(&data[..]).unwrap(), Vec::new()), Err(PcapError::InvalidField(_))));
+}
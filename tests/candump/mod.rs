@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use pcap_file::candump::{CanDirection, CanFrame, CandumpEntry, CandumpReader};
+use pcap_file::convert::{candump_to_pcapng, pcapng_to_candump};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionOption;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::{DataLink, PcapError};
+
+
+const LOG: &str = "\
+(1436509052.249713) vcan0 44C#44C9A95B
+(1436509052.250000) can1 12345678##1DEADBEEF
+(1436509052.300000) vcan0 20000080#0000000000000000 R
+
+(1436509052.350000) vcan0 7FF#R4 T
+(1436509052.400000) can1 123#1122334455667788_C
+";
+
+#[test]
+fn parse_log() {
+    let entries = CandumpReader::new(LOG.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(entries.len(), 5);
+
+    assert_eq!(entries[0].timestamp, Duration::new(1436509052, 249713000));
+    assert_eq!(entries[0].interface, "vcan0");
+    assert_eq!(entries[0].frame, CanFrame { can_id: 0x44C, fd_flags: None, len8_dlc: 0, data: vec![0x44, 0xC9, 0xA9, 0x5B] });
+
+    let fd = &entries[1].frame;
+    assert!(fd.is_fd() && fd.is_extended());
+    assert_eq!(fd.id(), 0x12345678);
+    assert_eq!(fd.fd_flags, Some(CanFrame::BRS));
+
+    let error = &entries[2].frame;
+    assert!(error.is_error() && !error.is_extended());
+    assert_eq!(entries[2].direction, Some(CanDirection::Received));
+
+    let remote = &entries[3].frame;
+    assert!(remote.is_remote());
+    assert_eq!(remote.data.len(), 4);
+    assert_eq!(entries[3].direction, Some(CanDirection::Transmitted));
+
+    assert_eq!(entries[4].frame.len8_dlc, 12);
+
+    // Formatting gives back the lines
+    let lines: Vec<_> = LOG.lines().filter(|line| !line.is_empty()).collect();
+    for (entry, line) in entries.iter().zip(lines) {
+        assert_eq!(entry.to_string(), line);
+    }
+
+    for invalid in ["(1436509052.249713) vcan0 1234#00", "(1436509052.249713) vcan0 123#001", "(1436509052) vcan0 123#00", "(1.000000) vcan0 123##0112"] {
+        assert!(matches!(CandumpEntry::parse(invalid), Err(PcapError::InvalidField(_))), "{invalid}");
+    }
+}
+
+#[test]
+fn pcapng_roundtrip() {
+    let pcapng = candump_to_pcapng(LOG.as_bytes(), Vec::new()).unwrap().into_inner();
+
+    let mut interfaces = vec![];
+    let mut packets = vec![];
+    let mut pcapng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    while let Some(block) = pcapng_reader.next_block() {
+        match block.unwrap() {
+            Block::InterfaceDescription(interface) => interfaces.push(interface.into_owned()),
+            Block::EnhancedPacket(packet) => packets.push(packet.into_owned()),
+            _ => {},
+        }
+    }
+
+    // One interface per name
+    assert_eq!(interfaces.len(), 2);
+    assert!(interfaces.iter().all(|interface| interface.linktype == DataLink::CAN_SOCKETCAN));
+    assert_eq!(interfaces[1].options[0], InterfaceDescriptionOption::IfName("can1".into()));
+
+    assert_eq!(packets.len(), 5);
+    assert_eq!(&packets[0].data[..], &[0x00, 0x00, 0x04, 0x4C, 4, 0, 0, 0, 0x44, 0xC9, 0xA9, 0x5B]);
+    assert_eq!(&packets[1].data[..8], &[0x92, 0x34, 0x56, 0x78, 4, CanFrame::BRS | CanFrame::FDF, 0, 0]);
+    assert_eq!(packets[1].interface_id, 1);
+    assert_eq!(packets[2].options, vec![EnhancedPacketOption::Flags(0x01)]);
+
+    // And back to the same log
+    let mut pcapng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let log = pcapng_to_candump(&mut pcapng_reader, Vec::new()).unwrap();
+    assert_eq!(String::from_utf8(log).unwrap(), LOG.replace("\n\n", "\n"));
+}
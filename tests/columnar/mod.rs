@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::time::Duration;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{TimestampNanosecondType, UInt32Type, UInt64Type};
+use arrow_array::Array;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use pcap_file::columnar::{pcap_to_parquet, pcapng_to_parquet, ColumnarConfig, PacketBatchBuilder};
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::section_header::SectionHeaderBlock;
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::DataLink;
+
+use crate::{build_pcap, build_pcapng, enhanced_packet, temp_dir};
+
+
+/// Builds a pcapng with two sections, the second one with a Linux cooked interface.
+fn two_sections_pcapng() -> Vec<u8> {
+    let mut packet = enhanced_packet(0, Duration::new(10, 1000), &[1, 2, 3]);
+    packet.original_len = 100;
+    packet.options = vec![EnhancedPacketOption::Flags(2), EnhancedPacketOption::DropCount(5)];
+
+    build_pcapng([
+        Block::InterfaceDescription(InterfaceDescriptionBlock { linktype: DataLink::ETHERNET, snaplen: 0, options: vec![] }),
+        Block::EnhancedPacket(packet),
+        Block::SimplePacket(SimplePacketBlock { original_len: 5, data: Cow::Borrowed(&[4, 5, 6, 7, 8]) }),
+        Block::SectionHeader(SectionHeaderBlock::default()),
+        Block::InterfaceDescription(InterfaceDescriptionBlock { linktype: DataLink::LINUX_SLL, snaplen: 0, options: vec![] }),
+        Block::EnhancedPacket(enhanced_packet(0, Duration::new(11, 0), &[8])),
+    ])
+}
+
+#[test]
+fn record_batches() {
+    let pcapng = two_sections_pcapng();
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+
+    let config = ColumnarConfig { payload: true, ..Default::default() };
+    let mut builder = PacketBatchBuilder::new(&config);
+    builder.update_from_block(&Block::SectionHeader(reader.section().clone()));
+    while let Some(block) = reader.next_block() {
+        builder.update_from_block(&block.unwrap());
+    }
+    assert_eq!(builder.len(), 3);
+
+    let batch = builder.finish();
+    assert!(builder.is_empty());
+    assert_eq!(batch.schema(), builder.schema());
+    assert_eq!(batch.num_columns(), 8);
+
+    let timestamps = batch.column(0).as_primitive::<TimestampNanosecondType>();
+    assert_eq!(timestamps.values(), &[10_000_001_000, 10_000_001_000, 11_000_000_000]);
+    assert_eq!(batch.column(1).as_primitive::<UInt32Type>().values(), &[0, 0, 1]);
+
+    let linktypes = batch.column(2).as_primitive::<UInt32Type>();
+    assert_eq!(linktypes.values(), &[1, 1, 113]);
+    assert_eq!(batch.column(3).as_primitive::<UInt32Type>().values(), &[3, 5, 1]);
+    assert_eq!(batch.column(4).as_primitive::<UInt32Type>().values(), &[100, 5, 1]);
+
+    let flags = batch.column(5).as_primitive::<UInt32Type>();
+    assert_eq!((flags.value(0), flags.null_count()), (2, 2));
+    let drops = batch.column(6).as_primitive::<UInt64Type>();
+    assert_eq!((drops.value(0), drops.null_count()), (5, 2));
+
+    let payloads = batch.column(7).as_binary::<i32>();
+    assert_eq!(payloads.value(1), [4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn parquet_files() {
+    let dir = temp_dir("columnar");
+
+    // pcapng, in row groups of 2 packets
+    let pcapng = two_sections_pcapng();
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let path = dir.join("pcapng.parquet");
+    let config = ColumnarConfig { batch_size: 2, ..Default::default() };
+    pcapng_to_parquet(&mut reader, File::create(&path).unwrap(), &config).unwrap();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(builder.metadata().num_row_groups(), 2);
+    assert_eq!(builder.schema().fields().len(), 7);
+    let rows: usize = builder.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum();
+    assert_eq!(rows, 3);
+
+    // pcap
+    let pcap = build_pcap(DataLink::RAW, (0..5).map(|i| (Duration::from_secs(i), [0x45; 20])));
+
+    let path = dir.join("pcap.parquet");
+    pcap_to_parquet(&mut PcapReader::new(&pcap[..]).unwrap(), File::create(&path).unwrap(), &ColumnarConfig::default()).unwrap();
+
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+    let batch = reader.next().unwrap().unwrap();
+    assert_eq!(batch.num_rows(), 5);
+    assert_eq!(batch.column(2).as_primitive::<UInt32Type>().values(), &[101; 5]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
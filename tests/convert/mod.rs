@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::convert::{pcap_to_pcapng, pcapng_to_pcap, pcapng_to_pcap_split};
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapReader, PcapWriter};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+use pcap_file::{DataLink, Endianness, PcapError, TsResolution};
+
+
+#[test]
+fn pcap_to_pcapng_to_pcap_round_trip() {
+    let header = PcapHeader { ts_resolution: TsResolution::NanoSecond, endianness: Endianness::Little, datalink: DataLink::RAW, ..Default::default() };
+    let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+    for i in 0..5_u32 {
+        let data = vec![i as u8; 20];
+        writer.write_packet(&PcapPacket::new(Duration::new(1000 + i as u64, 123_456_789), 30, data).unwrap()).unwrap();
+    }
+    let pcap = writer.into_writer();
+
+    let mut reader = PcapReader::new(&pcap[..]).unwrap();
+    let pcapng = pcap_to_pcapng(&mut reader, Vec::new()).unwrap().into_inner();
+
+    let ng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    assert_eq!(ng_reader.section().endianness, Endianness::Little);
+
+    let mut ng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let interface = ng_reader.next_block().unwrap().unwrap().into_interface_description().unwrap();
+    assert_eq!(interface.linktype, DataLink::RAW);
+    assert_eq!(interface.options, vec![InterfaceDescriptionOption::IfTsResol(9)]);
+
+    let mut ng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let out = pcapng_to_pcap(&mut ng_reader, Vec::new()).unwrap().into_writer();
+
+    assert_eq!(out, pcap);
+}
+
+#[test]
+fn pcapng_to_pcap_linktype_mismatch() {
+    let pcapng = build_two_interfaces();
+
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let res = pcapng_to_pcap(&mut reader, Vec::new());
+
+    assert!(matches!(res, Err(PcapError::DataLinkMismatch(DataLink::ETHERNET, DataLink::RAW))));
+}
+
+#[test]
+fn pcapng_to_pcap_split_by_linktype() {
+    let pcapng = build_two_interfaces();
+
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let mut headers = vec![];
+    let writers = pcapng_to_pcap_split(&mut reader, |header| {
+        headers.push(*header);
+        Ok(Vec::new())
+    })
+    .unwrap();
+
+    assert_eq!(headers.iter().map(|h| h.datalink).collect::<Vec<_>>(), vec![DataLink::ETHERNET, DataLink::RAW]);
+    assert_eq!(headers[0].snaplen, 262144);
+
+    let ethernet = writers.into_iter().next().unwrap().into_writer();
+    let packets: Vec<Vec<u8>> = PcapReader::new(&ethernet[..]).unwrap().into_packets().map(|p| p.unwrap().data().to_vec()).collect();
+
+    // The simple packet block is truncated to its original length, without the padding
+    assert_eq!(packets, vec![vec![0; 4], vec![2; 5]]);
+}
+
+#[test]
+fn pcapng_to_pcap_merged_interfaces() {
+    // Two Ethernet interfaces with different snaplens and timestamp resolutions
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 64)).unwrap();
+    let mut nano = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 1500);
+    nano.options.push(InterfaceDescriptionOption::IfTsResol(9));
+    writer.write_pcapng_block(nano).unwrap();
+
+    for (interface_id, len) in [(0, 64), (1, 200)] {
+        let packet = EnhancedPacketBlock {
+            interface_id,
+            timestamp: Duration::new(10, 123_456_789),
+            original_len: len,
+            data: Cow::Owned(vec![0; len as usize]),
+            options: vec![],
+        };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+    let pcapng = writer.into_inner();
+
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let pcap = pcapng_to_pcap(&mut reader, Vec::new()).unwrap().into_writer();
+
+    let mut reader = PcapReader::new(&pcap[..]).unwrap();
+    assert_eq!((reader.header().snaplen, reader.header().ts_resolution), (1500, TsResolution::NanoSecond));
+
+    let packet = reader.next_packet().unwrap().unwrap();
+    assert_eq!(packet.timestamp(), Duration::new(10, 123_456_000));
+    let packet = reader.next_packet().unwrap().unwrap();
+    assert_eq!((packet.timestamp(), packet.data().len()), (Duration::new(10, 123_456_789), 200));
+}
+
+/// Builds a pcapng with an ETHERNET and a RAW interface.
+fn build_two_interfaces() -> Vec<u8> {
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::RAW, 0)).unwrap();
+
+    for (interface_id, byte) in [(0, 0_u8), (1, 1)] {
+        let packet = EnhancedPacketBlock {
+            interface_id,
+            timestamp: Duration::from_secs(10),
+            original_len: 4,
+            data: Cow::Owned(vec![byte; 4]),
+            options: vec![],
+        };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+
+    writer.write_pcapng_block(SimplePacketBlock { original_len: 5, data: Cow::Owned(vec![2; 5]) }).unwrap();
+
+    writer.into_inner()
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::dedup::{DedupConfig, DedupStats, DedupWindow, DuplicateAnnotation, PcapDedup, PcapNgDedup};
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::opt_common::CommonOption;
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::DataLink;
+
+use crate::{build_pcap, build_pcapng, enhanced_packet};
+
+
+#[test]
+fn pcap_dedup_count_window() {
+    let packets = [(0, b"aaaa"), (1, b"bbbb"), (2, b"cccc"), (3, b"aaaa"), (4, b"cccc"), (5, b"aaaa")];
+    let pcap = build_pcap(DataLink::ETHERNET, packets.map(|(ts, data)| (Duration::from_millis(ts), data)));
+    let packets = PcapReader::new(&pcap[..]).unwrap().into_packets();
+
+    // "aaaa" at 3 is 3 packets after the first one, out of a window of 2
+    let mut dedup = PcapDedup::new(packets, DedupConfig { window: DedupWindow::Count(2), ..Default::default() });
+    let kept: Vec<u64> = dedup.by_ref().map(|p| p.unwrap().timestamp().as_millis() as u64).collect();
+
+    assert_eq!(kept, vec![0, 1, 2, 3]);
+    assert_eq!(dedup.stats(), DedupStats { packets: 6, duplicates: 2, duplicate_bytes: 8 });
+}
+
+#[test]
+fn pcap_dedup_time_window_ignored_ranges() {
+    let packets = [(0, b"a1aa"), (5, b"a2aa"), (20, b"a3aa"), (21, b"a3ab")];
+    let pcap = build_pcap(DataLink::ETHERNET, packets.map(|(ts, data)| (Duration::from_millis(ts), data)));
+    let packets = PcapReader::new(&pcap[..]).unwrap().into_packets();
+
+    let config = DedupConfig { window: DedupWindow::Time(Duration::from_millis(10)), ignored_ranges: vec![1..2, 10..12], ..Default::default() };
+    let kept: Vec<u64> = PcapDedup::new(packets, config).map(|p| p.unwrap().timestamp().as_millis() as u64).collect();
+
+    // The packet at 20 is too far from the one at 5, the one at 21 differs outside of the ignored range
+    assert_eq!(kept, vec![0, 20, 21]);
+}
+
+#[test]
+fn pcapng_dedup_annotation() {
+    let mut blocks = vec![Block::InterfaceDescription(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0))];
+    for ts in 0..3 {
+        let mut packet = enhanced_packet(0, Duration::from_millis(ts), b"aaaa");
+        if ts == 2 {
+            packet.options.push(EnhancedPacketOption::Flags(1));
+        }
+        blocks.push(Block::EnhancedPacket(packet));
+    }
+    let pcapng = build_pcapng(blocks);
+
+    let config = DedupConfig { annotation: Some(DuplicateAnnotation::Comment), ..Default::default() };
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let packets: Vec<_> = PcapNgDedup::new(blocks, config).skip(1).map(|b| b.unwrap().into_enhanced_packet().unwrap()).collect();
+
+    assert_eq!(packets.len(), 3);
+    assert!(packets[0].options.is_empty());
+    assert_eq!(packets[1].options, vec![EnhancedPacketOption::Common(CommonOption::Comment("Duplicate of packet 0".into()))]);
+
+    let config = DedupConfig { annotation: Some(DuplicateAnnotation::Flags(0x100)), ..Default::default() };
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let packets: Vec<_> = PcapNgDedup::new(blocks, config).skip(1).map(|b| b.unwrap().into_enhanced_packet().unwrap()).collect();
+
+    assert_eq!(packets[1].options, vec![EnhancedPacketOption::Flags(0x100)]);
+    assert_eq!(packets[2].options, vec![EnhancedPacketOption::Flags(0x101)]);
+}
+
+#[test]
+fn pcapng_dedup_simple_packets() {
+    // The bodies differ only by their padding
+    let pcapng = build_pcapng([
+        Block::InterfaceDescription(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)),
+        Block::SimplePacket(SimplePacketBlock { original_len: 3, data: Cow::Borrowed(&[1, 2, 3, 9]) }),
+        Block::SimplePacket(SimplePacketBlock { original_len: 3, data: Cow::Borrowed(&[1, 2, 3]) }),
+    ]);
+
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let mut dedup = PcapNgDedup::new(blocks, DedupConfig::default());
+    assert_eq!(dedup.by_ref().count(), 2);
+    assert_eq!(dedup.stats().duplicates, 1);
+}
@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::defrag::{DefragConfig, Defragmenter, PacketRef, PcapDefrag, PcapNgDefrag};
+use pcap_file::dissect::{IpHeader, Ipv4Header};
+use pcap_file::pcap::PcapPacket;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+use pcap_file::DataLink;
+
+
+/// Builds an IPv4 header with a valid checksum.
+fn ipv4_header(total_len: u16, flags_and_offset: u16) -> Vec<u8> {
+    let mut header = vec![0x45, 0, 0, 0, 0xAB, 0xCD, 0, 0, 64, 17, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2];
+    header[2..4].copy_from_slice(&total_len.to_be_bytes());
+    header[6..8].copy_from_slice(&flags_and_offset.to_be_bytes());
+
+    let mut sum = header.chunks(2).map(|w| u16::from_be_bytes([w[0], w[1]]) as u32).sum::<u32>();
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    header[10..12].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+    header
+}
+
+/// Splits an IPv4 payload into fragments of 16 bytes.
+fn ipv4_fragments(payload: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = payload.chunks(16).collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let more_fragments = if i + 1 < chunks.len() { 0x2000 } else { 0 };
+            [ipv4_header(20 + chunk.len() as u16, more_fragments | (i as u16 * 2)), chunk.to_vec()].concat()
+        })
+        .collect()
+}
+
+#[test]
+fn pcap_ipv4_reassembly() {
+    let payload: Vec<u8> = (0..40).collect();
+    let mut fragments = ipv4_fragments(&payload);
+    fragments.swap(0, 2);
+
+    let mut packets = vec![];
+    for (secs, data) in fragments.into_iter().enumerate() {
+        packets.push(Ok(PcapPacket::new(Duration::from_secs(secs as u64), data.len() as u32, data).unwrap()));
+    }
+    // An unfragmented datagram is yielded as is
+    let whole = [ipv4_header(24, 0x4000), vec![1, 2, 3, 4]].concat();
+    packets.push(Ok(PcapPacket::new(Duration::from_secs(5), 24, whole.clone()).unwrap()));
+
+    let mut defrag = PcapDefrag::new(packets.into_iter(), DataLink::IPV4, DefragConfig::default());
+
+    let datagram = defrag.next().unwrap().unwrap();
+    assert_eq!(datagram.data, [ipv4_header(60, 0), payload].concat());
+    assert!(Ipv4Header::parse(&datagram.data).unwrap().checksum_valid());
+    assert!(datagram.is_reassembled());
+    assert_eq!(datagram.packets.iter().map(|p| p.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(datagram.timestamp(), Duration::from_secs(2));
+
+    let datagram = defrag.next().unwrap().unwrap();
+    assert_eq!((datagram.data, datagram.packets.len()), (whole, 1));
+    assert!(defrag.next().is_none());
+    assert_eq!(defrag.defragmenter().stats().reassembled, 1);
+}
+
+#[test]
+fn pcapng_ipv6_reassembly() {
+    let (src, dst) = ([0x20, 0x01, 0x0d, 0xb8].repeat(4), [0xfe, 0x80, 0, 0].repeat(4));
+    let payload: Vec<u8> = (0..24).collect();
+
+    // Fixed header, then hop-by-hop options, then the fragment header
+    let fragment = |offset: u16, more: u16, data: &[u8]| {
+        let mut packet = vec![0x60, 0, 0, 0, 0, 0, 0, 64];
+        packet[4..6].copy_from_slice(&(16 + data.len() as u16).to_be_bytes());
+        packet.extend(&src);
+        packet.extend(&dst);
+        packet.extend([44, 0, 0, 0, 0, 0, 0, 0]);
+        packet.extend([17, 0]);
+        packet.extend((offset | more).to_be_bytes());
+        packet.extend(7_u32.to_be_bytes());
+        packet.extend(data);
+        [vec![0; 12], vec![0x86, 0xDD], packet].concat()
+    };
+
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)).unwrap();
+    for (secs, data) in [(1, fragment(16, 0, &payload[16..])), (2, fragment(0, 1, &payload[..16]))] {
+        let packet = EnhancedPacketBlock { interface_id: 0, timestamp: Duration::from_secs(secs), original_len: data.len() as u32, data: Cow::Owned(data), options: vec![] };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+    let pcapng = writer.into_inner();
+
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let datagrams: Vec<_> = PcapNgDefrag::new(blocks, DefragConfig::default()).map(Result::unwrap).collect();
+    assert_eq!(datagrams.len(), 1);
+
+    let Some(IpHeader::V6(ip)) = IpHeader::parse(&datagrams[0].data) else { panic!("The datagram should be IPv6") };
+    assert_eq!((ip.payload_len(), ip.header_len(), ip.protocol(), ip.is_fragment()), (32, 48, 17, false));
+    assert_eq!(ip.payload(), &payload[..]);
+}
+
+#[test]
+fn timeout_and_memory_limit() {
+    let payload: Vec<u8> = (0..40).collect();
+    let fragments = ipv4_fragments(&payload);
+    let packet = |index, secs| PacketRef { index, timestamp: Duration::from_secs(secs), interface: 0 };
+
+    let config = DefragConfig { timeout: Duration::from_secs(10), ..Default::default() };
+    let mut defrag = Defragmenter::new(config);
+    assert!(defrag.add_packet(DataLink::IPV4, &fragments[0], packet(0, 0)).is_none());
+    assert!(defrag.add_packet(DataLink::IPV4, &fragments[1], packet(1, 5)).is_none());
+    assert_eq!((defrag.pending(), defrag.memory()), (1, 20 + 32));
+
+    // The datagram expired before its last fragment
+    assert!(defrag.add_packet(DataLink::IPV4, &fragments[2], packet(2, 11)).is_none());
+    assert_eq!((defrag.stats().expired, defrag.pending()), (1, 1));
+
+    let config = DefragConfig { max_memory: 40, ..Default::default() };
+    let mut defrag = Defragmenter::new(config);
+    assert!(defrag.add_packet(DataLink::IPV4, &fragments[0], packet(0, 0)).is_none());
+    assert!(defrag.add_packet(DataLink::IPV4, &fragments[1], packet(1, 0)).is_none());
+    assert_eq!((defrag.stats().evicted, defrag.pending(), defrag.memory()), (1, 0, 0));
+}
+
+#[test]
+fn inconsistent_fragments() {
+    let packet = |index| PacketRef { index, timestamp: Duration::ZERO, interface: 0 };
+    let fragments = [
+        [ipv4_header(20 + 32, 0x2000), vec![0; 32]].concat(),
+        [ipv4_header(20 + 8, 0x2000 | 3), vec![1; 8]].concat(),
+        // The last fragment ends before the data already received
+        [ipv4_header(20 + 8, 1), vec![2; 8]].concat(),
+    ];
+
+    let mut defrag = Defragmenter::new(DefragConfig::default());
+    for (index, fragment) in fragments.iter().enumerate() {
+        assert!(defrag.add_packet(DataLink::IPV4, fragment, packet(index as u64)).is_none());
+    }
+    assert_eq!((defrag.stats().invalid, defrag.pending(), defrag.memory()), (1, 0, 0));
+}
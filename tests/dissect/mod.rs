@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::dissect::{IpHeader, LinkHeader, NetworkProtocol, PacketHeaders, TcpHeader, TransportHeader};
+use pcap_file::pcap::PcapPacket;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::Block;
+use pcap_file::DataLink;
+
+use crate::ones_complement_sum;
+
+
+#[test]
+fn parse_link_headers() {
+    let header = |protocol, payload_offset| Some(LinkHeader { protocol, payload_offset });
+
+    // Ethernet with QinQ tags
+    let mut ethernet = vec![0; 12];
+    ethernet.extend([0x88, 0xA8, 0, 1, 0x81, 0x00, 0, 2, 0x86, 0xDD]);
+    assert_eq!(LinkHeader::parse(DataLink::ETHERNET, &ethernet), header(NetworkProtocol::Ipv6, 22));
+    assert_eq!(LinkHeader::parse(DataLink::ETHERNET, &ethernet[..16]), None);
+
+    let mut sll = vec![0; 14];
+    sll.extend([0x08, 0x06]);
+    assert_eq!(LinkHeader::parse(DataLink::LINUX_SLL, &sll), header(NetworkProtocol::Arp, 16));
+
+    let mut sll2 = vec![0x88, 0xCC];
+    sll2.extend([0; 18]);
+    assert_eq!(LinkHeader::parse(DataLink::LINUX_SLL2, &sll2), header(NetworkProtocol::Other(0x88CC), 20));
+
+    // NULL is in host byte order, LOOP in network byte order
+    assert_eq!(LinkHeader::parse(DataLink::NULL, &[30, 0, 0, 0]), header(NetworkProtocol::Ipv6, 4));
+    assert_eq!(LinkHeader::parse(DataLink::NULL, &[0, 0, 0, 2]), header(NetworkProtocol::Ipv4, 4));
+    assert_eq!(LinkHeader::parse(DataLink::LOOP, &[0, 0, 0, 2]), header(NetworkProtocol::Ipv4, 4));
+    assert_eq!(LinkHeader::parse(DataLink::LOOP, &[0, 0, 0, 7]), header(NetworkProtocol::Other(0), 4));
+
+    assert_eq!(LinkHeader::parse(DataLink::RAW, &[0x45]), header(NetworkProtocol::Ipv4, 0));
+    assert_eq!(LinkHeader::parse(DataLink::IPV6, &[]), header(NetworkProtocol::Ipv6, 0));
+    assert_eq!(LinkHeader::parse(DataLink::BLUETOOTH_HCI_H4, &[0; 20]), None);
+
+    assert_eq!(NetworkProtocol::from_ethertype(0x0800), NetworkProtocol::Ipv4);
+    assert_eq!(NetworkProtocol::Ipv6.ethertype(), 0x86DD);
+}
+
+#[test]
+fn link_header_from_block() {
+    let interfaces = [InterfaceDescriptionBlock::new(DataLink::RAW, 0), InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)];
+
+    let epb = |interface_id, data: &'static [u8]| {
+        Block::EnhancedPacket(EnhancedPacketBlock { interface_id, timestamp: Duration::ZERO, original_len: 0, data: Cow::Borrowed(data), options: vec![] })
+    };
+
+    let ethernet: &'static [u8] = &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x00];
+    assert_eq!(LinkHeader::from_block(&epb(1, ethernet), &interfaces), Some(LinkHeader { protocol: NetworkProtocol::Ipv4, payload_offset: 14 }));
+    assert_eq!(LinkHeader::from_block(&epb(0, &[0x60]), &interfaces), Some(LinkHeader { protocol: NetworkProtocol::Ipv6, payload_offset: 0 }));
+    assert_eq!(LinkHeader::from_block(&epb(2, &[0x60]), &interfaces), None);
+
+    let spb = Block::SimplePacket(SimplePacketBlock { original_len: 1, data: Cow::Borrowed(&[0x45]) });
+    assert_eq!(LinkHeader::from_block(&spb, &interfaces), Some(LinkHeader { protocol: NetworkProtocol::Ipv4, payload_offset: 0 }));
+    assert_eq!(LinkHeader::from_block(&Block::InterfaceDescription(interfaces[0].clone()), &interfaces), None);
+}
+
+/// Builds an Ethernet + IPv4 + TCP packet with valid checksums.
+fn ethernet_ipv4_tcp(flags_and_offset: u16) -> Vec<u8> {
+    let payload = b"GET / HTTP/1.1";
+    let (src, dst) = ([10, 0, 0, 1], [10, 0, 0, 2]);
+
+    let mut tcp = vec![0xC0, 0x01, 0x00, 0x50, 0, 0, 0, 1, 0, 0, 0, 2, 0x50, 0x18, 0xFF, 0xFF, 0, 0, 0, 0];
+    tcp.extend(payload);
+    let pseudo = [&src[..], &dst[..], &[0, 6], &(tcp.len() as u16).to_be_bytes()].concat();
+    let checksum = !ones_complement_sum(&[&pseudo, &tcp]);
+    tcp[16..18].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut ip = vec![0x45, 0, 0, 0, 0x12, 0x34, 0, 0, 64, 6, 0, 0];
+    ip[2..4].copy_from_slice(&(20 + tcp.len() as u16).to_be_bytes());
+    ip[6..8].copy_from_slice(&flags_and_offset.to_be_bytes());
+    ip.extend(src);
+    ip.extend(dst);
+    let checksum = !ones_complement_sum(&[&ip]);
+    ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut packet = vec![0; 12];
+    packet.extend([0x08, 0x00]);
+    packet.extend(ip);
+    packet.extend(tcp);
+    packet
+}
+
+#[test]
+fn ipv4_tcp_headers() {
+    let data = ethernet_ipv4_tcp(0x4000);
+    let packet = PcapPacket::new(Duration::ZERO, data.len() as u32, &data[..]).unwrap();
+    let headers = packet.headers(DataLink::ETHERNET).unwrap();
+
+    let Some(IpHeader::V4(ip)) = headers.ip else { panic!("The packet should be IPv4") };
+    assert_eq!((ip.src(), ip.dst(), ip.ttl(), ip.identification()), ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap(), 64, 0x1234));
+    assert!(ip.dont_fragment() && !ip.is_fragment() && ip.checksum_valid());
+
+    let Some(TransportHeader::Tcp(tcp)) = headers.transport else { panic!("The packet should be TCP") };
+    assert_eq!((tcp.src_port(), tcp.dst_port(), tcp.seq(), tcp.ack()), (0xC001, 80, 1, 2));
+    assert!(tcp.has_flags(TcpHeader::PSH | TcpHeader::ACK) && !tcp.has_flags(TcpHeader::SYN));
+    assert_eq!(tcp.payload(), b"GET / HTTP/1.1");
+    assert_eq!(tcp.checksum_valid(&headers.ip.unwrap()), Some(true));
+
+    // The checksum of a truncated packet can't be checked
+    let truncated = PacketHeaders::parse(DataLink::ETHERNET, &data[..60]).unwrap();
+    let transport = truncated.transport.unwrap();
+    assert_eq!(transport.payload(), b"GET / ");
+    assert_eq!(transport.checksum_valid(&truncated.ip.unwrap()), None);
+
+    // A corrupted payload is detected
+    let mut corrupted = data.clone();
+    *corrupted.last_mut().unwrap() ^= 0xFF;
+    let corrupted = PacketHeaders::parse(DataLink::ETHERNET, &corrupted).unwrap();
+    assert_eq!(corrupted.transport.unwrap().checksum_valid(&corrupted.ip.unwrap()), Some(false));
+
+    // The next fragments have no transport header
+    let fragment = ethernet_ipv4_tcp(0x2001);
+    let fragment = PacketHeaders::parse(DataLink::ETHERNET, &fragment).unwrap();
+    assert_eq!(fragment.ip.unwrap().fragment_offset(), 8);
+    assert!(fragment.transport.is_none());
+}
+
+#[test]
+fn ipv6_udp_headers() {
+    let (src, dst) = ([0x20, 0x01, 0x0d, 0xb8].repeat(4), [0xfe, 0x80, 0, 0].repeat(4));
+
+    let mut udp = vec![0x00, 0x35, 0xD0, 0x00, 0, 12, 0, 0, 1, 2, 3, 4];
+    let pseudo = [&src[..], &dst[..], &[0, 0, 0, 12, 0, 0, 0, 17]].concat();
+    let checksum = !ones_complement_sum(&[&pseudo, &udp]);
+    udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+
+    // Fixed header, then destination options
+    let mut data = vec![0x60, 0, 0, 0, 0, 20, 60, 255];
+    data.extend(&src);
+    data.extend(&dst);
+    data.extend([17, 0, 1, 4, 0, 0, 0, 0]);
+    data.extend(&udp);
+
+    let epb = EnhancedPacketBlock { interface_id: 0, timestamp: Duration::ZERO, original_len: 0, data: Cow::Borrowed(&data), options: vec![] };
+    let headers = epb.headers(DataLink::RAW).unwrap();
+
+    let Some(IpHeader::V6(ip)) = headers.ip else { panic!("The packet should be IPv6") };
+    assert_eq!((ip.header_len(), ip.protocol(), ip.hop_limit()), (48, 17, 255));
+    assert_eq!(ip.src(), "2001:db8:2001:db8:2001:db8:2001:db8".parse::<std::net::Ipv6Addr>().unwrap());
+
+    let Some(TransportHeader::Udp(udp)) = headers.transport else { panic!("The packet should be UDP") };
+    assert_eq!((udp.src_port(), udp.dst_port(), udp.payload()), (53, 0xD000, &[1, 2, 3, 4][..]));
+    assert_eq!(udp.checksum_valid(&headers.ip.unwrap()), Some(true));
+}
@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::dump::{BlockDump, HexDump, PcapPacketDump};
+use pcap_file::pcap::PcapPacket;
+use pcap_file::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::opt_common::CommonOption;
+use pcap_file::pcapng::Block;
+use pcap_file::text2pcap::HexDumpReader;
+use pcap_file::DataLink;
+
+
+#[test]
+fn hex_dump() {
+    let data: Vec<u8> = (0x20..0x32).collect();
+    let expected = "\
+0000  20 21 22 23 24 25 26 27 28 29 2a 2b 2c 2d 2e 2f    !\"#$%&'()*+,-./
+0010  30 31                                             01";
+    assert_eq!(HexDump::new(&data).to_string(), expected);
+    assert_eq!(HexDump::new(&[]).to_string(), "");
+
+    let packet = PcapPacket::new(Duration::new(1, 500), 100, &data[..]).unwrap();
+    assert_eq!(PcapPacketDump::summary(&packet).to_string(), "Packet: timestamp 1.000000500, captured 18, original 100");
+
+    // The dump of a packet reads back into the packet
+    let dump = PcapPacketDump::new(&packet).to_string();
+    let packets = HexDumpReader::new(dump.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].data, data);
+}
+
+#[test]
+fn block_dump() {
+    let interface = Block::InterfaceDescription(InterfaceDescriptionBlock {
+        linktype: DataLink::ETHERNET,
+        snaplen: 65535,
+        options: vec![
+            InterfaceDescriptionOption::IfName(Cow::Borrowed("eth0")),
+            InterfaceDescriptionOption::IfIpv4Addr(Cow::Borrowed(&[192, 168, 1, 1, 255, 255, 255, 0])),
+            InterfaceDescriptionOption::IfMacAddr(Cow::Borrowed(&[0x0A, 0x02, 0x02, 0x02, 0x02, 0x01])),
+            InterfaceDescriptionOption::IfTsResol(9),
+        ],
+    });
+    let expected = "\
+Interface Description Block: linktype ETHERNET, snaplen 65535
+  if_name: \"eth0\"
+  if_IPv4addr: 192.168.1.1/255.255.255.0
+  if_MACaddr: 0a:02:02:02:02:01
+  if_tsresol: 9";
+    assert_eq!(BlockDump::new(&interface).to_string(), expected);
+
+    let packet = Block::EnhancedPacket(EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: Duration::new(1704187433, 103553000),
+        original_len: 60,
+        data: Cow::Borrowed(&[0xDE, 0xAD, 0xBE, 0xEF]),
+        options: vec![
+            EnhancedPacketOption::Flags(1),
+            EnhancedPacketOption::DropCount(3),
+            EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("retransmission"))),
+        ],
+    });
+    let summary = "\
+Enhanced Packet Block: interface 0, timestamp 1704187433.103553000, captured 4, original 60
+  epb_flags: 0x00000001
+  epb_dropcount: 3
+  opt_comment: \"retransmission\"";
+    assert_eq!(BlockDump::summary(&packet).to_string(), summary);
+    assert_eq!(BlockDump::new(&packet).to_string(), format!("{summary}\n0000  de ad be ef{:39}....", ""));
+}
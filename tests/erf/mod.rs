@@ -0,0 +1,116 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::convert::erf_to_pcapng;
+use pcap_file::erf::{ErfExtensionHeader, ErfReader, ErfRecord, ErfTimestamp, ErfType, ErfTypeHeader};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionOption;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::{DataLink, PcapError};
+
+
+/// Builds a record with a fixed timestamp of 1.5s.
+fn record(record_type: ErfType, flags: u8, type_header: ErfTypeHeader, wire_len: u16, data: &[u8]) -> ErfRecord<'static> {
+    ErfRecord {
+        timestamp: ErfTimestamp((1 << 32) | (1 << 31)),
+        record_type,
+        flags,
+        loss_counter: 0,
+        wire_len,
+        extension_headers: vec![],
+        type_header,
+        data: Cow::Owned(data.to_vec()),
+    }
+}
+
+/// Writes an ERF capture with an Ethernet record on port A, a multichannel HDLC record on port B,
+/// an IPv4 record from another host, a padding record and an ATM record.
+fn capture() -> Vec<u8> {
+    let mut eth = record(ErfType::ETH, 0x04, ErfTypeHeader::Eth { offset: 0, pad: 0 }, 6, &[1, 2, 3, 4, 5, 6, 0, 0, 0, 0]);
+    eth.loss_counter = 3;
+
+    let mc_hdlc = record(ErfType::MC_HDLC, 0x01, ErfTypeHeader::MultiChannel(0x0000_0123), 4, &[0x0F, 0x00, 0x08, 0x00]);
+
+    let mut ipv4 = record(ErfType::IPV4, 0x00, ErfTypeHeader::None, 4, &[0x45, 0, 0, 4]);
+    ipv4.extension_headers.push(ErfExtensionHeader(0x0602_0000_0000_ABCD));
+
+    let pad = record(ErfType::PAD, 0x00, ErfTypeHeader::None, 0, &[0; 8]);
+    let atm = record(ErfType::ATM, 0x00, ErfTypeHeader::None, 4, &[9, 9, 9, 9]);
+
+    let mut data = vec![];
+    for record in [eth, mc_hdlc, ipv4, pad, atm] {
+        record.write_to(&mut data).unwrap();
+    }
+
+    data
+}
+
+#[test]
+fn parse_records() {
+    let data = capture();
+
+    let records = ErfReader::new(&data[..]).into_records().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 5);
+
+    let eth = &records[0];
+    assert_eq!(eth.record_type, ErfType::ETH);
+    assert_eq!(eth.timestamp.seconds(), 1);
+    assert_eq!(eth.timestamp.fraction(), 1 << 31);
+    assert_eq!(eth.timestamp.to_duration(), Duration::from_millis(1500));
+    assert_eq!(ErfTimestamp::from_duration(Duration::from_millis(1500)), Some(eth.timestamp));
+    assert!(eth.is_varying_length());
+    assert_eq!(eth.record_len(), 28);
+    assert_eq!(eth.packet(), &[1, 2, 3, 4, 5, 6]);
+
+    let mc_hdlc = &records[1];
+    assert_eq!(mc_hdlc.interface(), 1);
+    assert_eq!(mc_hdlc.type_header.connection(), Some(0x123));
+    assert_eq!(mc_hdlc.packet(), &[0x0F, 0x00, 0x08, 0x00]);
+
+    let ipv4 = &records[2];
+    assert_eq!(ipv4.extension_headers.len(), 1);
+    assert_eq!(ipv4.host_id(), Some((2, 0xABCD)));
+    assert_eq!(ipv4.packet(), &[0x45, 0, 0, 4]);
+
+    // A record length smaller than the header is invalid
+    let mut invalid = data.clone();
+    invalid[10..12].copy_from_slice(&8_u16.to_be_bytes());
+    assert!(matches!(ErfRecord::from_slice(&invalid), Err(PcapError::InvalidField(_))));
+    assert!(matches!(ErfRecord::from_slice(&data[..20]), Err(PcapError::IncompleteBuffer(12, 4))));
+}
+
+#[test]
+fn convert_to_pcapng() {
+    let data = capture();
+    let pcapng = erf_to_pcapng(&mut ErfReader::new(&data[..]), Vec::new()).unwrap().into_inner();
+
+    let mut pcapng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let mut interfaces = vec![];
+    let mut packets = vec![];
+    while let Some(block) = pcapng_reader.next_block() {
+        match block.unwrap() {
+            Block::InterfaceDescription(interface) => interfaces.push(interface.into_owned()),
+            Block::EnhancedPacket(packet) => packets.push(packet.into_owned()),
+            _ => {},
+        }
+    }
+
+    let linktypes: Vec<_> = interfaces.iter().map(|interface| interface.linktype).collect();
+    assert_eq!(linktypes, [DataLink::ETHERNET, DataLink::C_HDLC, DataLink::IPV4, DataLink::ERF]);
+    assert!(interfaces[0].options.contains(&InterfaceDescriptionOption::IfFcsLen(4)));
+    assert_eq!(interfaces[1].options[0], InterfaceDescriptionOption::IfName(Cow::Borrowed("Port B")));
+    assert!(!interfaces[1].options.contains(&InterfaceDescriptionOption::IfFcsLen(4)));
+    assert!(interfaces[2].options.contains(&InterfaceDescriptionOption::IfDescription(Cow::Borrowed("Host 00000000abcd, source 2"))));
+
+    // The padding record is dropped
+    assert_eq!(packets.len(), 4);
+    assert_eq!(packets[0].timestamp, Duration::from_millis(1500));
+    assert_eq!(&packets[0].data[..], &[1, 2, 3, 4, 5, 6]);
+    assert_eq!(packets[0].options, vec![EnhancedPacketOption::DropCount(3)]);
+    assert_eq!(packets[2].interface_id, 2);
+
+    // The ATM record is kept whole
+    assert_eq!(packets[3].interface_id, 3);
+    assert_eq!(packets[3].data.len(), 20);
+    assert_eq!(&packets[3].data[16..], &[9, 9, 9, 9]);
+}
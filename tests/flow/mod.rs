@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::dissect::TcpHeader;
+use pcap_file::flow::{FiveTuple, FlowDirection, FlowTable};
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapReader, PcapWriter};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+use pcap_file::DataLink;
+
+
+/// Builds an IPv4 packet with a minimal transport header.
+fn ipv4(src: [u8; 4], dst: [u8; 4], protocol: u8, ports: (u16, u16), tcp_flags: u8) -> Vec<u8> {
+    let mut data = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, protocol, 0, 0];
+    data.extend(src);
+    data.extend(dst);
+    data.extend(ports.0.to_be_bytes());
+    data.extend(ports.1.to_be_bytes());
+    match protocol {
+        6 => data.extend([0, 0, 0, 0, 0, 0, 0, 0, 0x50, tcp_flags, 0, 0, 0, 0, 0, 0]),
+        _ => data.extend([0, 8, 0, 0]),
+    }
+    let len = data.len() as u16;
+    data[2..4].copy_from_slice(&len.to_be_bytes());
+    data
+}
+
+#[test]
+fn pcapng_bidirectional_flows() {
+    let (client, server) = ([10, 0, 0, 1], [10, 0, 0, 2]);
+    let ethernet = |data: Vec<u8>| [vec![0; 12], vec![0x08, 0x00], data].concat();
+
+    let packets: Vec<(u32, u64, Vec<u8>)> = vec![
+        (0, 1, ethernet(ipv4(client, server, 6, (40000, 80), TcpHeader::SYN))),
+        (1, 2, ipv4(server, client, 6, (80, 40000), TcpHeader::SYN | TcpHeader::ACK)),
+        (0, 3, ethernet(ipv4(client, server, 6, (40000, 80), TcpHeader::ACK))),
+        (0, 4, ethernet(ipv4(client, server, 17, (5353, 53), 0))),
+        (0, 5, [vec![0; 12], vec![0x08, 0x06], vec![0; 28]].concat()),
+    ];
+
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::RAW, 0)).unwrap();
+    for (interface_id, secs, data) in &packets {
+        let packet = EnhancedPacketBlock {
+            interface_id: *interface_id,
+            timestamp: Duration::from_secs(*secs),
+            original_len: data.len() as u32 + 10,
+            data: Cow::Borrowed(data),
+            options: vec![],
+        };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+    let pcapng = writer.into_inner();
+
+    let table = FlowTable::from_pcapng_reader(&mut PcapNgReader::new(&pcapng[..]).unwrap()).unwrap();
+    assert_eq!((table.len(), table.skipped_packets()), (2, 1));
+
+    let tcp = &table.flows()[0];
+    assert_eq!(tcp.tuple, FiveTuple { src: client.into(), src_port: 40000, dst: server.into(), dst_port: 80, protocol: 6 });
+    assert_eq!((tcp.first_timestamp, tcp.duration()), (Duration::from_secs(1), Duration::from_secs(2)));
+    assert_eq!(tcp.forward, FlowDirection { packets: 2, bytes: 2 * 64, tcp_flags: TcpHeader::SYN | TcpHeader::ACK });
+    assert_eq!(tcp.backward, FlowDirection { packets: 1, bytes: 50, tcp_flags: TcpHeader::SYN | TcpHeader::ACK });
+    assert_eq!(tcp.interfaces, vec![0, 1]);
+
+    // Flows can be looked up in both directions
+    assert_eq!(table.get(&tcp.tuple.reversed()), Some(tcp));
+
+    let flows: Vec<_> = table.into_iter().collect();
+    assert_eq!((flows[1].tuple.protocol, flows[1].tuple.dst_port, flows[1].forward.packets), (17, 53, 1));
+}
+
+#[test]
+fn pcap_flows() {
+    let header = PcapHeader { datalink: DataLink::RAW, ..Default::default() };
+    let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+    for (secs, src, dst) in [(10, [1, 1, 1, 1], [2, 2, 2, 2]), (12, [2, 2, 2, 2], [1, 1, 1, 1]), (11, [3, 3, 3, 3], [1, 1, 1, 1])] {
+        let data = ipv4(src, dst, 1, (0x0800, 0), 0);
+        writer.write_packet(&PcapPacket::new(Duration::from_secs(secs), data.len() as u32, &data[..]).unwrap()).unwrap();
+    }
+    let pcap = writer.into_writer();
+
+    let table = FlowTable::from_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    let summary: Vec<_> = table.iter().map(|flow| (flow.forward.packets, flow.backward.packets, flow.last_timestamp.as_secs())).collect();
+    assert_eq!(summary, vec![(1, 1, 12), (1, 0, 11)]);
+    assert_eq!(table.flows()[0].tuple.src_port, 0);
+}
@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::info::CaptureInfo;
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapReader, PcapWriter, RawPcapPacket};
+use pcap_file::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::interface_statistics::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+use pcap_file::DataLink;
+
+
+#[test]
+fn pcap_info() {
+    let header = PcapHeader { snaplen: 10, ..Default::default() };
+    let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+    for (secs, len) in [(10, 4_u32), (12, 10), (11, 6)] {
+        writer.write_packet(&PcapPacket::new(Duration::from_secs(secs), 20, vec![0; len as usize]).unwrap()).unwrap();
+    }
+    let pcap = writer.into_writer();
+
+    let info = CaptureInfo::from_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+
+    assert_eq!(info.packets, 3);
+    assert_eq!(info.captured_bytes, 20);
+    assert_eq!(info.original_bytes, 60);
+    assert_eq!(info.duration(), Some(Duration::from_secs(2)));
+    assert_eq!((info.min_packet_size, info.max_packet_size), (Some(4), Some(10)));
+    assert_eq!(info.average_packet_size(), Some(20.0 / 3.0));
+    assert_eq!(info.data_byte_rate(), Some(10.0));
+    assert_eq!(info.packet_rate(), Some(1.5));
+    assert_eq!(info.truncated_packets, 3);
+    assert_eq!(info.snaplen_violations, 0);
+    assert!(!info.strict_time_order);
+    assert_eq!(info.encapsulations(), vec![DataLink::ETHERNET]);
+
+    // The packets exceeding the snaplen are counted instead of failing the read
+    let header = PcapHeader { snaplen: 4, ..Default::default() };
+    let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+    let packet = RawPcapPacket { ts_sec: 1, ts_frac: 0, incl_len: 10, orig_len: 10, data: Cow::Owned(vec![0; 10]) };
+    writer.write_raw_packet(&packet).unwrap();
+    let pcap = writer.into_writer();
+
+    let info = CaptureInfo::from_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    assert_eq!((info.packets, info.snaplen_violations), (1, 1));
+}
+
+#[test]
+fn pcapng_info_interfaces() {
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+
+    let mut eth0 = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 4);
+    eth0.options.push(InterfaceDescriptionOption::IfName(Cow::Borrowed("eth0")));
+    writer.write_pcapng_block(eth0).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::RAW, 0)).unwrap();
+
+    for (interface_id, secs, len) in [(0, 1, 4_usize), (1, 2, 8), (0, 3, 6)] {
+        let packet = EnhancedPacketBlock {
+            interface_id,
+            timestamp: Duration::from_secs(secs),
+            original_len: len as u32,
+            data: Cow::Owned(vec![0; len]),
+            options: vec![EnhancedPacketOption::DropCount(1)],
+        };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+
+    let isb = InterfaceStatisticsBlock {
+        interface_id: 0,
+        timestamp: Duration::from_secs(3),
+        options: vec![InterfaceStatisticsOption::IsbIfDrop(5), InterfaceStatisticsOption::IsbOsDrop(2)],
+    };
+    writer.write_pcapng_block(isb).unwrap();
+    let pcapng = writer.into_inner();
+
+    let mut info = CaptureInfo::from_pcapng_reader(&mut PcapNgReader::new(&pcapng[..]).unwrap()).unwrap();
+
+    assert_eq!(info.packets, 3);
+    assert_eq!(info.captured_bytes, 18);
+    assert!(info.strict_time_order);
+    assert_eq!(info.snaplen_violations, 1);
+    assert_eq!(info.encapsulations(), vec![DataLink::ETHERNET, DataLink::RAW]);
+    assert_eq!(info.total_drops(), 10);
+
+    let eth0 = &info.interfaces[0];
+    assert_eq!(eth0.name.as_deref(), Some("eth0"));
+    assert_eq!(eth0.packets, 2);
+    assert_eq!((eth0.first_timestamp, eth0.last_timestamp), (Some(Duration::from_secs(1)), Some(Duration::from_secs(3))));
+    assert_eq!((eth0.packet_drops, eth0.if_drop, eth0.os_drop), (2, Some(5), Some(2)));
+
+    let raw = &info.interfaces[1];
+    assert_eq!((raw.packets, raw.captured_bytes, raw.if_drop), (1, 8, None));
+
+    // The counters of a corrupt capture saturate
+    info.interfaces[1].if_drop = Some(u64::MAX);
+    assert_eq!(info.total_drops(), u64::MAX);
+}
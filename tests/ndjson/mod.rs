@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::ndjson::{ndjson_to_pcapng, pcapng_to_ndjson, NdjsonBlock};
+use pcap_file::pcapng::blocks::custom::CustomBlock;
+use pcap_file::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::interface_statistics::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use pcap_file::pcapng::blocks::opt_common::{CommonOption, UnknownOption};
+use pcap_file::pcapng::blocks::section_header::{SectionHeaderBlock, SectionHeaderOption};
+use pcap_file::pcapng::blocks::unknown::UnknownBlock;
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use pcap_file::{DataLink, Endianness};
+
+
+/// Writes a PcapNg with most kinds of blocks and options, and the offset of its last packet.
+fn pcapng(endianness: Endianness) -> (Vec<u8>, usize) {
+    let section = SectionHeaderBlock {
+        endianness,
+        options: vec![SectionHeaderOption::UserApplication(Cow::Borrowed("tests"))],
+        ..Default::default()
+    };
+    let mut writer = PcapNgWriter::with_section_header(vec![], section).unwrap();
+
+    let interface = InterfaceDescriptionBlock {
+        linktype: DataLink::ETHERNET,
+        snaplen: 0xFFFF,
+        options: vec![InterfaceDescriptionOption::IfName(Cow::Borrowed("eth0")), InterfaceDescriptionOption::IfTsResol(9)],
+    };
+    writer.write_pcapng_block(interface).unwrap();
+
+    let packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: Duration::new(1704187433, 103553001),
+        original_len: 100,
+        data: Cow::Borrowed(&[0x01, 0x02, 0x03, 0x04, 0x05]),
+        options: vec![
+            EnhancedPacketOption::Flags(1),
+            EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("hello"))),
+            EnhancedPacketOption::Common(CommonOption::Unknown(UnknownOption::new(0x1234, &[0xFF]))),
+        ],
+    };
+    writer.write_pcapng_block(packet).unwrap();
+
+    writer.write_pcapng_block(CustomBlock::<true> { pen: 32473, payload: Cow::Borrowed(b"custom") }).unwrap();
+    writer.write_pcapng_block(UnknownBlock::new(0x1234, 12, &[0xAA; 8])).unwrap();
+
+    let statistics = InterfaceStatisticsBlock {
+        interface_id: 0,
+        timestamp: Duration::new(1704187434, 0),
+        options: vec![InterfaceStatisticsOption::IsbIfDrop(3)],
+    };
+    writer.write_pcapng_block(statistics).unwrap();
+
+    let offset = writer.get_ref().len();
+    let packet = EnhancedPacketBlock { interface_id: 0, data: Cow::Borrowed(&[0x01, 0x02, 0x03]), original_len: 3, ..Default::default() };
+    writer.write_pcapng_block(packet).unwrap();
+
+    (writer.into_inner(), offset)
+}
+
+#[test]
+fn round_trip() {
+    for endianness in [Endianness::Big, Endianness::Little] {
+        let (mut pcapng, offset) = pcapng(endianness);
+
+        let ndjson = pcapng_to_ndjson(&pcapng[..], vec![]).unwrap();
+        let ndjson = String::from_utf8(ndjson).unwrap();
+        assert_eq!(ndjson.lines().count(), 7);
+        assert!(!ndjson.contains("\"raw\""));
+
+        let lines = ndjson.lines().map(|line| serde_json::from_str::<NdjsonBlock>(line).unwrap()).collect::<Vec<_>>();
+        assert!(matches!(lines[3].block, Some(Block::CustomCopiable(_))));
+        assert!(matches!(lines[4].block, Some(Block::Unknown(_))));
+
+        assert_eq!(ndjson_to_pcapng(ndjson.as_bytes(), vec![]).unwrap(), pcapng);
+
+        // A non-zero padding is kept in the raw block
+        pcapng[offset + 31] = 0xEE;
+        let ndjson = String::from_utf8(pcapng_to_ndjson(&pcapng[..], vec![]).unwrap()).unwrap();
+        let last = serde_json::from_str::<NdjsonBlock>(ndjson.lines().last().unwrap()).unwrap();
+        assert!(last.block.is_some() && last.raw.is_some());
+
+        assert_eq!(ndjson_to_pcapng(ndjson.as_bytes(), vec![]).unwrap(), pcapng);
+    }
+}
+
+#[test]
+fn edit() {
+    let (pcapng, _) = pcapng(Endianness::Little);
+    let ndjson = String::from_utf8(pcapng_to_ndjson(&pcapng[..], vec![]).unwrap()).unwrap();
+
+    // Edit the comment and drop the custom block
+    let edited = ndjson.replace("\"hello\"", "\"world\"").lines().filter(|line| !line.contains("CustomCopiable")).collect::<Vec<_>>().join("\n");
+    let pcapng = ndjson_to_pcapng(edited.as_bytes(), vec![]).unwrap();
+
+    let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let mut blocks = vec![];
+    while let Some(block) = reader.next_block() {
+        blocks.push(block.unwrap().into_owned());
+    }
+    assert_eq!(blocks.len(), 5);
+
+    let packet = blocks[1].as_enhanced_packet().unwrap();
+    assert_eq!(packet.timestamp, Duration::new(1704187433, 103553001));
+    assert!(packet.options.contains(&EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("world")))));
+
+    assert!(ndjson_to_pcapng("{}".as_bytes(), vec![]).is_err());
+    assert!(ndjson_to_pcapng("not json".as_bytes(), vec![]).is_err());
+}
@@ -0,0 +1,101 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use pcap_file::netmon::{NetmonFrameInfo, NetmonReader};
+use pcap_file::{DataLink, PcapError};
+
+
+/// 2024-01-02 03:04:05.678 UTC
+const START_TIME: Duration = Duration::from_millis(1704164645678);
+
+/// Writes a NetMon capture of the given minor version, each frame being its offset from the start of the capture,
+/// its data and its trailer.
+fn capture(version_minor: u8, frames: &[(u64, &[u8], &[u8])]) -> Vec<u8> {
+    let mut data = vec![0_u8; 72];
+    data[..4].copy_from_slice(b"GMBU");
+    data[4] = version_minor;
+    data[5] = 2;
+    data[6..8].copy_from_slice(&1_u16.to_le_bytes());
+    for (i, field) in [2024_u16, 1, 2, 2, 3, 4, 5, 678].iter().enumerate() {
+        data[8 + 2 * i..10 + 2 * i].copy_from_slice(&field.to_le_bytes());
+    }
+
+    let mut offsets = vec![];
+    for (ts_delta, frame, trailer) in frames {
+        offsets.push(data.len() as u32);
+        data.extend_from_slice(&ts_delta.to_le_bytes());
+        data.extend_from_slice(&(frame.len() as u32 + 4).to_le_bytes());
+        data.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        data.extend_from_slice(frame);
+        data.extend_from_slice(trailer);
+    }
+
+    let table_offset = data.len() as u32;
+    data[24..28].copy_from_slice(&table_offset.to_le_bytes());
+    data[28..32].copy_from_slice(&(4 * offsets.len() as u32).to_le_bytes());
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    data
+}
+
+#[test]
+fn read_version_2_0() {
+    let data = capture(0, &[(0, &[1, 2, 3], &[]), (1_500_000, &[4, 5], &[])]);
+
+    let reader = NetmonReader::new(Cursor::new(data)).unwrap();
+    assert_eq!(reader.header().start_time, START_TIME);
+    assert_eq!(reader.header().datalink(), Some(DataLink::ETHERNET));
+    assert_eq!(reader.frame_count(), 2);
+
+    let packets = reader.into_packets().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets[0].timestamp, START_TIME);
+    assert_eq!(packets[1].timestamp, START_TIME + Duration::from_millis(1500));
+    assert_eq!(&packets[1].data[..], &[4, 5]);
+    assert_eq!(packets[1].orig_len, 6);
+    assert_eq!(packets[1].info, NetmonFrameInfo::default());
+
+    let pcap_packet = packets[1].as_pcap_packet().unwrap();
+    assert_eq!(pcap_packet.data(), &[4, 5]);
+    assert_eq!(pcap_packet.timestamp(), packets[1].timestamp);
+
+    let mut invalid = capture(0, &[]);
+    invalid[5] = 1;
+    assert!(matches!(NetmonReader::new(Cursor::new(invalid)), Err(PcapError::InvalidField(_))));
+}
+
+#[test]
+fn read_version_2_3_trailer() {
+    // Ethernet frame with process 7, 2024-01-02 00:00:00.000000100 UTC, time zone 4
+    let mut ethernet = vec![1, 0, 7, 0, 0, 0];
+    ethernet.extend_from_slice(&(116_444_736_000_000_000_u64 + 17_041_536_000_000_001).to_le_bytes());
+    ethernet.push(4);
+
+    // Raw IPv4 frame stored with a pcap link type
+    let mut raw_ip = (0xE000_u16 + 228).to_le_bytes().to_vec();
+    raw_ip.extend_from_slice(&[0; 13]);
+
+    // Network info metadata frame
+    let mut network_info = 0xFFFD_u16.to_le_bytes().to_vec();
+    network_info.extend_from_slice(&[0; 13]);
+
+    let data = capture(3, &[(0, &[0xAA; 14], &ethernet), (10, &[0x45; 20], &raw_ip), (20, b"info", &network_info)]);
+
+    let mut reader = NetmonReader::new(Cursor::new(data)).unwrap();
+
+    let packet = reader.next_packet().unwrap().unwrap();
+    assert_eq!(packet.datalink(), Some(DataLink::ETHERNET));
+    assert_eq!(packet.timestamp, Duration::new(1704153600, 100));
+    assert_eq!(packet.info, NetmonFrameInfo { process_info_index: Some(7), utc_timestamp: Some(133486272000000001), timezone_index: Some(4) });
+    assert_eq!(packet.data.len(), 14);
+
+    let packet = reader.next_packet().unwrap().unwrap();
+    assert_eq!(packet.datalink(), Some(DataLink::IPV4));
+
+    let packet = reader.next_packet().unwrap().unwrap();
+    assert_eq!(packet.datalink(), None);
+    assert_eq!(&packet.data[..], b"info");
+
+    assert!(reader.next_packet().is_none());
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::reorder::{find_out_of_order_pcapng, OutOfOrderPacket, PcapNgReorder, ReorderMode};
+use pcap_file::DataLink;
+
+use crate::{build_pcapng, enhanced_packet, temp_dir};
+
+
+/// Builds a pcapng with two interfaces, the second one with a millisecond resolution, and the packets `(interface_id, millis)`.
+fn two_interfaces_pcapng(packets: &[(u32, u64)]) -> Vec<u8> {
+    let mut millis = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0);
+    millis.options.push(InterfaceDescriptionOption::IfTsResol(3));
+
+    let mut blocks = vec![Block::InterfaceDescription(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)), Block::InterfaceDescription(millis)];
+    for (seq, &(interface_id, ts)) in packets.iter().enumerate() {
+        blocks.push(Block::EnhancedPacket(enhanced_packet(interface_id, Duration::from_millis(ts), &[seq as u8])));
+    }
+    build_pcapng(blocks)
+}
+
+/// Returns the `(millis, data)` of the packets and `None` for the other blocks.
+fn summarize(blocks: impl Iterator<Item = Block<'static>>) -> Vec<Option<(u64, u8)>> {
+    blocks
+        .map(|block| block.into_enhanced_packet().map(|p| (p.timestamp.as_millis() as u64, p.data[0])))
+        .collect()
+}
+
+#[test]
+fn detect_out_of_order() {
+    let pcapng = two_interfaces_pcapng(&[(0, 10), (1, 30), (0, 20), (1, 40), (0, 40), (1, 5)]);
+    let out_of_order = find_out_of_order_pcapng(&mut PcapNgReader::new(&pcapng[..]).unwrap()).unwrap();
+
+    let expected = vec![
+        OutOfOrderPacket { index: 2, timestamp: Duration::from_millis(20), max_timestamp: Duration::from_millis(30) },
+        OutOfOrderPacket { index: 5, timestamp: Duration::from_millis(5), max_timestamp: Duration::from_millis(40) },
+    ];
+    assert_eq!(out_of_order, expected);
+}
+
+#[test]
+fn reorder_window() {
+    let pcapng = two_interfaces_pcapng(&[(0, 30), (1, 10), (0, 20), (1, 20), (0, 50), (0, 40), (1, 1)]);
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+
+    let mut reorder = PcapNgReorder::new(blocks, ReorderMode::Window(2));
+    let out = summarize(reorder.by_ref().map(Result::unwrap));
+
+    // The interfaces are kept first, equal timestamps keep their order, the last packet is too late for the window
+    let expected = vec![None, None, Some((10, 1)), Some((20, 2)), Some((20, 3)), Some((30, 0)), Some((1, 6)), Some((40, 5)), Some((50, 4))];
+    assert_eq!(out, expected);
+    assert_eq!(reorder.late_packets(), 1);
+}
+
+#[test]
+fn reorder_external() {
+    let dir = temp_dir("reorder-external");
+    let packets: Vec<(u32, u64)> = (0..50).map(|i| (i % 2, (i as u64 * 37) % 50)).collect();
+    let pcapng = two_interfaces_pcapng(&packets);
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+
+    let mode = ReorderMode::External { chunk_size: 7, temp_dir: dir.clone() };
+    let mut reorder = PcapNgReorder::new(blocks, mode);
+    let out = summarize(reorder.by_ref().map(Result::unwrap));
+
+    let timestamps: Vec<u64> = out.iter().flatten().map(|(ts, _)| *ts).collect();
+    assert_eq!(out[..2], [None, None]);
+    assert_eq!(timestamps, (0..50).collect::<Vec<_>>());
+    assert_eq!(reorder.late_packets(), 0);
+
+    // The temporary files are deleted after the merge
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
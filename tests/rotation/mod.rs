@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::time::Duration;
+
+use pcap_file::pcap::{PcapPacket, PcapReader, RotatingPcapWriter};
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::{Block, PcapNgReader, RotatingPcapNgWriter};
+use pcap_file::{DataLink, RotationPolicy};
+
+use crate::{enhanced_packet, temp_dir};
+
+
+#[test]
+fn pcap_rotation_by_size_keeps_max_files() {
+    let dir = temp_dir("pcap-rotation");
+    let data = [0_u8; 100];
+
+    // Header (24) + 2 packets (2 * 116) >= 250, so each file holds 2 packets
+    let policy = RotationPolicy { max_file_size: Some(250), max_files: Some(2), ..Default::default() };
+    let mut writer = RotatingPcapWriter::new(dir.join("out.pcap"), policy).unwrap();
+
+    for i in 0..10 {
+        let pkt = PcapPacket::new(Duration::from_secs(i), 100, &data[..]).unwrap();
+        writer.write_packet(&pkt).unwrap();
+    }
+
+    let files = writer.close().unwrap();
+    assert_eq!(files, vec![dir.join("out_00004.pcap"), dir.join("out_00005.pcap")]);
+    assert!(!dir.join("out_00001.pcap").exists());
+
+    let mut reader = PcapReader::new(File::open(&files[0]).unwrap()).unwrap();
+    let mut timestamps = vec![];
+    while let Some(pkt) = reader.next_packet() {
+        timestamps.push(pkt.unwrap().timestamp().as_secs());
+    }
+    assert_eq!(timestamps, vec![6, 7]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn pcapng_rotation_by_duration_carries_interfaces() {
+    let dir = temp_dir("pcapng-rotation");
+
+    let policy = RotationPolicy { max_file_duration: Some(Duration::from_secs(5)), ..Default::default() };
+    let mut writer = RotatingPcapNgWriter::new(dir.join("out.pcapng"), policy).unwrap();
+    writer.set_write_statistics(true);
+
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0xFFFF)).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::RAW, 0xFFFF)).unwrap();
+
+    for i in 0..10 {
+        writer.write_pcapng_block(enhanced_packet((i % 2) as u32, Duration::from_secs(100 + i), &[0; 10])).unwrap();
+    }
+
+    let files = writer.close().unwrap();
+    assert_eq!(files.len(), 2);
+
+    let mut reader = PcapNgReader::new(File::open(&files[1]).unwrap()).unwrap();
+    let mut packets = 0;
+    let mut statistics = 0;
+    while let Some(block) = reader.next_block() {
+        match block.unwrap() {
+            Block::EnhancedPacket(_) => packets += 1,
+            Block::InterfaceStatistics(_) => statistics += 1,
+            _ => {},
+        }
+    }
+
+    assert_eq!(packets, 5);
+    assert_eq!(statistics, 2);
+    assert_eq!(reader.interfaces().len(), 2);
+    assert_eq!(reader.interfaces()[1].linktype, DataLink::RAW);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn pcapng_rotation_ignores_simple_packets_timestamps() {
+    let dir = temp_dir("pcapng-rotation-spb");
+
+    let policy = RotationPolicy { max_file_duration: Some(Duration::from_secs(5)), ..Default::default() };
+    let mut writer = RotatingPcapNgWriter::new(dir.join("out.pcapng"), policy).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0xFFFF)).unwrap();
+
+    // The simple packet has no timestamp, the duration of the file starts at the first enhanced packet
+    writer.write_pcapng_block(SimplePacketBlock { original_len: 4, data: Cow::Borrowed(&[0; 4]) }).unwrap();
+    for secs in [100, 101] {
+        writer.write_pcapng_block(enhanced_packet(0, Duration::from_secs(secs), &[0; 4])).unwrap();
+    }
+
+    assert_eq!(writer.close().unwrap().len(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::pcapng::blocks::custom::CustomBlock;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::opt_common::{CommonOption, CustomUtf8Option};
+use pcap_file::pcapng::blocks::section_header::{SectionHeaderBlock, SectionHeaderOption};
+use pcap_file::pcapng::blocks::systemd_journal_export::SystemdJournalExportBlock;
+use pcap_file::pcapng::blocks::unknown::UnknownBlock;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::scrub::{PcapNgScrub, PenSelection, ScrubAction, ScrubPolicy};
+use pcap_file::DataLink;
+
+use crate::{build_pcapng, enhanced_packet};
+
+
+/// Writes a capture with metadata in all the supported places.
+fn capture() -> Vec<u8> {
+    let section = SectionHeaderBlock {
+        options: vec![
+            SectionHeaderOption::Hardware(Cow::Borrowed("x86_64")),
+            SectionHeaderOption::OS(Cow::Borrowed("Linux 6.1")),
+            SectionHeaderOption::UserApplication(Cow::Borrowed("dumpcap")),
+            SectionHeaderOption::Common(CommonOption::Comment(Cow::Borrowed("captured at home"))),
+        ],
+        ..Default::default()
+    };
+
+    let mut interface = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0);
+    interface.options.push(InterfaceDescriptionOption::IfName(Cow::Borrowed("enp0s31f6")));
+    interface.options.push(InterfaceDescriptionOption::IfDescription(Cow::Borrowed("Intel I219")));
+    interface.options.push(InterfaceDescriptionOption::IfTsResol(9));
+
+    let custom_option = |pen| CommonOption::CustomUtf8Copiable(CustomUtf8Option { pen, value: Cow::Borrowed("secret") });
+    let mut packet = enhanced_packet(0, Duration::ZERO, &[1, 2, 3, 4]);
+    packet.options = vec![
+        EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("suspicious"))),
+        EnhancedPacketOption::Common(custom_option(32473)),
+        EnhancedPacketOption::Common(custom_option(1234)),
+        EnhancedPacketOption::Flags(1),
+    ];
+
+    build_pcapng([
+        Block::SectionHeader(section),
+        Block::InterfaceDescription(interface),
+        Block::EnhancedPacket(packet),
+        Block::CustomCopiable(CustomBlock { pen: 32473, payload: Cow::Borrowed(&[0; 4]) }),
+        Block::SystemdJournalExport(SystemdJournalExportBlock { journal_entry: Cow::Borrowed(b"MESSAGE=hello\n\0\0") }),
+        Block::Unknown(UnknownBlock::new(0x0000000A, 20, &[0x4B, 0x53, 0x4C, 0x54, 0, 0, 0, 0])),
+    ])
+}
+
+#[test]
+fn default_policy_removes_everything() {
+    let pcapng = capture();
+    let reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let blocks: Vec<Block> = PcapNgScrub::from_reader(reader, ScrubPolicy::default()).map(Result::unwrap).collect();
+    assert_eq!(blocks.len(), 3);
+
+    assert!(blocks[0].as_section_header().unwrap().options.is_empty());
+    assert_eq!(blocks[1].as_interface_description().unwrap().options, vec![InterfaceDescriptionOption::IfTsResol(9)]);
+    assert_eq!(blocks[2].as_enhanced_packet().unwrap().options, vec![EnhancedPacketOption::Flags(1)]);
+}
+
+#[test]
+fn targeted_policy() {
+    let policy = ScrubPolicy {
+        shb_hardware: ScrubAction::Remove,
+        if_name: ScrubAction::Replace("eth0".to_string()),
+        comments: ScrubAction::Replace("redacted".to_string()),
+        custom: PenSelection::Pens(vec![32473]),
+        remove_decryption_secrets: true,
+        ..ScrubPolicy::keep_all()
+    };
+
+    let pcapng = capture();
+    let reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let mut blocks = PcapNgScrub::from_reader(reader, policy);
+    let section = blocks.next().unwrap().unwrap().into_section_header().unwrap();
+    assert_eq!(section.options, vec![
+        SectionHeaderOption::OS(Cow::Borrowed("Linux 6.1")),
+        SectionHeaderOption::UserApplication(Cow::Borrowed("dumpcap")),
+        SectionHeaderOption::Common(CommonOption::Comment(Cow::Borrowed("redacted"))),
+    ]);
+
+    let blocks: Vec<Block> = blocks.map(Result::unwrap).collect();
+    assert_eq!(blocks.len(), 3);
+
+    let options = &blocks[0].as_interface_description().unwrap().options;
+    assert_eq!(options[0], InterfaceDescriptionOption::IfName(Cow::Borrowed("eth0")));
+    assert_eq!(options[1], InterfaceDescriptionOption::IfDescription(Cow::Borrowed("Intel I219")));
+
+    let options = &blocks[1].as_enhanced_packet().unwrap().options;
+    assert_eq!(options.len(), 3);
+    assert_eq!(options[0], EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("redacted"))));
+    assert!(matches!(&options[1], EnhancedPacketOption::Common(CommonOption::CustomUtf8Copiable(o)) if o.pen == 1234));
+
+    // The systemd journal is kept
+    assert!(blocks[2].as_systemd_journal_export().is_some());
+}
@@ -0,0 +1,70 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::pcap::{PcapHeader, PcapPacket};
+use pcap_file::pcapng::blocks::custom::CustomBlock;
+use pcap_file::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption, TsResolution};
+use pcap_file::pcapng::blocks::opt_common::{CommonOption, UnknownOption};
+use pcap_file::pcapng::Block;
+use pcap_file::DataLink;
+use serde_json::json;
+
+
+#[test]
+fn serde_pcap() {
+    let header = PcapHeader { datalink: DataLink::LINUX_SLL2, ..Default::default() };
+    let json = serde_json::to_string(&header).unwrap();
+    assert_eq!(serde_json::from_str::<PcapHeader>(&json).unwrap(), header);
+
+    let packet = PcapPacket::new(Duration::new(1, 2), 10, &[0xDE, 0xAD][..]).unwrap();
+    let value = serde_json::to_value(&packet).unwrap();
+    assert_eq!(value, json!({ "timestamp": { "secs": 1, "nanos": 2 }, "orig_len": 10, "data": "dead" }));
+
+    let packet: PcapPacket = serde_json::from_value(value).unwrap();
+    assert_eq!(packet.data(), [0xDE, 0xAD]);
+
+    // The deserialized packets are validated
+    let invalid = json!({ "timestamp": { "secs": 1, "nanos": 2 }, "orig_len": 1, "data": "dead" });
+    assert!(serde_json::from_value::<PcapPacket>(invalid).is_err());
+    let invalid = json!({ "timestamp": { "secs": 1, "nanos": 2 }, "orig_len": 10, "data": "dea" });
+    assert!(serde_json::from_value::<PcapPacket>(invalid).is_err());
+
+    // And so are the timestamp resolutions
+    assert_eq!(serde_json::from_str::<TsResolution>("9").unwrap(), TsResolution::NANO);
+    assert!(serde_json::from_str::<TsResolution>("200").is_err());
+}
+
+#[test]
+fn serde_blocks() {
+    let blocks = vec![
+        Block::InterfaceDescription(InterfaceDescriptionBlock {
+            linktype: DataLink::ETHERNET,
+            snaplen: 0,
+            options: vec![
+                InterfaceDescriptionOption::IfName(Cow::Borrowed("eth0")),
+                InterfaceDescriptionOption::IfMacAddr(Cow::Borrowed(&[0x0A, 0x02, 0x02, 0x02, 0x02, 0x01])),
+                InterfaceDescriptionOption::IfTsResol(9),
+            ],
+        }),
+        Block::EnhancedPacket(EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: Duration::new(1704187433, 103553000),
+            original_len: 60,
+            data: Cow::Borrowed(&[0x01, 0x02, 0x03]),
+            options: vec![
+                EnhancedPacketOption::Flags(1),
+                EnhancedPacketOption::Common(CommonOption::Comment(Cow::Borrowed("hello"))),
+                EnhancedPacketOption::Common(CommonOption::Unknown(UnknownOption::new(0x1234, &[0xFF]))),
+            ],
+        }),
+        Block::CustomCopiable(CustomBlock { pen: 32473, payload: Cow::Borrowed(b"payload") }),
+    ];
+
+    let json = serde_json::to_string(&blocks).unwrap();
+    assert!(json.contains(r#""data":"010203""#));
+    assert!(json.contains(r#"{"IfMacAddr":"0a0202020201"}"#));
+
+    let deserialized: Vec<Block> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, blocks);
+}
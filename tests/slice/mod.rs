@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use pcap_file::pcap::{PcapReader, PcapWriter};
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::name_resolution::NameResolutionBlock;
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use pcap_file::slice::{PacketSlice, PcapNgSlice, PcapSlice, slice_pcap, slice_pcapng};
+use pcap_file::DataLink;
+
+use crate::{build_pcap, build_pcapng, enhanced_packet};
+
+
+/// Builds a pcap with one packet per second, starting at 100s.
+fn seconds_pcap(nb_packets: u64) -> Vec<u8> {
+    build_pcap(DataLink::ETHERNET, (0..nb_packets).map(|i| (Duration::from_secs(100 + i), [i as u8; 4])))
+}
+
+/// Builds a pcapng with an IDB, then one packet per second starting at 100s, with a NRB in the middle.
+fn seconds_pcapng(nb_packets: u64) -> Vec<u8> {
+    let mut blocks = vec![Block::InterfaceDescription(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0xFFFF))];
+    for i in 0..nb_packets {
+        if i == nb_packets / 2 {
+            blocks.push(Block::NameResolution(NameResolutionBlock { records: vec![], options: vec![] }));
+        }
+        blocks.push(Block::EnhancedPacket(enhanced_packet(0, Duration::from_secs(100 + i), &[i as u8; 4])));
+    }
+    build_pcapng(blocks)
+}
+
+#[test]
+fn pcap_time_window() {
+    let data = seconds_pcap(10);
+    let reader = PcapReader::new(&data[..]).unwrap();
+
+    let slice = PacketSlice::around(Duration::from_secs(105), Duration::from_secs(2), Duration::from_secs(2));
+    let timestamps: Vec<u64> = PcapSlice::new(reader.into_packets(), slice).map(|pkt| pkt.unwrap().timestamp().as_secs()).collect();
+
+    assert_eq!(timestamps, vec![103, 104, 105, 106]);
+}
+
+#[test]
+fn pcap_index_range_helper() {
+    let data = seconds_pcap(10);
+    let mut reader = PcapReader::new(&data[..]).unwrap();
+    let mut writer = PcapWriter::with_header(Vec::new(), reader.header()).unwrap();
+
+    let written = slice_pcap(&mut reader, &mut writer, &PacketSlice::indexes(7..20)).unwrap();
+    assert_eq!(written, 3);
+
+    let out = writer.into_writer();
+    let packets: Vec<u8> = PcapReader::new(&out[..]).unwrap().into_packets().map(|pkt| pkt.unwrap().data()[0]).collect();
+    assert_eq!(packets, vec![7, 8, 9]);
+}
+
+#[test]
+fn pcapng_slice_keeps_non_packet_blocks() {
+    let data = seconds_pcapng(10);
+    let reader = PcapNgReader::new(&data[..]).unwrap();
+
+    let slice = PacketSlice { sorted: true, ..PacketSlice::between(Duration::from_secs(102), Duration::from_secs(107)) };
+    let blocks: Vec<Block> = PcapNgSlice::new(reader.into_blocks(), slice).map(Result::unwrap).collect();
+
+    assert!(matches!(blocks[0], Block::InterfaceDescription(_)));
+    assert!(matches!(blocks[4], Block::NameResolution(_)));
+    assert_eq!(blocks.len(), 7);
+
+    let timestamps: Vec<u64> = blocks.iter().filter_map(|blk| blk.as_enhanced_packet()).map(|pkt| pkt.timestamp.as_secs()).collect();
+    assert_eq!(timestamps, vec![102, 103, 104, 105, 106]);
+}
+
+#[test]
+fn pcapng_slice_helper_output_is_valid() {
+    let data = seconds_pcapng(10);
+    let mut reader = PcapNgReader::new(&data[..]).unwrap();
+    let mut writer = PcapNgWriter::with_section_header(Vec::new(), reader.section().clone()).unwrap();
+
+    let written = slice_pcapng(&mut reader, &mut writer, &PacketSlice::indexes(8..10)).unwrap();
+    assert_eq!(written, 2);
+
+    let out = writer.into_inner();
+    let blocks: Vec<Block> = PcapNgReader::new(&out[..]).unwrap().into_blocks().map(Result::unwrap).collect();
+    assert_eq!(blocks.len(), 4);
+    assert_eq!(blocks[3].as_enhanced_packet().unwrap().data[0], 9);
+}
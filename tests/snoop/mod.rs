@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use pcap_file::convert::snoop_to_pcapng;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::PcapNgReader;
+use pcap_file::snoop::{SnoopDataLink, SnoopHeader, SnoopPacket, SnoopParser, SnoopReader, SnoopWriter};
+use pcap_file::{DataLink, PcapError};
+
+
+/// Writes a snoop with packets whose lengths need padding.
+fn capture() -> Vec<u8> {
+    let mut writer = SnoopWriter::new(Vec::new()).unwrap();
+
+    let packets = [
+        SnoopPacket::new(Duration::new(1, 500_000), 5, 0, &[1, 2, 3, 4, 5][..]).unwrap(),
+        SnoopPacket::new(Duration::new(2, 0), 100, 2, &[6, 7, 8, 9][..]).unwrap(),
+        SnoopPacket::new(Duration::new(3, 250_000_000), 10, 5, &[10][..]).unwrap(),
+    ];
+    for packet in &packets {
+        writer.write_packet(packet).unwrap();
+    }
+
+    writer.into_writer()
+}
+
+#[test]
+fn roundtrip() {
+    let data = capture();
+    assert_eq!(&data[..8], b"snoop\0\0\0");
+    assert_eq!(data.len(), 16 + 32 + 28 + 28);
+
+    let reader = SnoopReader::new(&data[..]).unwrap();
+    assert_eq!(reader.header(), SnoopHeader { version: 2, datalink: SnoopDataLink::ETHERNET });
+
+    let packets = reader.into_packets().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 3);
+    assert_eq!(packets[0].timestamp(), Duration::new(1, 500_000));
+    assert_eq!(packets[0].data(), &[1, 2, 3, 4, 5]);
+    assert_eq!(packets[1].orig_len(), 100);
+    assert_eq!(packets[1].cumulative_drops(), 2);
+    assert_eq!(packets[2].data(), &[10]);
+
+    // The parser needs the whole padded record
+    let (rem, parser) = SnoopParser::new(&data[..]).unwrap();
+    assert!(matches!(parser.next_packet(&rem[..30]), Err(PcapError::IncompleteBuffer(8, 6))));
+
+    let mut bad_magic = data.clone();
+    bad_magic[0] = b'S';
+    assert!(matches!(SnoopReader::new(&bad_magic[..]), Err(PcapError::InvalidField(_))));
+}
+
+#[test]
+fn convert_to_pcapng() {
+    let data = capture();
+    let mut reader = SnoopReader::new(&data[..]).unwrap();
+    let pcapng = snoop_to_pcapng(&mut reader, Vec::new()).unwrap().into_inner();
+
+    let mut pcapng_reader = PcapNgReader::new(&pcapng[..]).unwrap();
+    let mut packets = vec![];
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block.unwrap();
+        if let Some(interface) = block.as_interface_description() {
+            assert_eq!(interface.linktype, DataLink::ETHERNET);
+        }
+        if let Some(packet) = block.as_enhanced_packet() {
+            packets.push(packet.clone().into_owned());
+        }
+    }
+
+    assert_eq!(packets.len(), 3);
+    assert_eq!(packets[2].timestamp, Duration::new(3, 250_000_000));
+    assert_eq!(packets[2].original_len, 10);
+    assert!(packets[0].options.is_empty());
+    assert_eq!(packets[1].options, vec![EnhancedPacketOption::DropCount(2)]);
+    assert_eq!(packets[2].options, vec![EnhancedPacketOption::DropCount(3)]);
+
+    // Token bus has no pcap equivalent
+    let mut writer = SnoopWriter::with_header(Vec::new(), SnoopHeader { version: 2, datalink: SnoopDataLink::IEEE802_4 }).unwrap();
+    writer.write_packet(&SnoopPacket::new(Duration::ZERO, 1, 0, &[0][..]).unwrap()).unwrap();
+    let data = writer.into_writer();
+    let mut reader = SnoopReader::new(&data[..]).unwrap();
+    assert!(matches!(snoop_to_pcapng(&mut reader, Vec::new()), Err(PcapError::InvalidField(_))));
+}
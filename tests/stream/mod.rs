@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use pcap_file::dissect::TcpHeader;
+use pcap_file::flow::FiveTuple;
+use pcap_file::pcap::PcapReader;
+use pcap_file::stream::{Direction, StreamCollector, StreamConfig, StreamFileWriter, TcpReassembler};
+use pcap_file::DataLink;
+
+use crate::{build_pcap, temp_dir};
+
+
+const CLIENT: [u8; 4] = [10, 0, 0, 1];
+const SERVER: [u8; 4] = [10, 0, 0, 2];
+
+/// Builds an IPv4 + TCP segment, from the client if `from_client`.
+fn segment(from_client: bool, seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let (src, dst, ports) = match from_client {
+        true => (CLIENT, SERVER, [0xC0, 0x00, 0x00, 0x50]),
+        false => (SERVER, CLIENT, [0x00, 0x50, 0xC0, 0x00]),
+    };
+
+    let mut data = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, 6, 0, 0];
+    data.extend(src);
+    data.extend(dst);
+    data.extend(ports);
+    data.extend(seq.to_be_bytes());
+    data.extend([0, 0, 0, 0, 0x50, flags, 0xFF, 0xFF, 0, 0, 0, 0]);
+    data.extend(payload);
+    let len = data.len() as u16;
+    data[2..4].copy_from_slice(&len.to_be_bytes());
+    data
+}
+
+/// Writes segments into a RAW pcap.
+fn segments_pcap(segments: &[Vec<u8>]) -> Vec<u8> {
+    build_pcap(DataLink::RAW, segments.iter().map(|data| (Duration::ZERO, data)))
+}
+
+fn client_tuple() -> FiveTuple {
+    FiveTuple { src: CLIENT.into(), src_port: 0xC000, dst: SERVER.into(), dst_port: 80, protocol: 6 }
+}
+
+#[test]
+fn reorder_retransmission_and_overlap() {
+    const SYN: u8 = TcpHeader::SYN;
+    const ACK: u8 = TcpHeader::ACK;
+    const FIN: u8 = TcpHeader::FIN | TcpHeader::ACK;
+
+    // The client ISN is 1000, the server one u32::MAX - 2 to wrap
+    let isn = u32::MAX - 2;
+    let pcap = segments_pcap(&[
+        segment(true, 1000, SYN, b""),
+        segment(false, isn, SYN | ACK, b""),
+        segment(true, 1001, ACK, b"GET "),
+        segment(true, 1011, ACK, b" HTTP/1.1"),
+        segment(true, 1005, ACK, b"/ind"),
+        segment(true, 1001, ACK, b"GET "),
+        segment(true, 1007, ACK, b"ndex"),
+        segment(false, isn.wrapping_add(1), ACK, b"HTTP/1.1 "),
+        segment(false, isn.wrapping_add(10), FIN, b"200 OK"),
+        segment(true, 1020, FIN, b""),
+    ]);
+
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), StreamConfig::default());
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    assert_eq!(reassembler.open_streams(), 0);
+
+    let collector = reassembler.finish().unwrap();
+    let [(tuple, stream)] = collector.streams() else { panic!("There should be a single stream") };
+    assert_eq!(*tuple, client_tuple());
+    assert_eq!(stream.client_data, b"GET /index HTTP/1.1");
+    assert_eq!(stream.server_data, b"HTTP/1.1 200 OK");
+    assert!(stream.client_gaps.is_empty() && stream.closed);
+}
+
+#[test]
+fn retransmission_before_offset() {
+    // The retransmission starts before the first byte seen and overlaps the delivered data
+    let pcap = segments_pcap(&[
+        segment(true, 1000, TcpHeader::ACK, b"0123456789"),
+        segment(true, 990, TcpHeader::ACK, b"xxxxxxxxxx0123456789abcdefghij"),
+    ]);
+
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), StreamConfig::default());
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    let collector = reassembler.finish().unwrap();
+    let (_, stream) = &collector.streams()[0];
+    assert_eq!(stream.client_data, b"0123456789abcdefghij");
+    assert!(stream.client_gaps.is_empty());
+}
+
+#[test]
+fn missing_data_and_files() {
+    // No handshake was captured and 4 bytes of the client are missing
+    let pcap = segments_pcap(&[
+        segment(true, 5000, TcpHeader::ACK, b"abcd"),
+        segment(true, 5008, TcpHeader::ACK, b"ijkl"),
+        segment(false, 7000, TcpHeader::ACK, b"hello"),
+    ]);
+
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), StreamConfig::default());
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    let collector = reassembler.finish().unwrap();
+    let (_, stream) = &collector.streams()[0];
+    assert_eq!(stream.client_data, b"abcdijkl");
+    assert_eq!(stream.client_gaps, vec![4..8]);
+
+    // A small buffer gives up waiting for the missing data
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), StreamConfig { max_buffered: 2, ..Default::default() });
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    assert_eq!(reassembler.handler().streams()[0].1.client_gaps, vec![4..8]);
+
+    let dir = temp_dir("stream");
+    let mut reassembler = TcpReassembler::new(StreamFileWriter::new(&dir), StreamConfig::default());
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    let writer = reassembler.finish().unwrap();
+
+    let client_path = writer.path(&client_tuple(), Direction::ClientToServer);
+    assert_eq!(client_path, dir.join("10.0.0.1.49152-10.0.0.2.80"));
+    assert_eq!(std::fs::read(client_path).unwrap(), b"abcd\0\0\0\0ijkl");
+    assert_eq!(std::fs::read(writer.path(&client_tuple(), Direction::ServerToClient)).unwrap(), b"hello");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn window_and_stream_limits() {
+    // A segment far outside the receive window is dropped instead of creating a huge gap
+    let pcap = segments_pcap(&[
+        segment(true, 5000, TcpHeader::ACK, b"abcd"),
+        segment(true, 5004 + (1 << 30), TcpHeader::ACK, b"efgh"),
+        segment(true, 5004, TcpHeader::ACK, b"ijkl"),
+    ]);
+
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), StreamConfig::default());
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    let collector = reassembler.finish().unwrap();
+    let (_, stream) = &collector.streams()[0];
+    assert_eq!(stream.client_data, b"abcdijkl");
+    assert!(stream.client_gaps.is_empty());
+
+    // The least recently active stream is closed when there are too many
+    let mut second = segment(true, 9000, TcpHeader::ACK, b"mnop");
+    second[20..22].copy_from_slice(&0xC001_u16.to_be_bytes());
+    let pcap = segments_pcap(&[segment(true, 5000, TcpHeader::ACK, b"abcd"), second]);
+
+    let config = StreamConfig { max_streams: 1, ..Default::default() };
+    let mut reassembler = TcpReassembler::new(StreamCollector::new(), config);
+    reassembler.process_pcap_reader(&mut PcapReader::new(&pcap[..]).unwrap()).unwrap();
+    assert_eq!(reassembler.open_streams(), 1);
+
+    let streams = reassembler.handler().streams();
+    assert_eq!(streams.len(), 2);
+    assert_eq!((&streams[0].1.client_data[..], streams[0].1.closed), (&b"abcd"[..], true));
+    assert_eq!((&streams[1].1.client_data[..], streams[1].1.closed), (&b"mnop"[..], false));
+}
@@ -1,11 +1,96 @@
 #![allow(clippy::unreadable_literal)]
 
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::{fs::File, time::Duration};
 
-use pcap_file::pcapng::{PcapNgReader, blocks::interface_description::InterfaceDescriptionOption};
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter, blocks::interface_description::InterfaceDescriptionOption};
+use pcap_file::DataLink;
 
+#[cfg(feature = "anonymize")]
+mod anonymize;
+mod btsnoop;
+mod candump;
+#[cfg(feature = "parquet")]
+mod columnar;
+mod convert;
+mod dedup;
+mod defrag;
+mod dissect;
+mod dump;
+mod erf;
+mod flow;
+mod info;
+#[cfg(feature = "ndjson")]
+mod ndjson;
+mod netmon;
 mod pcap;
 mod pcapng;
+mod reorder;
+mod rotation;
+mod scrub;
+#[cfg(feature = "serde")]
+mod serde;
+mod slice;
+mod snoop;
+mod stream;
+mod text2pcap;
+mod timeshift;
+mod truncate;
+
+
+/// Computes the one's complement sum of some bytes.
+fn ones_complement_sum(chunks: &[&[u8]]) -> u16 {
+    let mut sum = 0_u32;
+    for chunk in chunks {
+        for word in chunk.chunks(2) {
+            sum += u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)]) as u32;
+        }
+    }
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum as u16
+}
+
+/// Creates an empty temporary directory for the test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pcap-file-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Builds a pcap of the given link type with the packets `(timestamp, data)`.
+fn build_pcap<D: AsRef<[u8]>>(datalink: DataLink, packets: impl IntoIterator<Item = (Duration, D)>) -> Vec<u8> {
+    let header = PcapHeader { datalink, ..Default::default() };
+    let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+    for (ts, data) in packets {
+        let data = data.as_ref();
+        writer.write_packet(&PcapPacket::new(ts, data.len() as u32, data).unwrap()).unwrap();
+    }
+    writer.into_writer()
+}
+
+/// Builds a pcapng with the blocks, a leading section header replacing the default one.
+fn build_pcapng<'a>(blocks: impl IntoIterator<Item = Block<'a>>) -> Vec<u8> {
+    let mut blocks = blocks.into_iter().peekable();
+    let mut writer = match blocks.next_if(|block| matches!(block, Block::SectionHeader(_))) {
+        Some(Block::SectionHeader(section)) => PcapNgWriter::with_section_header(Vec::new(), section).unwrap(),
+        _ => PcapNgWriter::new(Vec::new()).unwrap(),
+    };
+    for block in blocks {
+        writer.write_block(&block).unwrap();
+    }
+    writer.into_inner()
+}
+
+/// Builds an EPB capturing the whole `data`, without options.
+fn enhanced_packet(interface_id: u32, timestamp: Duration, data: &[u8]) -> EnhancedPacketBlock<'static> {
+    EnhancedPacketBlock { interface_id, timestamp, original_len: data.len() as u32, data: Cow::Owned(data.to_vec()), options: vec![] }
+}
 
 
 /// Test that the timestamp resolution is correctly read and set in the packets.
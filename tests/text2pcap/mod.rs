@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use pcap_file::dissect::{PacketHeaders, TransportHeader};
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketOption;
+use pcap_file::pcapng::{Block, PcapNgReader};
+use pcap_file::text2pcap::{hex_to_pcap, hex_to_pcapng, DummyHeaders, HexDumpReader, PacketDirection, Text2PcapConfig};
+use pcap_file::{DataLink, TsResolution};
+
+
+const WIRESHARK_DUMP: &str = "\
+# Exported by Wireshark
+I 2024-01-02 03:04:05.678 0000  00 11 22 33 44 55 66 77 88 99 aa bb cc dd ee ff   ..\"3DUfw........
+0010  01 02                                              ..
+
+O 2024-01-02 03:04:06.5 0000  ab cd                      ..
+";
+
+const OD_DUMP: &str = "\
+0000000 00 11 22 33 44 55 66 77
+0000008 88 99
+0000000 aa bb cc dd
+0000004
+";
+
+#[test]
+fn read_dumps() {
+    let reader = HexDumpReader::with_timestamp_format(WIRESHARK_DUMP.as_bytes(), "%Y-%m-%d %T.%f");
+    let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 2);
+
+    assert_eq!(packets[0].timestamp, Some(Duration::new(1704164645, 678000000)));
+    assert_eq!(packets[0].direction, Some(PacketDirection::Inbound));
+    assert_eq!(packets[0].data, [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF, 0x01, 0x02]);
+
+    assert_eq!(packets[1].timestamp, Some(Duration::new(1704164646, 500000000)));
+    assert_eq!(packets[1].direction, Some(PacketDirection::Outbound));
+    assert_eq!(packets[1].data, [0xAB, 0xCD]);
+
+    let packets = HexDumpReader::new(OD_DUMP.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0].data.len(), 10);
+    assert_eq!(packets[1].data, [0xAA, 0xBB, 0xCC, 0xDD]);
+    assert!(packets.iter().all(|packet| packet.timestamp.is_none() && packet.direction.is_none()));
+
+    // A line whose offset is after the end of the packet is skipped
+    let reader = HexDumpReader::new("0000 00 11\n0004 22 33\ncafe is not an offset\n0002 44\n".as_bytes());
+    let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].data, [0x00, 0x11, 0x44]);
+
+    // pcap of the raw packets, timestamps 1 µs apart
+    let config = Text2PcapConfig { datalink: DataLink::RAW, start_time: Duration::from_secs(10), ..Default::default() };
+    let pcap = hex_to_pcap(OD_DUMP.as_bytes(), vec![], &config).unwrap().into_writer();
+
+    let mut reader = PcapReader::new(&pcap[..]).unwrap();
+    assert_eq!(reader.header().datalink, DataLink::RAW);
+    assert_eq!(reader.header().ts_resolution, TsResolution::MicroSecond);
+
+    let first = reader.next_packet().unwrap().unwrap();
+    assert_eq!(first.timestamp(), Duration::from_secs(10));
+    assert_eq!(first.data().len(), 10);
+    let second = reader.next_packet().unwrap().unwrap();
+    assert_eq!(second.timestamp(), Duration::new(10, 1000));
+    assert!(reader.next_packet().is_none());
+}
+
+#[test]
+fn dummy_headers() {
+    for headers in [DummyHeaders::Udp { src_port: 1000, dst_port: 2000 }, DummyHeaders::Tcp { src_port: 1000, dst_port: 2000 }] {
+        for dst_ip in ["2.2.2.2", "::2"] {
+            let src_ip = if dst_ip == "::2" { "::1" } else { "1.1.1.1" };
+            let config = Text2PcapConfig {
+                headers,
+                src_ip: src_ip.parse().unwrap(),
+                dst_ip: dst_ip.parse().unwrap(),
+                timestamp_format: Some("%s.%f".to_string()),
+                ..Default::default()
+            };
+
+            let dump = "O 1700000000.5 0000 01 02 03\nI 1700000001.0 0000 04 05 06 07\n0004 08\n";
+            let pcapng = hex_to_pcapng(dump.as_bytes(), vec![], &config).unwrap().into_inner();
+
+            let mut reader = PcapNgReader::new(&pcapng[..]).unwrap();
+            let mut packets = vec![];
+            while let Some(block) = reader.next_block() {
+                if let Block::EnhancedPacket(packet) = block.unwrap() {
+                    packets.push(packet.into_owned());
+                }
+            }
+            assert_eq!(packets.len(), 2);
+            assert_eq!(packets[0].timestamp, Duration::new(1700000000, 500000000));
+            assert_eq!(packets[0].options, [EnhancedPacketOption::Flags(0x02)]);
+            assert_eq!(packets[1].options, [EnhancedPacketOption::Flags(0x01)]);
+
+            let mut seqs = vec![];
+            for (packet, (payload, src_port)) in packets.iter().zip([(&[1, 2, 3][..], 1000), (&[4, 5, 6, 7, 8], 2000)]) {
+                let headers = PacketHeaders::parse(DataLink::ETHERNET, &packet.data).unwrap();
+                let ip = headers.ip.unwrap();
+                let transport = headers.transport.unwrap();
+
+                assert_eq!(transport.src_port(), Some(src_port));
+                assert_eq!(transport.payload(), payload);
+                assert_eq!(transport.checksum_valid(&ip), Some(true));
+                if let TransportHeader::Tcp(tcp) = transport {
+                    seqs.push(tcp.seq());
+                }
+            }
+
+            // The inbound packet is the reply, its addresses are swapped
+            let first = PacketHeaders::parse(DataLink::ETHERNET, &packets[0].data).unwrap().ip.unwrap();
+            let second = PacketHeaders::parse(DataLink::ETHERNET, &packets[1].data).unwrap().ip.unwrap();
+            assert_eq!((first.src(), first.dst()), (second.dst(), second.src()));
+            assert_eq!(first.src(), config.src_ip);
+
+            if matches!(headers, DummyHeaders::Tcp { .. }) {
+                assert_eq!(seqs, [0, 0]);
+            }
+        }
+    }
+
+    // Addresses of different versions are rejected
+    let config = Text2PcapConfig { headers: DummyHeaders::Ip { protocol: 17 }, dst_ip: "::2".parse().unwrap(), ..Default::default() };
+    assert!(hex_to_pcapng("0000 00\n".as_bytes(), vec![], &config).is_err());
+}
@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::pcap::{PcapPacket, PcapReader, PcapWriter};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::blocks::interface_statistics::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+use pcap_file::timeshift::{PcapNgTimeShift, PcapTimeShift, TimeOffset, TimeShift};
+use pcap_file::DataLink;
+
+
+#[test]
+fn time_offset_sum() {
+    let forward = TimeOffset::forward(Duration::from_secs(3));
+    let backward = TimeOffset::backward(Duration::from_secs(5));
+
+    assert_eq!(forward + backward, TimeOffset::backward(Duration::from_secs(2)));
+    assert_eq!(backward + forward + forward, TimeOffset::forward(Duration::from_secs(1)));
+    assert_eq!(TimeOffset::between(Duration::from_secs(10), Duration::from_secs(4)), TimeOffset::backward(Duration::from_secs(6)));
+    assert!(backward.apply(Duration::from_secs(4)).is_err());
+}
+
+#[test]
+fn pcap_rebase() {
+    let mut writer = PcapWriter::new(Vec::new()).unwrap();
+    for secs in [100, 102, 101] {
+        writer.write_packet(&PcapPacket::new(Duration::from_secs(secs), 0, &[][..]).unwrap()).unwrap();
+    }
+    let pcap = writer.into_writer();
+
+    let packets = PcapReader::new(&pcap[..]).unwrap().into_packets();
+    let timestamps: Vec<u64> = PcapTimeShift::new(packets, TimeShift::rebase(Duration::from_secs(10)))
+        .map(|p| p.unwrap().timestamp().as_secs())
+        .collect();
+
+    assert_eq!(timestamps, vec![10, 12, 11]);
+
+    let packets = PcapReader::new(&pcap[..]).unwrap().into_packets();
+    let res: Result<Vec<_>, _> = PcapTimeShift::new(packets, TimeShift::shift(TimeOffset::backward(Duration::from_secs(101)))).collect();
+    assert!(res.is_err());
+}
+
+#[test]
+fn pcapng_interface_offsets_and_ts_offset() {
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)).unwrap();
+    let mut with_offset = InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0);
+    with_offset.options.push(InterfaceDescriptionOption::IfTsOffset(2000));
+    writer.write_pcapng_block(with_offset).unwrap();
+
+    for (interface_id, secs) in [(0, 2000), (1, 2100)] {
+        let packet = EnhancedPacketBlock { interface_id, timestamp: Duration::from_secs(secs), original_len: 0, data: Cow::Borrowed(&[]), options: vec![] };
+        writer.write_pcapng_block(packet).unwrap();
+    }
+
+    let isb = InterfaceStatisticsBlock {
+        interface_id: 1,
+        timestamp: Duration::from_secs(2101),
+        options: vec![InterfaceStatisticsOption::IsbStartTime(Duration::from_secs(2100))],
+    };
+    writer.write_pcapng_block(isb).unwrap();
+    let pcapng = writer.into_inner();
+
+    // Interface 1 is 0.5 s early, then the whole capture starts at 100 s
+    let shift = TimeShift {
+        rebase: Some(Duration::from_secs(100)),
+        interface_offsets: vec![TimeOffset::ZERO, TimeOffset::backward(Duration::from_millis(500))],
+        ..Default::default()
+    };
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let blocks: Vec<_> = PcapNgTimeShift::new(blocks, shift).map(Result::unwrap).collect();
+
+    // The global offset is -1900 s, the if_tsoffset of interface 1 is shifted by -1901 s
+    assert_eq!(blocks[1].as_interface_description().unwrap().options, vec![InterfaceDescriptionOption::IfTsOffset(99)]);
+    assert_eq!(blocks[2].as_enhanced_packet().unwrap().timestamp, Duration::from_secs(100));
+    assert_eq!(blocks[3].as_enhanced_packet().unwrap().timestamp, Duration::from_millis(199_500));
+
+    let isb = blocks[4].as_interface_statistics().unwrap();
+    assert_eq!(isb.timestamp, Duration::from_millis(200_500));
+    assert_eq!(isb.options, vec![InterfaceStatisticsOption::IsbStartTime(Duration::from_millis(199_500))]);
+
+    // The shifted blocks can be written back
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    for block in &blocks {
+        writer.write_block(block).unwrap();
+    }
+}
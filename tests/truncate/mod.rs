@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pcap_file::pcap::{PcapHeader, PcapPacket};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionBlock;
+use pcap_file::pcapng::blocks::simple_packet::SimplePacketBlock;
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use pcap_file::truncate::{PcapNgTruncate, PcapTruncate, Truncation};
+use pcap_file::DataLink;
+
+
+/// Ethernet + 802.1Q + IPv4 (with 4 bytes of options) + TCP (with 12 bytes of options) + 10 bytes of payload
+fn ethernet_vlan_ipv4_tcp() -> Vec<u8> {
+    let mut data = vec![0; 12];
+    data.extend([0x81, 0x00, 0x00, 0x01, 0x08, 0x00]);
+    data.extend([0x46, 0, 0, 0, 0, 0, 0, 0, 64, 6]);
+    data.extend([0; 14]);
+    data.extend([0; 12]);
+    data.extend([0x80, 0]);
+    data.extend([0; 18]);
+    data.extend([0xAA; 10]);
+    data
+}
+
+/// IPv6 + hop-by-hop options + UDP + 4 bytes of payload
+fn raw_ipv6_udp() -> Vec<u8> {
+    let mut data = vec![0x60, 0, 0, 0, 0, 0, 0, 64];
+    data.extend([0; 32]);
+    data.extend([17, 0, 0, 0, 0, 0, 0, 0]);
+    data.extend([0; 8]);
+    data.extend([0xAA; 4]);
+    data
+}
+
+#[test]
+fn headers_only_length() {
+    let truncation = Truncation { snaplen: None, headers_only: true };
+
+    assert_eq!(truncation.captured_len(DataLink::ETHERNET, &ethernet_vlan_ipv4_tcp()), 18 + 24 + 32);
+    assert_eq!(truncation.captured_len(DataLink::RAW, &raw_ipv6_udp()), 40 + 8 + 8);
+    assert_eq!(truncation.captured_len(DataLink::BLUETOOTH_HCI_H4, &[0; 10]), 0);
+
+    let truncation = Truncation { snaplen: Some(20), headers_only: true };
+    assert_eq!(truncation.captured_len(DataLink::ETHERNET, &ethernet_vlan_ipv4_tcp()), 20);
+}
+
+#[test]
+fn pcap_snaplen() {
+    let truncation = Truncation::snaplen(16);
+    assert_eq!(truncation.pcap_header(PcapHeader::default()).snaplen, 16);
+    assert_eq!(truncation.pcap_header(PcapHeader { snaplen: 8, ..Default::default() }).snaplen, 8);
+
+    let packets = vec![
+        Ok(PcapPacket::new(Duration::ZERO, 100, vec![1; 30]).unwrap()),
+        Ok(PcapPacket::new(Duration::ZERO, 100, vec![1; 10]).unwrap()),
+    ];
+    let lens: Vec<(u32, u32)> = PcapTruncate::new(packets.into_iter(), DataLink::ETHERNET, truncation)
+        .map(|p| p.unwrap())
+        .map(|p| (p.len(), p.orig_len()))
+        .collect();
+
+    assert_eq!(lens, vec![(16, 100), (10, 100)]);
+}
+
+#[test]
+fn pcapng_headers_only() {
+    let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+    writer.write_pcapng_block(InterfaceDescriptionBlock::new(DataLink::ETHERNET, 0)).unwrap();
+
+    let data = ethernet_vlan_ipv4_tcp();
+    let packet = EnhancedPacketBlock { interface_id: 0, timestamp: Duration::from_secs(5), original_len: 1500, data: Cow::Borrowed(&data), options: vec![] };
+    writer.write_pcapng_block(packet).unwrap();
+    writer.write_pcapng_block(SimplePacketBlock { original_len: data.len() as u32, data: Cow::Borrowed(&data) }).unwrap();
+    let pcapng = writer.into_inner();
+
+    let truncation = Truncation { snaplen: Some(1000), headers_only: true };
+    let blocks = PcapNgReader::new(&pcapng[..]).unwrap().into_blocks();
+    let blocks: Vec<Block> = PcapNgTruncate::new(blocks, truncation).map(Result::unwrap).collect();
+
+    assert_eq!(blocks[0].as_interface_description().unwrap().snaplen, 1000);
+
+    let epb = blocks[1].as_enhanced_packet().unwrap();
+    assert_eq!((epb.data.len(), epb.original_len), (74, 1500));
+
+    // The simple packet block is converted to keep its truncated length
+    let spb = blocks[2].as_enhanced_packet().unwrap();
+    assert_eq!((spb.data.len(), spb.original_len, spb.timestamp), (74, data.len() as u32, Duration::from_secs(5)));
+}